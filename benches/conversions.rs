@@ -12,7 +12,7 @@ fn main() {
 #[divan::bench(types = [i16, u16, f32])]
 fn from_i16_to<T: rodio::Sample + FromSample<i16>>(bencher: Bencher) {
     bencher
-        .with_inputs(|| TestSource::music_wav())
+        .with_inputs(TestSource::music_wav)
         .bench_values(|source| {
             source
                 .convert_samples::<T>()
@@ -13,7 +13,7 @@ fn main() {
 #[divan::bench]
 fn reverb(bencher: Bencher) {
     bencher
-        .with_inputs(|| TestSource::music_wav())
+        .with_inputs(TestSource::music_wav)
         .bench_values(|source| {
             source
                 .buffered()
@@ -26,13 +26,13 @@ fn reverb(bencher: Bencher) {
 fn high_pass(bencher: Bencher) {
     bencher
         .with_inputs(|| TestSource::music_wav().to_f32s())
-        .bench_values(|source| source.high_pass(200).for_each(divan::black_box_drop))
+        .bench_values(|source| source.high_pass(200.0).for_each(divan::black_box_drop))
 }
 
 #[divan::bench]
 fn fade_out(bencher: Bencher) {
     bencher
-        .with_inputs(|| TestSource::music_wav())
+        .with_inputs(TestSource::music_wav)
         .bench_values(|source| {
             source
                 .fade_out(Duration::from_secs(5))
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use divan::Bencher;
+use rodio::buffer::SamplesBuffer;
+use rodio::Source;
+
+mod shared;
+use shared::TestSource;
+
+fn main() {
+    divan::main();
+}
+
+#[divan::bench]
+fn add(bencher: Bencher) {
+    bencher
+        .with_inputs(|| {
+            let source = TestSource::music_wav().to_f32s();
+            let (controller, mixer) =
+                rodio::dynamic_mixer::mixer::<f32>(source.channels(), source.sample_rate());
+            controller.add(source);
+            mixer
+        })
+        .bench_values(|mixer| mixer.for_each(divan::black_box_drop))
+}
+
+#[divan::bench]
+fn add_direct(bencher: Bencher) {
+    bencher
+        .with_inputs(|| {
+            let source = TestSource::music_wav().to_f32s();
+            let (controller, mixer) =
+                rodio::dynamic_mixer::mixer::<f32>(source.channels(), source.sample_rate());
+            assert!(controller.add_direct(source).is_ok());
+            mixer
+        })
+        .bench_values(|mixer| mixer.for_each(divan::black_box_drop))
+}
+
+/// The slowest iterations show how long the audio thread waits while sources are added.
+#[divan::bench]
+fn next_while_adding(bencher: Bencher) {
+    let (controller, mut mixer) = rodio::dynamic_mixer::mixer::<f32>(2, 44100);
+    // Keeps the mix from ending between iterations.
+    controller.add(rodio::source::Zero::<f32>::new(2, 44100));
+    let stop = Arc::new(AtomicBool::new(false));
+    let adder = {
+        let stop = stop.clone();
+        let controller = controller.clone();
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                controller.add(SamplesBuffer::new(2, 44100, vec![0.1f32; 64]));
+                thread::sleep(Duration::from_micros(20));
+            }
+        })
+    };
+
+    // One callback buffer of a device.
+    bencher.bench_local(|| {
+        for _ in 0..512 {
+            divan::black_box(mixer.next());
+        }
+    });
+
+    stop.store(true, Ordering::Relaxed);
+    adder.join().unwrap();
+}
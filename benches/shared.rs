@@ -62,6 +62,7 @@ impl TestSource<i16> {
     }
 
     #[allow(unused, reason = "not everything from shared is used in all libs")]
+    #[allow(clippy::wrong_self_convention)]
     pub fn to_f32s(self) -> TestSource<f32> {
         let TestSource {
             samples,
@@ -70,7 +71,7 @@ impl TestSource<i16> {
             total_duration,
         } = self;
         let samples = samples
-            .map(|s| cpal::Sample::from_sample(s))
+            .map(cpal::Sample::from_sample)
             .collect::<Vec<_>>()
             .into_iter();
         TestSource {
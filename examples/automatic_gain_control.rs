@@ -1,5 +1,6 @@
 use rodio::source::Source;
 use rodio::Decoder;
+use rodio::OutputStreamTrait;
 use std::fs::File;
 use std::io::BufReader;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -3,8 +3,6 @@ use rodio::Source;
 use rodio::OutputStreamTrait;
 
 use rodio::source::FadeDirection;
-use std::sync::mpsc::channel;
-use std::sync::Arc;
 
 fn main() {
     let (_stream, handle) = rodio::OutputStream::try_default().unwrap();
@@ -44,4 +44,9 @@ fn main() {
     pipeline.set_state(gst::State::Playing).unwrap();
     sink.sleep_until_end();
     pipeline.set_state(gst::State::Null).unwrap();
+    println!(
+        "underruns: {}, overruns: {}",
+        _stream.underruns(),
+        _stream.overruns()
+    );
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use rodio::OutputStreamTrait;
+use rodio::Source;
+use std::time::Duration;
+
+fn main() {
+    let (_stream, handle) = rodio::OutputStream::try_default().unwrap();
+    let sink = rodio::Sink::try_new(&handle).unwrap();
+
+    // Plays the microphone back through a low pass filter for ten seconds.
+    let (_input, microphone) = rodio::input_stream().unwrap();
+    sink.append(microphone.low_pass(2000.0).amplify(2.0));
+
+    std::thread::sleep(Duration::from_secs(10));
+}
@@ -3,6 +3,7 @@
 #[cfg(feature = "noise")]
 fn main() {
     use rodio::source::{pink, white, Source};
+    use rodio::OutputStreamTrait;
     use std::thread;
     use std::time::Duration;
 
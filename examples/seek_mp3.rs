@@ -1,3 +1,4 @@
+use rodio::OutputStreamTrait;
 use std::io::BufReader;
 use std::time::Duration;
 
@@ -2,6 +2,7 @@
 
 fn main() {
     use rodio::source::{chirp, Function, SignalGenerator, Source};
+    use rodio::OutputStreamTrait;
     use std::thread;
     use std::time::Duration;
 
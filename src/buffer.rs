@@ -180,11 +180,8 @@ mod tests {
         fn channel_order_stays_correct() {
             const SAMPLE_RATE: u32 = 100;
             const CHANNELS: u16 = 2;
-            let mut buf = SamplesBuffer::new(
-                CHANNELS,
-                SAMPLE_RATE,
-                (0..2000i16).into_iter().collect::<Vec<_>>(),
-            );
+            let mut buf =
+                SamplesBuffer::new(CHANNELS, SAMPLE_RATE, (0..2000i16).collect::<Vec<_>>());
             buf.try_seek(Duration::from_secs(5)).unwrap();
             assert_eq!(
                 buf.next(),
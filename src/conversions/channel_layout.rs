@@ -0,0 +1,152 @@
+/// Speaker position of a single channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelPosition {
+    /// The only channel of a mono sound.
+    Mono,
+    /// Front left speaker.
+    FrontLeft,
+    /// Front right speaker.
+    FrontRight,
+    /// Front center speaker.
+    FrontCenter,
+    /// Subwoofer.
+    LowFrequency,
+    /// Back (rear) left speaker.
+    BackLeft,
+    /// Back (rear) right speaker.
+    BackRight,
+    /// Side left speaker.
+    SideLeft,
+    /// Side right speaker.
+    SideRight,
+}
+
+/// Describes which speaker each interleaved channel belongs to.
+///
+/// The channel orders follow the WAVE/SMPTE convention used by most devices and file formats:
+///
+/// | Layout        | Channels                           |
+/// |---------------|------------------------------------|
+/// | `Mono`        | M                                  |
+/// | `Stereo`      | FL, FR                             |
+/// | `Surround2_1` | FL, FR, LFE                        |
+/// | `Surround3_0` | FL, FR, C                          |
+/// | `Quad`        | FL, FR, BL, BR                     |
+/// | `Surround5_0` | FL, FR, C, BL, BR                  |
+/// | `Surround5_1` | FL, FR, C, LFE, BL, BR             |
+/// | `Surround7_1` | FL, FR, C, LFE, BL, BR, SL, SR     |
+///
+/// When converting between two layouts every output channel takes the input channel with the
+/// same position. A mono input is sent to both front speakers, channels with no matching input
+/// are silent and input channels with no matching output are dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelLayout {
+    /// One channel.
+    Mono,
+    /// Front left and right.
+    Stereo,
+    /// Stereo with a subwoofer.
+    Surround2_1,
+    /// Stereo with a center speaker.
+    Surround3_0,
+    /// Front and back pairs.
+    Quad,
+    /// Front pair, center and back pair.
+    Surround5_0,
+    /// 5.1 surround.
+    Surround5_1,
+    /// 7.1 surround.
+    Surround7_1,
+}
+
+impl ChannelLayout {
+    /// Returns the layout assumed for the given channel count, if there is one.
+    ///
+    /// Sources and devices that do not declare a layout are assumed to use this one. Channel
+    /// counts without a default layout (7 or more than 8) are converted positionally.
+    pub fn from_channels(channels: u16) -> Option<ChannelLayout> {
+        match channels {
+            1 => Some(ChannelLayout::Mono),
+            2 => Some(ChannelLayout::Stereo),
+            3 => Some(ChannelLayout::Surround3_0),
+            4 => Some(ChannelLayout::Quad),
+            5 => Some(ChannelLayout::Surround5_0),
+            6 => Some(ChannelLayout::Surround5_1),
+            8 => Some(ChannelLayout::Surround7_1),
+            _ => None,
+        }
+    }
+
+    /// The speaker positions of the channels, in interleaving order.
+    pub fn positions(&self) -> &'static [ChannelPosition] {
+        use ChannelPosition::*;
+        match self {
+            ChannelLayout::Mono => &[Mono],
+            ChannelLayout::Stereo => &[FrontLeft, FrontRight],
+            ChannelLayout::Surround2_1 => &[FrontLeft, FrontRight, LowFrequency],
+            ChannelLayout::Surround3_0 => &[FrontLeft, FrontRight, FrontCenter],
+            ChannelLayout::Quad => &[FrontLeft, FrontRight, BackLeft, BackRight],
+            ChannelLayout::Surround5_0 => {
+                &[FrontLeft, FrontRight, FrontCenter, BackLeft, BackRight]
+            }
+            ChannelLayout::Surround5_1 => &[
+                FrontLeft,
+                FrontRight,
+                FrontCenter,
+                LowFrequency,
+                BackLeft,
+                BackRight,
+            ],
+            ChannelLayout::Surround7_1 => &[
+                FrontLeft,
+                FrontRight,
+                FrontCenter,
+                LowFrequency,
+                BackLeft,
+                BackRight,
+                SideLeft,
+                SideRight,
+            ],
+        }
+    }
+
+    /// The number of channels in this layout.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.positions().len() as u16
+    }
+}
+
+/// For every output channel the index of the input channel it copies, `None` for silence.
+pub(crate) fn routing(from: ChannelLayout, to: ChannelLayout) -> Vec<Option<usize>> {
+    let from = from.positions();
+    to.positions()
+        .iter()
+        .map(|out| {
+            from.iter()
+                .position(|input| input == out)
+                .or_else(|| match out {
+                    // Downmixing to mono keeps the first channel.
+                    ChannelPosition::Mono => Some(0),
+                    ChannelPosition::FrontLeft | ChannelPosition::FrontRight
+                        if from == [ChannelPosition::Mono] =>
+                    {
+                        Some(0)
+                    }
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+/// Routing used when at least one side has no known layout: channels are kept by index and a
+/// mono input is duplicated to the second output channel.
+pub(crate) fn positional_routing(from: u16, to: u16) -> Vec<Option<usize>> {
+    (0..to as usize)
+        .map(|out| match out {
+            out if out < from as usize => Some(out),
+            1 => Some(0),
+            _ => None,
+        })
+        .collect()
+}
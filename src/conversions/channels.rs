@@ -1,6 +1,11 @@
 use cpal::Sample;
 
+use super::channel_layout::{self, ChannelLayout};
+
 /// Iterator that converts from a certain channel count to another.
+///
+/// Channels are mapped by speaker position using the default [`ChannelLayout`] of both
+/// channel counts, or by index when a channel count has no default layout.
 #[derive(Clone, Debug)]
 pub struct ChannelCountConverter<I>
 where
@@ -9,7 +14,10 @@ where
     input: I,
     from: cpal::ChannelCount,
     to: cpal::ChannelCount,
-    sample_repeat: Option<I::Item>,
+    // For each output channel the input channel it copies.
+    routing: Vec<Option<usize>>,
+    // The input frame being converted.
+    frame: Vec<Option<I::Item>>,
     next_output_sample_pos: cpal::ChannelCount,
 }
 
@@ -32,11 +40,41 @@ where
         assert!(from >= 1);
         assert!(to >= 1);
 
+        let routing = match (
+            ChannelLayout::from_channels(from),
+            ChannelLayout::from_channels(to),
+        ) {
+            (Some(from), Some(to)) => channel_layout::routing(from, to),
+            _ => channel_layout::positional_routing(from, to),
+        };
+        ChannelCountConverter::with_routing(input, from, routing)
+    }
+
+    /// Initializes the iterator, converting between two explicit channel layouts.
+    #[inline]
+    pub fn with_layouts(
+        input: I,
+        from: ChannelLayout,
+        to: ChannelLayout,
+    ) -> ChannelCountConverter<I> {
+        ChannelCountConverter::with_routing(
+            input,
+            from.channels(),
+            channel_layout::routing(from, to),
+        )
+    }
+
+    fn with_routing(
+        input: I,
+        from: cpal::ChannelCount,
+        routing: Vec<Option<usize>>,
+    ) -> ChannelCountConverter<I> {
         ChannelCountConverter {
             input,
             from,
-            to,
-            sample_repeat: None,
+            to: routing.len() as cpal::ChannelCount,
+            routing,
+            frame: Vec::with_capacity(from as usize),
             next_output_sample_pos: 0,
         }
     }
@@ -47,11 +85,26 @@ where
         self.input
     }
 
+    /// Get access to the iterator
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
     /// Get mutable access to the iterator
     #[inline]
     pub fn inner_mut(&mut self) -> &mut I {
         &mut self.input
     }
+
+    /// Number of samples left in the output frame being produced.
+    #[inline]
+    pub(crate) fn left_in_frame(&self) -> usize {
+        match self.next_output_sample_pos {
+            0 => 0,
+            pos => (self.to - pos) as usize,
+        }
+    }
 }
 
 impl<I> Iterator for ChannelCountConverter<I>
@@ -62,44 +115,35 @@ where
     type Item = I::Item;
 
     fn next(&mut self) -> Option<I::Item> {
-        let result = match self.next_output_sample_pos {
-            0 => {
-                // save first sample for mono -> stereo conversion
-                let value = self.input.next();
-                self.sample_repeat = value;
-                value
+        if self.next_output_sample_pos == 0 {
+            self.frame.clear();
+            let first = self.input.next()?;
+            self.frame.push(Some(first));
+            for _ in 1..self.from {
+                self.frame.push(self.input.next());
             }
-            x if x < self.from => self.input.next(),
-            1 => self.sample_repeat,
-            _ => Some(I::Item::EQUILIBRIUM),
-        };
-
-        if result.is_some() {
-            self.next_output_sample_pos += 1;
         }
 
+        let result = match self.routing[self.next_output_sample_pos as usize] {
+            Some(channel) => self.frame[channel].unwrap_or(I::Item::EQUILIBRIUM),
+            None => I::Item::EQUILIBRIUM,
+        };
+
+        self.next_output_sample_pos += 1;
         if self.next_output_sample_pos == self.to {
             self.next_output_sample_pos = 0;
-
-            if self.from > self.to {
-                for _ in self.to..self.from {
-                    self.input.next(); // discarding extra input
-                }
-            }
         }
 
-        result
+        Some(result)
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         let (min, max) = self.input.size_hint();
 
-        let consumed = std::cmp::min(self.from, self.next_output_sample_pos) as usize;
-        let calculate = |size| {
-            (size + consumed) / self.from as usize * self.to as usize
-                - self.next_output_sample_pos as usize
-        };
+        let left_in_frame = self.left_in_frame();
+        let calculate =
+            |size: usize| size.div_ceil(self.from as usize) * self.to as usize + left_in_frame;
 
         let min = calculate(min);
         let max = max.map(calculate);
@@ -118,6 +162,7 @@ where
 #[cfg(test)]
 mod test {
     use super::ChannelCountConverter;
+    use crate::conversions::ChannelLayout;
 
     #[test]
     fn remove_channels() {
@@ -145,6 +190,34 @@ mod test {
         assert_eq!(output, [1, 2, 0, 0, 3, 4, 0, 0]);
     }
 
+    #[test]
+    fn stereo_to_surround() {
+        let input = vec![1i16, 2, 3, 4];
+        let output = ChannelCountConverter::new(input.into_iter(), 2, 6).collect::<Vec<_>>();
+        // FL, FR, C, LFE, BL, BR
+        assert_eq!(output, [1, 2, 0, 0, 0, 0, 3, 4, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn quad_to_surround() {
+        let input = vec![1i16, 2, 3, 4];
+        let output = ChannelCountConverter::new(input.into_iter(), 4, 6).collect::<Vec<_>>();
+        // The back channels of the quad source go to the back speakers, not C and LFE.
+        assert_eq!(output, [1, 2, 0, 0, 3, 4]);
+    }
+
+    #[test]
+    fn explicit_layouts() {
+        let input = vec![1i16, 2, 3];
+        let output = ChannelCountConverter::with_layouts(
+            input.into_iter(),
+            ChannelLayout::Surround2_1,
+            ChannelLayout::Surround5_1,
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(output, [1, 2, 0, 3, 0, 0]);
+    }
+
     #[test]
     fn size_hint() {
         fn test(input: &[i16], from: cpal::ChannelCount, to: cpal::ChannelCount) {
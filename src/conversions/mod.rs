@@ -5,11 +5,13 @@ This includes conversion between sample formats, channels or sample rates.
 
 */
 
+pub use self::channel_layout::{ChannelLayout, ChannelPosition};
 pub use self::channels::ChannelCountConverter;
 pub use self::sample::DataConverter;
 pub use self::sample::Sample;
 pub use self::sample_rate::SampleRateConverter;
 
+mod channel_layout;
 mod channels;
 // TODO: < shouldn't be public ; there's a bug in Rust 1.4 and below that makes This
 // `pub` mandatory
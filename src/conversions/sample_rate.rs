@@ -357,7 +357,7 @@ mod test {
             let duration =
                 Duration::from_secs_f32(resampled.count() as f32 / to.0 as f32);
 
-            let delta = if d < duration { duration - d } else { d - duration };
+            let delta = d.abs_diff(duration);
             assert!(delta < Duration::from_millis(1),
                     "Resampled duration ({:?}) is not close to original ({:?}); Δ = {:?}",
                     duration, d, delta);
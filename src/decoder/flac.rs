@@ -6,6 +6,9 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::metadata::{self, AudioMetadata};
+use super::CodecInfo;
+
 use claxon::FlacReader;
 
 /// Decoder for the Flac format.
@@ -21,6 +24,9 @@ where
     sample_rate: u32,
     channels: u16,
     samples: Option<u64>,
+    // Samples returned so far, of all channels.
+    samples_read: u64,
+    metadata: AudioMetadata,
 }
 
 impl<R> FlacDecoder<R>
@@ -33,8 +39,19 @@ where
             return Err(data);
         }
 
-        let reader = FlacReader::new(data).unwrap();
+        Ok(Self::open(data).unwrap())
+    }
+
+    /// Reads the Flac data starting at the current position of `data`.
+    pub fn open(mut data: R) -> Result<FlacDecoder<R>, claxon::Error> {
+        // claxon skips picture blocks, they are read separately.
+        let picture = read_picture(data.by_ref());
+        let reader = FlacReader::new(data)?;
         let spec = reader.streaminfo();
+        let mut metadata = AudioMetadata::from_vorbis_comments(reader.tags());
+        if let Some((picture, front_cover)) = picture {
+            metadata.add_picture(picture, front_cover);
+        }
 
         Ok(FlacDecoder {
             reader,
@@ -47,11 +64,59 @@ where
             sample_rate: spec.sample_rate,
             channels: spec.channels as u16,
             samples: spec.samples,
+            samples_read: 0,
+            metadata,
         })
     }
+
     pub fn into_inner(self) -> R {
         self.reader.into_inner()
     }
+
+    /// Returns true if `pos` lies before the current position, claxon can only seek forward.
+    pub fn is_past(&self, pos: Duration) -> bool {
+        self.samples_at(pos) < self.samples_read
+    }
+
+    /// Returns the channel the next sample is for.
+    pub fn next_channel(&self) -> u16 {
+        (self.samples_read % self.channels as u64) as u16
+    }
+
+    /// Returns the number of samples of all channels played before `pos`, keeping the
+    /// channel the next sample is for.
+    fn samples_at(&self, pos: Duration) -> u64 {
+        let frames = (pos.as_secs_f64() * self.sample_rate as f64) as u64;
+        frames * self.channels as u64 + self.next_channel() as u64
+    }
+
+    /// Decodes the next block, returns false at the end of the stream.
+    fn load_next_block(&mut self) -> bool {
+        self.current_block_off = 0;
+        let buffer = mem::take(&mut self.current_block);
+        match self.reader.blocks().read_next_or_eof(buffer) {
+            Ok(Some(block)) => {
+                self.current_block_channel_len = (block.len() / block.channels()) as usize;
+                self.current_block = block.into_buffer();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    pub fn codec_info(&self) -> CodecInfo {
+        CodecInfo {
+            codec: "flac",
+            container: Some("flac"),
+            bits_per_sample: Some(self.bits_per_sample),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
 }
 
 impl<R> Source for FlacDecoder<R>
@@ -81,11 +146,32 @@ where
             .map(|s| Duration::from_micros(s * 1_000_000 / self.sample_rate as u64))
     }
 
-    #[inline]
-    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
-        Err(SeekError::NotSupported {
-            underlying_source: std::any::type_name::<Self>(),
-        })
+    /// Seeks forward by decoding and discarding the samples up to `pos`. Seeking beyond the
+    /// end ends the source.
+    ///
+    /// Seeking backwards is not supported, the decoder has to start over from the beginning
+    /// of the file for that.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if self.is_past(pos) {
+            return Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            });
+        }
+
+        let target = self.samples_at(pos);
+        while self.samples_read < target {
+            let left_in_block = self.current_block.len() - self.current_block_off;
+            if left_in_block == 0 {
+                if !self.load_next_block() {
+                    break;
+                }
+                continue;
+            }
+            let skip = left_in_block.min((target - self.samples_read) as usize);
+            self.current_block_off += skip;
+            self.samples_read += skip as u64;
+        }
+        Ok(())
     }
 }
 
@@ -105,6 +191,7 @@ where
                     + self.current_block_off / self.channels as usize;
                 let raw_val = self.current_block[real_offset];
                 self.current_block_off += 1;
+                self.samples_read += 1;
                 let real_val = match self.bits_per_sample.cmp(&16) {
                     Ordering::Less => (raw_val << (16 - self.bits_per_sample)) as i16,
                     Ordering::Equal => raw_val as i16,
@@ -113,15 +200,8 @@ where
                 return Some(real_val);
             }
 
-            // Load the next block.
-            self.current_block_off = 0;
-            let buffer = mem::take(&mut self.current_block);
-            match self.reader.blocks().read_next_or_eof(buffer) {
-                Ok(Some(block)) => {
-                    self.current_block_channel_len = (block.len() / block.channels()) as usize;
-                    self.current_block = block.into_buffer();
-                }
-                _ => return None,
+            if !self.load_next_block() {
+                return None;
             }
         }
     }
@@ -142,3 +222,53 @@ where
     data.seek(SeekFrom::Start(stream_pos)).unwrap();
     true
 }
+
+/// Reads the picture blocks of Flac data, then resets it to where it was. Returns the front
+/// cover, or the first picture if none is marked as such.
+fn read_picture<R>(mut data: R) -> Option<(metadata::Picture, bool)>
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().ok()?;
+    let picture = find_picture(data.by_ref());
+    data.seek(SeekFrom::Start(stream_pos)).ok()?;
+    picture
+}
+
+fn find_picture<R>(mut data: R) -> Option<(metadata::Picture, bool)>
+where
+    R: Read + Seek,
+{
+    const PICTURE: u8 = 6;
+
+    let mut marker = [0u8; 4];
+    data.read_exact(&mut marker).ok()?;
+    if &marker != b"fLaC" {
+        return None;
+    }
+
+    let mut found = None;
+    loop {
+        let mut header = [0u8; 4];
+        if data.read_exact(&mut header).is_err() {
+            return found;
+        }
+        let is_last = header[0] & 0x80 != 0;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+        if header[0] & 0x7f == PICTURE {
+            let mut block = vec![0u8; len as usize];
+            data.read_exact(&mut block).ok()?;
+            if let Some((picture, front_cover)) = metadata::parse_flac_picture(&block) {
+                if front_cover {
+                    return Some((picture, true));
+                }
+                found.get_or_insert((picture, false));
+            }
+        } else {
+            data.seek(SeekFrom::Current(len as i64)).ok()?;
+        }
+        if is_last {
+            return found;
+        }
+    }
+}
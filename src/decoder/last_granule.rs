@@ -0,0 +1,52 @@
+use std::io::{Read, Seek, SeekFrom};
+
+/// Returns the granule position of the last page of the first logical stream, then resets
+/// the data to where it was. For Vorbis this is the number of frames in the stream.
+///
+/// Only the end of the data is read, `None` if it can not seek there.
+pub(crate) fn read_last_granule<R>(mut data: R) -> Option<u64>
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().ok()?;
+    let granule = find_last_granule(data.by_ref(), stream_pos);
+    data.seek(SeekFrom::Start(stream_pos)).ok()?;
+    granule
+}
+
+fn find_last_granule<R>(mut data: R, start: u64) -> Option<u64>
+where
+    R: Read + Seek,
+{
+    // Offsets in the page header.
+    const GRANULE: usize = 6;
+    const SERIAL: usize = 14;
+    const HEADER_LEN: usize = 27;
+    const MAX_PAGE_LEN: u64 = 65307;
+
+    let mut first = [0u8; HEADER_LEN];
+    data.read_exact(&mut first).ok()?;
+    if &first[0..4] != b"OggS" {
+        return None;
+    }
+    let serial = &first[SERIAL..SERIAL + 4];
+
+    // The last page starts within the maximum page length before the end.
+    let end = data.seek(SeekFrom::End(0)).ok()?;
+    data.seek(SeekFrom::Start(end.saturating_sub(MAX_PAGE_LEN).max(start)))
+        .ok()?;
+    let mut tail = Vec::new();
+    data.read_to_end(&mut tail).ok()?;
+
+    (0..=tail.len().checked_sub(HEADER_LEN)?)
+        .rev()
+        .map(|at| &tail[at..at + HEADER_LEN])
+        .filter(|page| &page[0..4] == b"OggS" && page[4] == 0 && &page[SERIAL..SERIAL + 4] == serial)
+        .map(|page| {
+            let mut granule = [0u8; 8];
+            granule.copy_from_slice(&page[GRANULE..GRANULE + 8]);
+            u64::from_le_bytes(granule)
+        })
+        // Pages on which no packet ends have no granule position.
+        .find(|&granule| granule != u64::MAX)
+}
@@ -0,0 +1,273 @@
+/// Tags of a decoded file, for display.
+///
+/// Read from ID3 tags, Vorbis comments, MP4 atoms or the `INFO` list of WAV files, depending
+/// on the format. Fields the file does not have, or leaves empty, are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AudioMetadata {
+    /// Title of the track.
+    pub title: Option<String>,
+    /// Performing artist.
+    pub artist: Option<String>,
+    /// Album the track belongs to.
+    pub album: Option<String>,
+    /// Artist of the whole album, if it differs per track.
+    pub album_artist: Option<String>,
+    /// Genre, as written in the file.
+    pub genre: Option<String>,
+    /// Release date or year, as written in the file.
+    pub date: Option<String>,
+    /// Position of the track on the album.
+    pub track_number: Option<u32>,
+    /// Embedded picture, the front cover if the file marks one.
+    pub picture: Option<Picture>,
+}
+
+/// Image embedded in a file, usually the album art.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Picture {
+    /// Media type of the image, for example `"image/jpeg"` or `"image/png"`.
+    pub media_type: String,
+    /// The encoded image.
+    pub data: Vec<u8>,
+}
+
+/// Metadata of decoders that do not read any.
+pub(crate) static NO_METADATA: AudioMetadata = AudioMetadata {
+    title: None,
+    artist: None,
+    album: None,
+    album_artist: None,
+    genre: None,
+    date: None,
+    track_number: None,
+    picture: None,
+};
+
+/// Field of [`AudioMetadata`] a tag is stored in.
+#[derive(Clone, Copy)]
+pub(crate) enum Field {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Genre,
+    Date,
+    TrackNumber,
+}
+
+impl AudioMetadata {
+    /// Stores a tag, the first value of a field wins. Empty values are skipped.
+    pub(crate) fn set(&mut self, field: Field, value: &str) {
+        let value = value.trim_matches(char::from(0)).trim();
+        if value.is_empty() {
+            return;
+        }
+        let slot = match field {
+            Field::Title => &mut self.title,
+            Field::Artist => &mut self.artist,
+            Field::Album => &mut self.album,
+            Field::AlbumArtist => &mut self.album_artist,
+            Field::Genre => &mut self.genre,
+            Field::Date => &mut self.date,
+            Field::TrackNumber => {
+                // Often written as "3/12".
+                let number = value.split('/').next().unwrap_or_default().trim();
+                if self.track_number.is_none() {
+                    self.track_number = number.parse().ok();
+                }
+                return;
+            }
+        };
+        slot.get_or_insert_with(|| value.to_owned());
+    }
+
+    /// Stores a picture, a front cover replaces any other picture.
+    pub(crate) fn add_picture(&mut self, picture: Picture, front_cover: bool) {
+        if front_cover || self.picture.is_none() {
+            self.picture = Some(picture);
+        }
+    }
+
+    /// Reads Vorbis comments, as found in Ogg Vorbis, Ogg Opus and FLAC files.
+    #[cfg(any(
+        all(feature = "flac", not(feature = "symphonia-flac")),
+        all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+        feature = "opus"
+    ))]
+    pub(crate) fn from_vorbis_comments<'a>(
+        comments: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> AudioMetadata {
+        let mut metadata = AudioMetadata::default();
+        for (key, value) in comments {
+            let field = match key.to_ascii_uppercase().as_str() {
+                "TITLE" => Field::Title,
+                "ARTIST" => Field::Artist,
+                "ALBUM" => Field::Album,
+                "ALBUMARTIST" | "ALBUM ARTIST" => Field::AlbumArtist,
+                "GENRE" => Field::Genre,
+                "DATE" | "YEAR" => Field::Date,
+                "TRACKNUMBER" => Field::TrackNumber,
+                // Ogg files carry their pictures as base64 encoded FLAC picture blocks.
+                "METADATA_BLOCK_PICTURE" => {
+                    if let Some((picture, front_cover)) =
+                        decode_base64(value).and_then(|block| parse_flac_picture(&block))
+                    {
+                        metadata.add_picture(picture, front_cover);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            metadata.set(field, value);
+        }
+        metadata
+    }
+}
+
+/// Reads a FLAC `PICTURE` metadata block, returns the picture and whether it is the front
+/// cover.
+#[cfg(any(
+    all(feature = "flac", not(feature = "symphonia-flac")),
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "opus"
+))]
+pub(crate) fn parse_flac_picture(block: &[u8]) -> Option<(Picture, bool)> {
+    const FRONT_COVER: u32 = 3;
+
+    let mut rest = block;
+    let picture_type = take_u32(&mut rest)?;
+    let media_type_len = take_u32(&mut rest)?;
+    let media_type = take(&mut rest, media_type_len)?;
+    let media_type = String::from_utf8_lossy(media_type).into_owned();
+    let description_len = take_u32(&mut rest)?;
+    take(&mut rest, description_len)?;
+    // Width, height, color depth and number of colors.
+    take(&mut rest, 16)?;
+    let data_len = take_u32(&mut rest)?;
+    let data = take(&mut rest, data_len)?;
+
+    let picture = Picture {
+        media_type,
+        data: data.to_vec(),
+    };
+    Some((picture, picture_type == FRONT_COVER))
+}
+
+/// Splits the first `len` bytes off `rest`.
+#[cfg(any(
+    all(feature = "flac", not(feature = "symphonia-flac")),
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "opus"
+))]
+fn take<'a>(rest: &mut &'a [u8], len: u32) -> Option<&'a [u8]> {
+    let len = len as usize;
+    if rest.len() < len {
+        return None;
+    }
+    let (head, tail) = rest.split_at(len);
+    *rest = tail;
+    Some(head)
+}
+
+/// Splits a big endian `u32` off `rest`.
+#[cfg(any(
+    all(feature = "flac", not(feature = "symphonia-flac")),
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "opus"
+))]
+fn take_u32(rest: &mut &[u8]) -> Option<u32> {
+    let bytes = take(rest, 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Decodes standard base64, padding is optional.
+#[cfg(any(
+    all(feature = "flac", not(feature = "symphonia-flac")),
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "opus"
+))]
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let value = |byte: u8| match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        let mut bits = 0u32;
+        for (index, &byte) in chunk.iter().enumerate() {
+            bits |= (value(byte)? as u32) << (18 - 6 * index);
+        }
+        let decoded = bits.to_be_bytes();
+        bytes.extend_from_slice(&decoded[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+#[cfg(all(
+    test,
+    any(
+        all(feature = "flac", not(feature = "symphonia-flac")),
+        all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+        feature = "opus"
+    )
+))]
+mod tests {
+    use super::{decode_base64, AudioMetadata};
+
+    #[test]
+    fn vorbis_comments() {
+        let metadata = AudioMetadata::from_vorbis_comments([
+            ("title", "Song"),
+            ("ARTIST", "Band"),
+            ("album", ""),
+            ("TRACKNUMBER", "3/12"),
+            ("title", "Other"),
+        ]);
+        assert_eq!(metadata.title.as_deref(), Some("Song"));
+        assert_eq!(metadata.artist.as_deref(), Some("Band"));
+        assert_eq!(metadata.album, None);
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.picture, None);
+    }
+
+    #[test]
+    fn embedded_picture() {
+        // A FLAC picture block of a front cover, with a two byte "image/x" file.
+        let mut block = Vec::new();
+        block.extend(3u32.to_be_bytes());
+        block.extend(7u32.to_be_bytes());
+        block.extend(b"image/x");
+        block.extend(0u32.to_be_bytes());
+        block.extend([0; 16]);
+        block.extend(2u32.to_be_bytes());
+        block.extend([0xab, 0xcd]);
+        let encoded = encode_base64(&block);
+        assert_eq!(decode_base64(&encoded).unwrap(), block);
+
+        let metadata =
+            AudioMetadata::from_vorbis_comments([("METADATA_BLOCK_PICTURE", encoded.as_str())]);
+        let picture = metadata.picture.unwrap();
+        assert_eq!(picture.media_type, "image/x");
+        assert_eq!(picture.data, vec![0xab, 0xcd]);
+    }
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut text = String::new();
+        for chunk in bytes.chunks(3) {
+            let mut bits = 0u32;
+            for (index, &byte) in chunk.iter().enumerate() {
+                bits |= (byte as u32) << (16 - 8 * index);
+            }
+            for index in 0..=chunk.len() {
+                text.push(ALPHABET[(bits >> (18 - 6 * index) & 63) as usize] as char);
+            }
+        }
+        text
+    }
+}
@@ -3,14 +3,22 @@
 use std::error::Error;
 use std::fmt;
 #[allow(unused_imports)]
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::mem;
+use std::ops::Range;
 use std::str::FromStr;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use crate::source::SeekError;
 use crate::Source;
 
+pub use self::metadata::{AudioMetadata, Picture};
+use self::progress::{ProgressReader, ReadProgress};
+pub use self::quality::DecodeQuality;
+use self::quality::QualityState;
+pub use self::streaming::StreamingReader;
+
 #[cfg(feature = "symphonia")]
 use self::read_seek_source::ReadSeekSource;
 #[cfg(feature = "symphonia")]
@@ -18,10 +26,21 @@ use ::symphonia::core::io::{MediaSource, MediaSourceStream};
 
 #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
 mod flac;
+#[cfg(any(
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    feature = "opus"
+))]
+mod last_granule;
+mod metadata;
 #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
 mod mp3;
+#[cfg(feature = "opus")]
+mod opus;
+mod progress;
+mod quality;
 #[cfg(feature = "symphonia")]
 mod read_seek_source;
+mod streaming;
 #[cfg(feature = "symphonia")]
 /// Symphonia decoders types
 pub mod symphonia;
@@ -30,18 +49,39 @@ mod vorbis;
 #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
 mod wav;
 
+/// Describes how a [`Decoder`] decodes its data, for diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodecInfo {
+    /// Short lowercase name of the codec, for example `"flac"`, `"vorbis"`, `"mp3"` or `"pcm"`.
+    pub codec: &'static str,
+    /// Name of the container holding the encoded data, for example `"wav"`, `"ogg"` or
+    /// `"mp4"`. `None` for raw streams such as MP3, or if the container could not be told.
+    pub container: Option<&'static str>,
+    /// Bit depth of the encoded samples. `None` for lossy codecs, which have no fixed depth.
+    pub bits_per_sample: Option<u32>,
+    /// Sample rate of the decoded audio.
+    pub sample_rate: u32,
+    /// Number of channels of the decoded audio.
+    pub channels: u16,
+}
+
 /// Source of audio samples from decoding a file.
 ///
-/// Supports MP3, WAV, Vorbis and Flac.
-pub struct Decoder<R>(DecoderImpl<R>)
+/// Supports MP3, WAV, Vorbis and Flac, and Opus with the `opus` feature.
+///
+/// [`Source::total_duration`] is exact for WAV, Flac and Ogg files, and for MP3 files with a
+/// Xing or Info header. Other MP3 files report a duration estimated from their size and the
+/// bitrate of the first frames, which is approximate, and off further for variable bitrates.
+/// The `minimp3` decoder reports no duration.
+pub struct Decoder<R>(DecoderImpl<ProgressReader<R>>, ReadProgress, QualityState)
 where
     R: Read + Seek;
 
 /// Source of audio samples from decoding a file that never ends. When the
 /// end of the file is reached the decoder starts again from the beginning.
 ///
-/// Supports MP3, WAV, Vorbis and Flac.
-pub struct LoopedDecoder<R>(DecoderImpl<R>)
+/// Supports MP3, WAV, Vorbis and Flac, and Opus with the `opus` feature.
+pub struct LoopedDecoder<R>(DecoderImpl<ProgressReader<R>>)
 where
     R: Read + Seek;
 
@@ -56,6 +96,8 @@ where
     Wav(wav::WavDecoder<R>),
     #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
     Vorbis(vorbis::VorbisDecoder<R>),
+    #[cfg(feature = "opus")]
+    Opus(opus::OpusDecoder<R>),
     #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
     Flac(flac::FlacDecoder<R>),
     #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -73,6 +115,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.next(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.next(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.next(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.next(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -90,6 +134,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.size_hint(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.size_hint(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.size_hint(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.size_hint(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -107,6 +153,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.current_frame_len(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.current_frame_len(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.current_frame_len(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.current_frame_len(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -124,6 +172,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.channels(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.channels(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.channels(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.channels(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -141,6 +191,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.sample_rate(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.sample_rate(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.sample_rate(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.sample_rate(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -158,6 +210,8 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::Wav(source) => source.total_duration(),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.total_duration(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.total_duration(),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.total_duration(),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -168,13 +222,66 @@ impl<R: Read + Seek> DecoderImpl<R> {
         }
     }
 
+    fn loop_points(&self) -> Option<Range<u64>> {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.loop_points(),
+            _ => None,
+        }
+    }
+
+    fn metadata(&self) -> &AudioMetadata {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.metadata(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.metadata(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.metadata(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.metadata(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.metadata(),
+            _ => &metadata::NO_METADATA,
+        }
+    }
+
+    fn codec_info(&self) -> CodecInfo {
+        match self {
+            #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+            DecoderImpl::Wav(source) => source.codec_info(),
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.codec_info(),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.codec_info(),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.codec_info(),
+            #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
+            DecoderImpl::Mp3(source) => source.codec_info(),
+            #[cfg(feature = "symphonia")]
+            DecoderImpl::Symphonia(source) => source.codec_info(),
+            DecoderImpl::None(_) => CodecInfo {
+                codec: "none",
+                container: None,
+                bits_per_sample: None,
+                sample_rate: 1,
+                channels: 0,
+            },
+        }
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if self.is_past(pos) {
+            self.restart()?;
+        }
         match self {
             #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
             DecoderImpl::Wav(source) => source.try_seek(pos),
             #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
             DecoderImpl::Vorbis(source) => source.try_seek(pos),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.try_seek(pos),
             #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
             DecoderImpl::Flac(source) => source.try_seek(pos),
             #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
@@ -186,6 +293,69 @@ impl<R: Read + Seek> DecoderImpl<R> {
             }),
         }
     }
+
+    /// Returns true for decoders that only seek forward, when `pos` lies before their
+    /// current position.
+    #[allow(unused_variables)]
+    fn is_past(&self, pos: Duration) -> bool {
+        match self {
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => source.is_past(pos),
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => source.is_past(pos),
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => source.is_past(pos),
+            _ => false,
+        }
+    }
+
+    /// Starts a decoder that only seeks forward over from the beginning of the file. The
+    /// first samples are skipped so the next one is for the same channel as before.
+    ///
+    /// On error the decoder is gone, and plays as an empty source.
+    fn restart(&mut self) -> Result<(), SeekError> {
+        let decoder = mem::replace(self, DecoderImpl::None(Default::default()));
+        *self = match decoder {
+            #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
+            DecoderImpl::Vorbis(source) => {
+                use lewton::{OggReadError, VorbisError};
+                let next_channel = source.next_channel();
+                let mut reader = source.into_inner().into_inner().into_inner();
+                reader.seek(SeekFrom::Start(0)).map_err(|err| {
+                    SeekError::LewtonDecoder(VorbisError::OggError(OggReadError::ReadError(err)))
+                })?;
+                let mut source =
+                    vorbis::VorbisDecoder::open(reader).map_err(SeekError::LewtonDecoder)?;
+                source.by_ref().take(next_channel as usize).for_each(drop);
+                DecoderImpl::Vorbis(source)
+            }
+            #[cfg(feature = "opus")]
+            DecoderImpl::Opus(source) => {
+                let next_channel = source.next_channel();
+                let mut reader = source.into_inner();
+                reader
+                    .seek(SeekFrom::Start(0))
+                    .map_err(|err| SeekError::OpusDecoder(ogg::OggReadError::ReadError(err)))?;
+                let mut source = opus::OpusDecoder::open(reader).map_err(SeekError::OpusDecoder)?;
+                source.by_ref().take(next_channel as usize).for_each(drop);
+                DecoderImpl::Opus(source)
+            }
+            #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+            DecoderImpl::Flac(source) => {
+                let next_channel = source.next_channel();
+                let mut reader = source.into_inner();
+                reader
+                    .seek(SeekFrom::Start(0))
+                    .map_err(|err| SeekError::ClaxonDecoder(claxon::Error::IoError(err)))?;
+                let mut source =
+                    flac::FlacDecoder::open(reader).map_err(SeekError::ClaxonDecoder)?;
+                source.by_ref().take(next_channel as usize).for_each(drop);
+                DecoderImpl::Flac(source)
+            }
+            other => other,
+        };
+        Ok(())
+    }
 }
 
 impl<R> Decoder<R>
@@ -197,11 +367,17 @@ where
     /// Attempts to automatically detect the format of the source of data.
     #[allow(unused_variables)]
     pub fn new(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
+
         #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
         let data = match wav::WavDecoder::new(data) {
             Err(data) => data,
             Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Wav(decoder)));
+                return Ok(Decoder(
+                    DecoderImpl::Wav(decoder),
+                    progress,
+                    QualityState::default(),
+                ));
             }
         };
 
@@ -209,7 +385,11 @@ where
         let data = match flac::FlacDecoder::new(data) {
             Err(data) => data,
             Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Flac(decoder)));
+                return Ok(Decoder(
+                    DecoderImpl::Flac(decoder),
+                    progress,
+                    QualityState::default(),
+                ));
             }
         };
 
@@ -217,7 +397,25 @@ where
         let data = match vorbis::VorbisDecoder::new(data) {
             Err(data) => data,
             Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Vorbis(decoder)));
+                return Ok(Decoder(
+                    DecoderImpl::Vorbis(decoder),
+                    progress,
+                    QualityState::default(),
+                ));
+            }
+        };
+
+        #[cfg(feature = "opus")]
+        let data = match opus::OpusDecoder::new(data) {
+            Err(data) => data,
+            // Looks like opus, so none of the other decoders can read it either.
+            Ok(Err(_)) => return Err(DecoderError::UnrecognizedFormat),
+            Ok(Ok(decoder)) => {
+                return Ok(Decoder(
+                    DecoderImpl::Opus(decoder),
+                    progress,
+                    QualityState::default(),
+                ));
             }
         };
 
@@ -225,20 +423,28 @@ where
         let data = match mp3::Mp3Decoder::new(data) {
             Err(data) => data,
             Ok(decoder) => {
-                return Ok(Decoder(DecoderImpl::Mp3(decoder)));
+                return Ok(Decoder(
+                    DecoderImpl::Mp3(decoder),
+                    progress,
+                    QualityState::default(),
+                ));
             }
         };
 
         #[cfg(feature = "symphonia")]
         {
             let mss = MediaSourceStream::new(
-                Box::new(ReadSeekSource::new(data)) as Box<dyn MediaSource>,
+                Box::new(ReadSeekSource::new(data, progress.total())) as Box<dyn MediaSource>,
                 Default::default(),
             );
 
             match symphonia::SymphoniaDecoder::new(mss, None) {
                 Err(e) => Err(e),
-                Ok(decoder) => Ok(Decoder(DecoderImpl::Symphonia(decoder))),
+                Ok(decoder) => Ok(Decoder(
+                    DecoderImpl::Symphonia(decoder),
+                    progress,
+                    QualityState::default(),
+                )),
             }
         }
         #[cfg(not(feature = "symphonia"))]
@@ -255,9 +461,14 @@ where
     /// Builds a new decoder from wav data.
     #[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
     pub fn new_wav(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
         match wav::WavDecoder::new(data) {
             Err(_) => Err(DecoderError::UnrecognizedFormat),
-            Ok(decoder) => Ok(Decoder(DecoderImpl::Wav(decoder))),
+            Ok(decoder) => Ok(Decoder(
+                DecoderImpl::Wav(decoder),
+                progress,
+                QualityState::default(),
+            )),
         }
     }
 
@@ -270,9 +481,14 @@ where
     /// Builds a new decoder from flac data.
     #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
     pub fn new_flac(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
         match flac::FlacDecoder::new(data) {
             Err(_) => Err(DecoderError::UnrecognizedFormat),
-            Ok(decoder) => Ok(Decoder(DecoderImpl::Flac(decoder))),
+            Ok(decoder) => Ok(Decoder(
+                DecoderImpl::Flac(decoder),
+                progress,
+                QualityState::default(),
+            )),
         }
     }
 
@@ -285,9 +501,14 @@ where
     /// Builds a new decoder from vorbis data.
     #[cfg(all(feature = "vorbis", not(feature = "symphonia-vorbis")))]
     pub fn new_vorbis(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
         match vorbis::VorbisDecoder::new(data) {
             Err(_) => Err(DecoderError::UnrecognizedFormat),
-            Ok(decoder) => Ok(Decoder(DecoderImpl::Vorbis(decoder))),
+            Ok(decoder) => Ok(Decoder(
+                DecoderImpl::Vorbis(decoder),
+                progress,
+                QualityState::default(),
+            )),
         }
     }
 
@@ -297,12 +518,31 @@ where
         Decoder::new_symphonia(data, "ogg")
     }
 
+    /// Builds a new decoder from ogg/opus data.
+    #[cfg(feature = "opus")]
+    pub fn new_opus(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
+        match opus::OpusDecoder::new(data) {
+            Err(_) | Ok(Err(_)) => Err(DecoderError::UnrecognizedFormat),
+            Ok(Ok(decoder)) => Ok(Decoder(
+                DecoderImpl::Opus(decoder),
+                progress,
+                QualityState::default(),
+            )),
+        }
+    }
+
     /// Builds a new decoder from mp3 data.
     #[cfg(all(feature = "minimp3", not(feature = "symphonia-mp3")))]
     pub fn new_mp3(data: R) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
         match mp3::Mp3Decoder::new(data) {
             Err(_) => Err(DecoderError::UnrecognizedFormat),
-            Ok(decoder) => Ok(Decoder(DecoderImpl::Mp3(decoder))),
+            Ok(decoder) => Ok(Decoder(
+                DecoderImpl::Mp3(decoder),
+                progress,
+                QualityState::default(),
+            )),
         }
     }
 
@@ -326,18 +566,191 @@ where
 
     #[cfg(feature = "symphonia")]
     fn new_symphonia(data: R, hint: &str) -> Result<Decoder<R>, DecoderError> {
+        let (data, progress) = ProgressReader::new(data);
         let mss = MediaSourceStream::new(
-            Box::new(ReadSeekSource::new(data)) as Box<dyn MediaSource>,
+            Box::new(ReadSeekSource::new(data, progress.total())) as Box<dyn MediaSource>,
             Default::default(),
         );
 
         match symphonia::SymphoniaDecoder::new(mss, Some(hint)) {
             Err(e) => Err(e),
-            Ok(decoder) => Ok(Decoder(DecoderImpl::Symphonia(decoder))),
+            Ok(decoder) => Ok(Decoder(
+                DecoderImpl::Symphonia(decoder),
+                progress,
+                QualityState::default(),
+            )),
         }
     }
 }
 
+impl<R> Decoder<StreamingReader<R>>
+where
+    R: Read + Send + 'static,
+{
+    /// Builds a new decoder from a stream that can not seek, such as a network socket.
+    ///
+    /// The format is detected from the first bytes of the stream. Formats that can be decoded
+    /// in one pass work: WAV, Flac, Ogg Vorbis, Ogg Opus and MP3. MP4 files that store their index
+    /// after the audio, as most M4A files do, return [`DecoderError::NotStreamable`].
+    ///
+    /// The decoder does not know the total duration of the stream unless the format states it
+    /// in its header. Seeking forward decodes and discards the audio in between, seeking
+    /// backwards fails and may end the decoder.
+    pub fn new_streaming(input: R) -> Result<Decoder<StreamingReader<R>>, DecoderError> {
+        let (mut data, probing) = StreamingReader::new(input);
+        // Long enough to hold the first packet of an Ogg stream, which tells the codec.
+        let mut magic = [0u8; 36];
+        let mut len = 0;
+        while len < magic.len() {
+            match data.read(&mut magic[len..]) {
+                Ok(0) => break,
+                Ok(read) => len += read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => return Err(DecoderError::UnrecognizedFormat),
+            }
+        }
+        data.seek(SeekFrom::Start(0))
+            .expect("the start of the stream is kept while probing");
+
+        // Only the matching decoder reads, some give up on other formats only at the end of
+        // the stream.
+        let decoder = match &magic {
+            #[cfg(any(feature = "wav", feature = "symphonia-wav"))]
+            [b'R', b'I', b'F', b'F', ..] => Decoder::new_wav(data),
+            #[cfg(any(feature = "flac", feature = "symphonia-flac"))]
+            [b'f', b'L', b'a', b'C', ..] => Decoder::new_flac(data),
+            #[cfg(feature = "opus")]
+            [b'O', b'g', b'g', b'S', ..] if &magic[28..36] == b"OpusHead" => {
+                Decoder::new_opus(data)
+            }
+            #[cfg(any(feature = "vorbis", feature = "symphonia-vorbis"))]
+            [b'O', b'g', b'g', b'S', ..] => Decoder::new_vorbis(data),
+            #[cfg(any(feature = "minimp3", feature = "symphonia-mp3"))]
+            [b'I', b'D', b'3', ..] | [0xff, 0xe0..=0xff, ..] => Decoder::new_mp3(data),
+            _ => Decoder::new(data),
+        };
+        // Without seeking symphonia only finds an index that comes before the audio.
+        #[cfg(feature = "symphonia-isomp4")]
+        let decoder = match decoder {
+            Err(DecoderError::UnrecognizedFormat) if &magic[4..8] == b"ftyp" => {
+                Err(DecoderError::NotStreamable)
+            }
+            decoder => decoder,
+        };
+        probing.store(false, Ordering::Relaxed);
+        decoder
+    }
+}
+
+impl<R> Decoder<R>
+where
+    R: Read + Seek,
+{
+    /// Returns the number of bytes of the underlying data read so far.
+    ///
+    /// This is the offset up to which the decoder has read, it moves back when the decoder
+    /// seeks. Together with [`total_bytes`](Decoder::total_bytes) it can be used to show the
+    /// progress of a stream, or to tell how much of a download is still needed.
+    #[inline]
+    pub fn bytes_consumed(&self) -> u64 {
+        self.1.position()
+    }
+
+    /// Returns the size of the underlying data in bytes, if it is known.
+    ///
+    /// The size is determined once when the decoder is built, by seeking to the end of the data.
+    #[inline]
+    pub fn total_bytes(&self) -> Option<u64> {
+        self.1.total()
+    }
+
+    /// Returns which codec decodes the data and its parameters.
+    ///
+    /// This is purely informational, useful when reporting a file that does not play correctly.
+    pub fn codec_info(&self) -> CodecInfo {
+        self.0.codec_info()
+    }
+
+    /// Returns the tags and the embedded picture of the file, read when the decoder was built.
+    ///
+    /// Without the `symphonia-mp3` feature MP3 files report no metadata.
+    pub fn metadata(&self) -> &AudioMetadata {
+        self.0.metadata()
+    }
+
+    /// Returns the loop embedded in the file, as a range of frames, if there is one.
+    ///
+    /// Loop points are read from the `smpl` chunk of WAV files, as written by most sample
+    /// editors; the first loop is returned if there are several. Other formats never report
+    /// loop points. Use [`Source::loop_region`] or
+    /// [`Sink::append_looped`](crate::Sink::append_looped) to play them.
+    pub fn loop_points(&self) -> Option<Range<u64>> {
+        self.0.loop_points()
+    }
+
+    /// Switches between full quality and a cheaper preview, see [`DecodeQuality`].
+    ///
+    /// While previewing the decoder outputs a single channel at a reduced sample rate. The
+    /// switch happens once the frame length reported by [`Source::current_frame_len`] runs
+    /// out, a few milliseconds later at most, so the format never changes unannounced.
+    /// Typically the quality is lowered while the user drags a seek bar and restored
+    /// once playback resumes.
+    #[inline]
+    pub fn set_quality(&mut self, quality: DecodeQuality) {
+        self.2.requested = quality;
+    }
+
+    /// Returns the quality the decoder was last set to.
+    #[inline]
+    pub fn quality(&self) -> DecodeQuality {
+        self.2.requested
+    }
+
+    /// Returns the number of frames decoded since the decoder was created or last seeked.
+    ///
+    /// A frame holds one sample of every channel and only counts once all of them were read.
+    /// Frames skipped by a [`DecodeQuality::Preview`] are counted too, so dividing by the
+    /// sample rate of the file gives the exact distance played from the start or the seek
+    /// target, independently of any buffering further down the audio pipeline.
+    #[inline]
+    pub fn frames_decoded(&self) -> u64 {
+        self.2.frames_decoded
+    }
+
+    /// Averages the channels of one decoded frame and skips the frames the preview drops.
+    ///
+    /// Nothing more is read from the decoder once the preview window is used up.
+    fn next_preview(&mut self) -> Option<i16> {
+        if self.2.preview_left == 0 {
+            return None;
+        }
+        let channels = self.0.channels().max(1);
+        let decimation = DecodeQuality::Preview.decimation(self.0.sample_rate());
+        let mut sum = 0i32;
+        for _ in 0..channels {
+            sum += self.0.next()? as i32;
+        }
+        self.2.frames_decoded += 1;
+        self.2.preview_left -= 1;
+        for _ in 1..decimation {
+            if self.2.preview_left == 0 || (0..channels).any(|_| self.0.next().is_none()) {
+                break;
+            }
+            self.2.frames_decoded += 1;
+            self.2.preview_left -= 1;
+        }
+        Some((sum / channels as i32) as i16)
+    }
+
+    /// Number of samples the preview still outputs before it ends.
+    #[inline]
+    fn preview_outputs_left(&self) -> usize {
+        let rate = self.0.sample_rate();
+        let frames = self.2.next_preview_left(rate);
+        frames.div_ceil(DecodeQuality::Preview.decimation(rate) as u64) as usize
+    }
+}
+
 #[allow(missing_docs)] // Reason: will be removed, see: #612
 #[derive(Debug)]
 pub enum Mp4Type {
@@ -399,12 +812,54 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<i16> {
-        self.0.next()
+        self.2.start_span(self.0.channels(), self.0.sample_rate());
+        let sample = match self.2.current {
+            DecodeQuality::Full => {
+                let sample = self.0.next()?;
+                self.2.sample_in_frame = (self.2.sample_in_frame + 1) % self.0.channels().max(1);
+                if self.2.sample_in_frame == 0 {
+                    self.2.frames_decoded += 1;
+                }
+                sample
+            }
+            DecodeQuality::Preview => self.next_preview()?,
+        };
+        self.2.span_left -= 1;
+        Some(sample)
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        let (lower, upper) = self.0.size_hint();
+        let divisor = self.decoded_per_output();
+        let (lower, upper) = (lower / divisor, upper.map(|upper| upper.div_ceil(divisor)));
+        match self.2.next_quality() {
+            DecodeQuality::Full => (lower, upper),
+            DecodeQuality::Preview => {
+                let left = self.preview_outputs_left();
+                (
+                    lower.min(left),
+                    Some(upper.map_or(left, |upper| upper.min(left))),
+                )
+            }
+        }
+    }
+}
+
+impl<R> Decoder<R>
+where
+    R: Read + Seek,
+{
+    /// Number of decoded samples each output sample is made of.
+    #[inline]
+    fn decoded_per_output(&self) -> usize {
+        match self.2.next_quality() {
+            DecodeQuality::Full => 1,
+            quality => {
+                let decimation = quality.decimation(self.0.sample_rate());
+                self.0.channels().max(1) as usize * decimation as usize
+            }
+        }
     }
 }
 
@@ -414,16 +869,29 @@ where
 {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
-        self.0.current_frame_len()
+        let divisor = self.decoded_per_output();
+        let span = self.2.next_span(self.0.channels());
+        let len = match self.0.current_frame_len() {
+            Some(len) => len.div_ceil(divisor).min(span),
+            None => span,
+        };
+        match self.2.next_quality() {
+            DecodeQuality::Full => Some(len),
+            DecodeQuality::Preview => Some(len.min(self.preview_outputs_left())),
+        }
     }
 
     #[inline]
     fn channels(&self) -> u16 {
-        self.0.channels()
+        match self.2.next_quality() {
+            DecodeQuality::Full => self.0.channels(),
+            DecodeQuality::Preview => 1,
+        }
     }
 
     fn sample_rate(&self) -> u32 {
-        self.0.sample_rate()
+        let rate = self.0.sample_rate();
+        rate / self.2.next_quality().decimation(rate)
     }
 
     #[inline]
@@ -433,7 +901,10 @@ where
 
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
-        self.0.try_seek(pos)
+        self.0.try_seek(pos)?;
+        // Seeking lands on a frame boundary, a requested quality applies right away.
+        self.2.seeked(self.0.sample_rate());
+        Ok(())
     }
 }
 
@@ -469,6 +940,14 @@ where
                     let sample = source.next();
                     (DecoderImpl::Vorbis(source), sample)
                 }
+                #[cfg(feature = "opus")]
+                DecoderImpl::Opus(source) => {
+                    let mut reader = source.into_inner();
+                    reader.seek(SeekFrom::Start(0)).ok()?;
+                    let mut source = opus::OpusDecoder::open(reader).ok()?;
+                    let sample = source.next();
+                    (DecoderImpl::Opus(source), sample)
+                }
                 #[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
                 DecoderImpl::Flac(source) => {
                     let mut reader = source.into_inner();
@@ -530,6 +1009,11 @@ where
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.0.try_seek(pos)
     }
@@ -541,6 +1025,10 @@ pub enum DecoderError {
     /// The format of the data has not been recognized.
     UnrecognizedFormat,
 
+    /// The data can only be decoded from a source that can seek, see
+    /// [`Decoder::new_streaming`].
+    NotStreamable,
+
     /// An IO error occurred while reading, writing, or seeking the stream.
     #[cfg(feature = "symphonia")]
     IoError(String),
@@ -567,6 +1055,7 @@ impl fmt::Display for DecoderError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text = match self {
             DecoderError::UnrecognizedFormat => "Unrecognized format",
+            DecoderError::NotStreamable => "The format can not be decoded from a stream",
             #[cfg(feature = "symphonia")]
             DecoderError::IoError(msg) => &msg[..],
             #[cfg(feature = "symphonia")]
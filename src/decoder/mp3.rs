@@ -4,6 +4,8 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::CodecInfo;
+
 use minimp3::Decoder;
 use minimp3::Frame;
 use minimp3_fixed as minimp3;
@@ -46,6 +48,16 @@ where
     pub fn into_inner(self) -> R {
         self.decoder.into_inner()
     }
+
+    pub fn codec_info(&self) -> CodecInfo {
+        CodecInfo {
+            codec: "mp3",
+            container: None,
+            bits_per_sample: None,
+            sample_rate: self.sample_rate(),
+            channels: self.channels(),
+        }
+    }
 }
 
 impl<R> Source for Mp3Decoder<R>
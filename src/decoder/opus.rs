@@ -0,0 +1,340 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use crate::source::SeekError;
+use crate::Source;
+
+use super::last_granule::read_last_granule;
+use super::metadata::AudioMetadata;
+use super::CodecInfo;
+
+use audiopus::coder::Decoder as OpusPacketDecoder;
+use audiopus::packet::Packet;
+use audiopus::{Channels, MutSignals, SampleRate};
+use cpal::Sample as CpalSample;
+use ogg::{OggReadError, PacketReader};
+
+/// Granule positions of Ogg Opus streams count samples at this rate, whatever the rate of
+/// the decoded audio.
+const GRANULE_RATE: u64 = 48_000;
+
+/// Decoder for an OGG file that contains Opus sound format.
+///
+/// Only mono and stereo files are supported, files that need several opus streams for more
+/// channels are not recognized.
+pub struct OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    packets: PacketReader<R>,
+    decoder: OpusPacketDecoder,
+    channels: u16,
+    sample_rate: u32,
+    // Samples at the start of the stream that are only there to prime the decoder, at 48 kHz.
+    pre_skip: u64,
+    // Position of the end of the decoded packets, at 48 kHz and including the pre-skip.
+    granule_position: u64,
+    current_data: Vec<f32>,
+    next: usize,
+    // Samples returned so far, of all channels.
+    samples_read: u64,
+    metadata: AudioMetadata,
+    total_duration: Option<Duration>,
+}
+
+impl<R> OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    /// Attempts to decode the data as ogg/opus, hands the data back if it is not.
+    ///
+    /// Data that starts like ogg/opus but can not be read fails with the read error.
+    pub fn new(mut data: R) -> Result<Result<OpusDecoder<R>, OggReadError>, R> {
+        if !is_opus(data.by_ref()) {
+            return Err(data);
+        }
+
+        Ok(Self::open(data))
+    }
+
+    /// Reads the ogg/opus data starting at the current position of `data`.
+    pub fn open(mut data: R) -> Result<OpusDecoder<R>, OggReadError> {
+        let last_granule = read_last_granule(data.by_ref());
+        let mut packets = PacketReader::new(data);
+        let head = packets.read_packet_expected()?;
+        let head = OpusHead::parse(&head.data).ok_or(OggReadError::InvalidData)?;
+        let tags = packets.read_packet_expected()?;
+        let metadata = read_tags(&tags.data).unwrap_or_default();
+
+        // Decode at the rate of the original audio if opus supports it.
+        let rate =
+            SampleRate::try_from(head.input_sample_rate as i32).unwrap_or(SampleRate::Hz48000);
+        let channels = match head.channels {
+            1 => Channels::Mono,
+            2 => Channels::Stereo,
+            _ => return Err(OggReadError::InvalidData),
+        };
+        let decoder =
+            OpusPacketDecoder::new(rate, channels).map_err(|_| OggReadError::InvalidData)?;
+        decoder
+            .set_gain(head.output_gain as i32)
+            .map_err(|_| OggReadError::InvalidData)?;
+
+        let pre_skip = head.pre_skip as u64;
+        let total_duration = last_granule.map(|granule| {
+            let samples = granule.saturating_sub(pre_skip);
+            let nanos = (samples % GRANULE_RATE) * 1_000_000_000 / GRANULE_RATE;
+            Duration::new(samples / GRANULE_RATE, nanos as u32)
+        });
+
+        let mut decoder = OpusDecoder {
+            packets,
+            decoder,
+            channels: head.channels as u16,
+            sample_rate: rate as i32 as u32,
+            pre_skip,
+            granule_position: 0,
+            current_data: Vec::new(),
+            next: 0,
+            samples_read: 0,
+            metadata,
+            total_duration,
+        };
+        decoder.decode_packet();
+        Ok(decoder)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.packets.into_inner()
+    }
+
+    /// Returns true if `pos` lies before the current position, only forward seeks are
+    /// possible without starting over.
+    pub fn is_past(&self, pos: Duration) -> bool {
+        self.samples_at(pos) < self.samples_read
+    }
+
+    /// Returns the channel the next sample is for.
+    pub fn next_channel(&self) -> u16 {
+        (self.samples_read % self.channels as u64) as u16
+    }
+
+    /// Returns the number of samples of all channels played before `pos`, keeping the
+    /// channel the next sample is for.
+    fn samples_at(&self, pos: Duration) -> u64 {
+        let frames = (pos.as_secs_f64() * self.sample_rate as f64) as u64;
+        frames * self.channels as u64 + self.next_channel() as u64
+    }
+
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    pub fn codec_info(&self) -> CodecInfo {
+        CodecInfo {
+            codec: "opus",
+            container: Some("ogg"),
+            bits_per_sample: None,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
+
+    /// Decodes packets until one has samples left after trimming the pre-skip and the end
+    /// of the stream. Returns false at the end of the stream.
+    fn decode_packet(&mut self) -> bool {
+        // Samples of the decoded rate per granule position.
+        let step = GRANULE_RATE / self.sample_rate as u64;
+        let channels = self.channels as usize;
+        // The longest packet opus allows is 120 ms.
+        let max_frames = self.sample_rate as usize * 120 / 1000;
+
+        while let Ok(Some(packet)) = self.packets.read_packet() {
+            self.current_data.resize(max_frames * channels, 0.0);
+            let (Ok(input), Ok(output)) = (
+                Packet::try_from(packet.data.as_slice()),
+                MutSignals::try_from(&mut self.current_data),
+            ) else {
+                continue;
+            };
+            // A packet that does not decode is skipped.
+            let Ok(frames) = self.decoder.decode_float(Some(input), output, false) else {
+                continue;
+            };
+
+            let start_position = self.granule_position;
+            self.granule_position += frames as u64 * step;
+            let start = (self.pre_skip.saturating_sub(start_position) / step) as usize;
+            // The last page ends the stream at its granule position, before the end of the
+            // last packet.
+            let trimmed = match packet.last_in_stream() {
+                true => self.granule_position.saturating_sub(packet.absgp_page()) / step,
+                false => 0,
+            };
+            let end = frames.saturating_sub(trimmed as usize);
+
+            self.current_data.truncate(end * channels);
+            self.next = (start * channels).min(self.current_data.len());
+            if self.next < self.current_data.len() {
+                return true;
+            }
+        }
+        self.current_data.clear();
+        self.next = 0;
+        false
+    }
+}
+
+impl<R> Source for OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.current_data.len() - self.next)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The duration follows from the granule position of the last page, less the pre-skip.
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    /// Seeks forward by decoding and discarding the samples up to `pos`. Seeking beyond the
+    /// end ends the source.
+    ///
+    /// Seeking backwards is not supported, the decoder has to start over from the beginning
+    /// of the file for that.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if self.is_past(pos) {
+            return Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            });
+        }
+
+        let target = self.samples_at(pos);
+        while self.samples_read < target {
+            let left_in_packet = self.current_data.len() - self.next;
+            if left_in_packet == 0 && !self.decode_packet() {
+                break;
+            }
+            let skip = left_in_packet.min((target - self.samples_read) as usize);
+            self.next += skip;
+            self.samples_read += skip as u64;
+        }
+        Ok(())
+    }
+}
+
+impl<R> Iterator for OpusDecoder<R>
+where
+    R: Read + Seek,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        let sample = *self.current_data.get(self.next)?;
+        self.next += 1;
+        self.samples_read += 1;
+        if self.next == self.current_data.len() {
+            self.decode_packet();
+        }
+        Some(CpalSample::from_sample(sample))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.current_data.len() - self.next, None)
+    }
+}
+
+/// Identification header of an Ogg Opus stream.
+struct OpusHead {
+    channels: u8,
+    pre_skip: u16,
+    input_sample_rate: u32,
+    // Gain to apply to the decoded audio, in 1/256 dB.
+    output_gain: i16,
+}
+
+impl OpusHead {
+    /// Parses the header, `None` if it is not one or describes a layout that needs more than
+    /// a single mono or stereo opus stream.
+    fn parse(packet: &[u8]) -> Option<OpusHead> {
+        if packet.len() < 19 || &packet[0..8] != b"OpusHead" || packet[8] >> 4 != 0 {
+            return None;
+        }
+        let head = OpusHead {
+            channels: packet[9],
+            pre_skip: u16::from_le_bytes([packet[10], packet[11]]),
+            input_sample_rate: u32::from_le_bytes([packet[12], packet[13], packet[14], packet[15]]),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+        };
+        let single_stream = match packet[18] {
+            0 => true,
+            // Stream count, coupled stream count and the channel mapping.
+            _ => packet
+                .get(19..21 + head.channels as usize)
+                .is_some_and(|table| {
+                    table[0] == 1
+                        && table[1] == head.channels - 1
+                        && table[2..].iter().copied().eq(0..head.channels)
+                }),
+        };
+        (matches!(head.channels, 1 | 2) && single_stream).then_some(head)
+    }
+}
+
+/// Reads the comment header of an Ogg Opus stream, which holds Vorbis comments.
+fn read_tags(packet: &[u8]) -> Option<AudioMetadata> {
+    fn take<'a>(rest: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        let head = rest.get(..len)?;
+        *rest = &rest[len..];
+        Some(head)
+    }
+    fn take_len(rest: &mut &[u8]) -> Option<usize> {
+        let bytes = take(rest, 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    let mut rest = packet.strip_prefix(b"OpusTags")?;
+    let vendor_len = take_len(&mut rest)?;
+    take(&mut rest, vendor_len)?;
+    let count = take_len(&mut rest)?;
+    let mut comments = Vec::new();
+    for _ in 0..count {
+        let len = take_len(&mut rest)?;
+        comments.push(String::from_utf8_lossy(take(&mut rest, len)?));
+    }
+    Some(AudioMetadata::from_vorbis_comments(
+        comments
+            .iter()
+            .filter_map(|comment| comment.split_once('=')),
+    ))
+}
+
+/// Returns true if the stream contains Opus data, then resets it to where it was.
+fn is_opus<R>(mut data: R) -> bool
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().unwrap();
+
+    let is_opus = PacketReader::new(data.by_ref())
+        .read_packet()
+        .is_ok_and(|packet| packet.is_some_and(|packet| OpusHead::parse(&packet.data).is_some()));
+
+    data.seek(SeekFrom::Start(stream_pos)).unwrap();
+    is_opus
+}
@@ -0,0 +1,75 @@
+use std::io::{Read, Result, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Wraps the reader given to a decoder and keeps track of how far it was read.
+pub(crate) struct ProgressReader<R> {
+    inner: R,
+    position: Arc<AtomicU64>,
+}
+
+/// Read progress of a [`ProgressReader`], shared with the decoder that owns it.
+#[derive(Clone, Debug)]
+pub(crate) struct ReadProgress {
+    position: Arc<AtomicU64>,
+    total: Option<u64>,
+}
+
+impl<R> ProgressReader<R>
+where
+    R: Read + Seek,
+{
+    /// Wraps `inner`. The total size is determined by seeking to the end of the data and back,
+    /// it is `None` if the reader cannot do that.
+    pub(crate) fn new(mut inner: R) -> (ProgressReader<R>, ReadProgress) {
+        let start = inner.stream_position().unwrap_or(0);
+        let total = inner
+            .seek(SeekFrom::End(0))
+            .and_then(|end| inner.seek(SeekFrom::Start(start)).map(|_| end))
+            .ok();
+        let position = Arc::new(AtomicU64::new(start));
+        let progress = ReadProgress {
+            position: position.clone(),
+            total,
+        };
+        (ProgressReader { inner, position }, progress)
+    }
+}
+
+impl<R> Read for ProgressReader<R>
+where
+    R: Read,
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+impl<R> Seek for ProgressReader<R>
+where
+    R: Seek,
+{
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.position.store(position, Ordering::Relaxed);
+        Ok(position)
+    }
+}
+
+impl ReadProgress {
+    /// Offset in bytes up to which the data has been read.
+    #[inline]
+    pub(crate) fn position(&self) -> u64 {
+        self.position.load(Ordering::Relaxed)
+    }
+
+    /// Size of the data in bytes, if known.
+    #[inline]
+    pub(crate) fn total(&self) -> Option<u64> {
+        self.total
+    }
+}
@@ -0,0 +1,120 @@
+/// Sample rate a [`DecodeQuality::Preview`] decoder reduces its output to, at most.
+const PREVIEW_RATE: u32 = 11025;
+
+/// Length of audio in milliseconds a [`DecodeQuality::Preview`] decoder decodes after each
+/// seek before it ends.
+const PREVIEW_WINDOW_MS: u64 = 500;
+
+/// Number of frames between the points at which the quality may change.
+const SPAN_FRAMES: usize = 1024;
+
+/// How faithfully a [`Decoder`](super::Decoder) reproduces the decoded audio.
+///
+/// Set it with [`Decoder::set_quality`](super::Decoder::set_quality).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeQuality {
+    /// Every sample of every channel, exactly as decoded.
+    #[default]
+    Full,
+    /// A rough mono rendition at a reduced rate, for scrubbing through long files.
+    ///
+    /// The channels are averaged to a single one and only one frame out of every few is kept,
+    /// bringing the rate down to at most 11025 Hz without any filtering. This sacrifices the
+    /// stereo image and everything above about 5 kHz, and folds higher frequencies back into
+    /// the audible range as aliasing. Eight times fewer samples (for a 44.1 kHz stereo file)
+    /// reach the rest of the audio pipeline, which makes resampling, mixing and drawing
+    /// waveforms of the preview correspondingly cheaper.
+    ///
+    /// Only half a second of audio is decoded after each seek, or after switching to the
+    /// preview, then the decoder ends until it is seeked again. Scrubbing therefore costs a
+    /// short stretch of decoding per seek target instead of the rest of the file.
+    Preview,
+}
+
+impl DecodeQuality {
+    /// Number of decoded frames each output frame stands for at the given input rate.
+    #[inline]
+    pub(crate) fn decimation(self, sample_rate: u32) -> u32 {
+        match self {
+            DecodeQuality::Full => 1,
+            DecodeQuality::Preview => sample_rate.div_ceil(PREVIEW_RATE).max(1),
+        }
+    }
+}
+
+/// Tracks the quality a decoder is producing and the one it was asked to switch to, along with
+/// the decoded frames.
+#[derive(Default)]
+pub(crate) struct QualityState {
+    pub(crate) current: DecodeQuality,
+    pub(crate) requested: DecodeQuality,
+    // Position inside the current frame at full quality.
+    pub(crate) sample_in_frame: u16,
+    // Complete frames read from the decoder since it was created or last seeked.
+    pub(crate) frames_decoded: u64,
+    // Samples left until the quality may change, reported as the frame length so the format
+    // never changes unannounced. Zero when the next sample starts a new span.
+    pub(crate) span_left: usize,
+    // Frames the preview still decodes before the decoder ends.
+    pub(crate) preview_left: u64,
+}
+
+impl QualityState {
+    /// Returns the quality the next sample is produced with.
+    #[inline]
+    pub(crate) fn next_quality(&self) -> DecodeQuality {
+        if self.span_left == 0 {
+            self.requested
+        } else {
+            self.current
+        }
+    }
+
+    /// Returns the number of samples until the quality may change next.
+    #[inline]
+    pub(crate) fn next_span(&self, channels: u16) -> usize {
+        if self.span_left > 0 {
+            return self.span_left;
+        }
+        match self.requested {
+            DecodeQuality::Full => SPAN_FRAMES * channels.max(1) as usize,
+            DecodeQuality::Preview => SPAN_FRAMES,
+        }
+    }
+
+    /// Returns the number of frames the preview decodes from the next sample on.
+    #[inline]
+    pub(crate) fn next_preview_left(&self, sample_rate: u32) -> u64 {
+        if self.span_left == 0 && self.current != DecodeQuality::Preview {
+            preview_window(sample_rate)
+        } else {
+            self.preview_left
+        }
+    }
+
+    /// Applies the requested quality if the next sample starts a new span.
+    #[inline]
+    pub(crate) fn start_span(&mut self, channels: u16, sample_rate: u32) {
+        if self.span_left > 0 {
+            return;
+        }
+        self.preview_left = self.next_preview_left(sample_rate);
+        self.span_left = self.next_span(channels);
+        self.current = self.requested;
+    }
+
+    /// Starts over after the decoder was seeked, which lands on a frame boundary.
+    #[inline]
+    pub(crate) fn seeked(&mut self, sample_rate: u32) {
+        self.sample_in_frame = 0;
+        self.frames_decoded = 0;
+        self.span_left = 0;
+        self.preview_left = preview_window(sample_rate);
+    }
+}
+
+/// Number of frames the preview decodes after a seek at the given input rate.
+#[inline]
+fn preview_window(sample_rate: u32) -> u64 {
+    sample_rate as u64 * PREVIEW_WINDOW_MS / 1000
+}
@@ -4,23 +4,27 @@ use symphonia::core::io::MediaSource;
 
 pub struct ReadSeekSource<T: Read + Seek + Send + Sync> {
     inner: T,
+    byte_len: Option<u64>,
 }
 
 impl<T: Read + Seek + Send + Sync> ReadSeekSource<T> {
     /// Instantiates a new `ReadSeekSource<T>` by taking ownership and wrapping the provided
-    /// `Read + Seek`er.
-    pub fn new(inner: T) -> Self {
-        ReadSeekSource { inner }
+    /// `Read + Seek`er. The length in bytes lets symphonia estimate the duration of files
+    /// without a header that states it, such as constant bitrate MP3s.
+    pub fn new(inner: T, byte_len: Option<u64>) -> Self {
+        ReadSeekSource { inner, byte_len }
     }
 }
 
 impl<T: Read + Seek + Send + Sync> MediaSource for ReadSeekSource<T> {
+    /// A source without a known length is a stream, see
+    /// [`Decoder::new_streaming`](crate::Decoder::new_streaming).
     fn is_seekable(&self) -> bool {
-        true
+        self.byte_len.is_some()
     }
 
     fn byte_len(&self) -> Option<u64> {
-        None
+        self.byte_len
     }
 }
 
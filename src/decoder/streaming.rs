@@ -0,0 +1,152 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Most bytes read from the start of a stream while its format is detected.
+const PROBE_LIMIT: u64 = 1024 * 1024;
+
+/// Reads a stream that can not seek, for [`Decoder::new_streaming`](super::Decoder::new_streaming).
+///
+/// While the decoder is built the start of the stream is kept, so detecting the format can
+/// read ahead and return. Afterwards the stream is only read forward: seeking forward reads
+/// and discards the bytes in between, seeking backwards fails with
+/// [`io::ErrorKind::Unsupported`].
+pub struct StreamingReader<R> {
+    // Only ever locked through `get_mut`, the mutex makes the reader `Sync` for symphonia.
+    inner: Mutex<R>,
+    // Bytes from the start of the stream, kept until the format is detected.
+    prefix: Option<Vec<u8>>,
+    probing: Arc<AtomicBool>,
+    position: u64,
+    read_pos: u64,
+}
+
+impl<R> StreamingReader<R>
+where
+    R: Read,
+{
+    /// Returns the reader, and the flag that is cleared once the format is detected.
+    pub(crate) fn new(inner: R) -> (StreamingReader<R>, Arc<AtomicBool>) {
+        let probing = Arc::new(AtomicBool::new(true));
+        let reader = StreamingReader {
+            inner: Mutex::new(inner),
+            prefix: Some(Vec::new()),
+            probing: probing.clone(),
+            position: 0,
+            read_pos: 0,
+        };
+        (reader, probing)
+    }
+
+    /// Returns the stream.
+    pub fn into_inner(self) -> R {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads from the stream at the read position.
+    fn read_inner(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = match self.prefix {
+            // Never let detecting the format read an endless stream.
+            Some(_) => buf.len().min((PROBE_LIMIT - self.read_pos) as usize),
+            None => buf.len(),
+        };
+        let inner = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let read = inner.read(&mut buf[..len])?;
+        if let Some(prefix) = &mut self.prefix {
+            prefix.extend_from_slice(&buf[..read]);
+        }
+        self.read_pos += read as u64;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R> Read for StreamingReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(prefix) = &self.prefix {
+            if self.position < self.read_pos {
+                let start = self.position as usize;
+                let len = (prefix.len() - start).min(buf.len());
+                buf[..len].copy_from_slice(&prefix[start..start + len]);
+                self.position += len as u64;
+                return Ok(len);
+            }
+            if !self.probing.load(Ordering::Relaxed) {
+                self.prefix = None;
+            }
+        }
+        self.read_inner(buf)
+    }
+}
+
+impl<R> Seek for StreamingReader<R>
+where
+    R: Read,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        let reachable = match self.prefix {
+            Some(_) => target.filter(|&target| target <= PROBE_LIMIT),
+            None => target.filter(|&target| target >= self.position),
+        };
+        let Some(target) = reachable else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the stream can not seek there",
+            ));
+        };
+
+        if target <= self.read_pos {
+            // Within the kept start of the stream.
+            self.position = target;
+            return Ok(target);
+        }
+        self.position = self.read_pos;
+        let mut skipped = [0u8; 4096];
+        while self.position < target {
+            let len = (target - self.position).min(skipped.len() as u64) as usize;
+            if self.read_inner(&mut skipped[..len])? == 0 {
+                break;
+            }
+        }
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+    use std::sync::atomic::Ordering;
+
+    use super::StreamingReader;
+
+    #[test]
+    fn rewinds_only_while_probing() {
+        let data: Vec<u8> = (0..100).collect();
+        let (mut reader, probing) = StreamingReader::new(data.as_slice());
+        let mut start = [0u8; 10];
+        reader.read_exact(&mut start).unwrap();
+        assert_eq!(reader.seek(SeekFrom::Current(20)).unwrap(), 30);
+        assert_eq!(reader.seek(SeekFrom::Start(5)).unwrap(), 5);
+        assert!(reader.seek(SeekFrom::End(0)).is_err());
+
+        probing.store(false, Ordering::Relaxed);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, data[5..]);
+        assert!(reader.seek(SeekFrom::Start(0)).is_err());
+    }
+}
@@ -7,7 +7,7 @@ use symphonia::{
         errors::Error,
         formats::{FormatOptions, FormatReader, SeekedTo},
         io::MediaSourceStream,
-        meta::MetadataOptions,
+        meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey},
         probe::Hint,
         units::{self, Time},
     },
@@ -16,7 +16,8 @@ use symphonia::{
 
 use crate::{source, Source};
 
-use super::DecoderError;
+use super::metadata::{AudioMetadata, Field, Picture};
+use super::{CodecInfo, DecoderError};
 
 // Decoder errors are not considered fatal.
 // The correct action is to just get a new packet and try again.
@@ -30,6 +31,10 @@ pub(crate) struct SymphoniaDecoder {
     total_duration: Option<Time>,
     buffer: SampleBuffer<i16>,
     spec: SignalSpec,
+    codec: &'static str,
+    container: Option<&'static str>,
+    bits_per_sample: Option<u32>,
+    metadata: AudioMetadata,
 }
 
 impl SymphoniaDecoder {
@@ -57,6 +62,20 @@ impl SymphoniaDecoder {
         self.format.into_inner()
     }
 
+    pub(crate) fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    pub(crate) fn codec_info(&self) -> CodecInfo {
+        CodecInfo {
+            codec: self.codec,
+            container: self.container,
+            bits_per_sample: self.bits_per_sample,
+            sample_rate: self.sample_rate(),
+            channels: self.channels(),
+        }
+    }
+
     fn init(
         mss: MediaSourceStream,
         extension: Option<&str>,
@@ -72,6 +91,20 @@ impl SymphoniaDecoder {
         let metadata_opts: MetadataOptions = Default::default();
         let mut probed = get_probe().format(&hint, mss, &format_opts, &metadata_opts)?;
 
+        // Tags inside the container win over tags in front of it, such as ID3 tags.
+        let mut metadata = AudioMetadata::default();
+        if let Some(revision) = probed.format.metadata().skip_to_latest() {
+            add_revision(&mut metadata, revision);
+        }
+        if let Some(revision) = probed
+            .metadata
+            .get()
+            .as_mut()
+            .and_then(|metadata| metadata.skip_to_latest())
+        {
+            add_revision(&mut metadata, revision);
+        }
+
         let stream = match probed.format.default_track() {
             Some(stream) => stream,
             None => return Ok(None),
@@ -97,6 +130,10 @@ impl SymphoniaDecoder {
 
         let mut decoder = symphonia::default::get_codecs()
             .make(&track.codec_params, &DecoderOptions::default())?;
+        let codec = symphonia::default::get_codecs()
+            .get_codec(track.codec_params.codec)
+            .map_or("unknown", |descriptor| descriptor.short_name);
+        let bits_per_sample = track.codec_params.bits_per_sample;
         let total_duration = stream
             .codec_params
             .time_base
@@ -140,6 +177,10 @@ impl SymphoniaDecoder {
             total_duration,
             buffer,
             spec,
+            codec,
+            container: extension.and_then(container_name),
+            bits_per_sample,
+            metadata,
         }))
     }
 
@@ -171,7 +212,7 @@ impl Source for SymphoniaDecoder {
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
         self.total_duration
-            .map(|Time { seconds, frac }| Duration::new(seconds, (1f64 / frac) as u32))
+            .map(|Time { seconds, frac }| Duration::new(seconds, (frac * 1e9) as u32))
     }
 
     fn try_seek(&mut self, pos: Duration) -> Result<(), source::SeekError> {
@@ -291,6 +332,42 @@ impl SymphoniaDecoder {
     }
 }
 
+/// Adds the tags and pictures of one metadata revision.
+fn add_revision(metadata: &mut AudioMetadata, revision: &MetadataRevision) {
+    for tag in revision.tags() {
+        let field = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => Field::Title,
+            Some(StandardTagKey::Artist) => Field::Artist,
+            Some(StandardTagKey::Album) => Field::Album,
+            Some(StandardTagKey::AlbumArtist) => Field::AlbumArtist,
+            Some(StandardTagKey::Genre) => Field::Genre,
+            Some(StandardTagKey::Date | StandardTagKey::ReleaseDate) => Field::Date,
+            Some(StandardTagKey::TrackNumber) => Field::TrackNumber,
+            _ => continue,
+        };
+        metadata.set(field, &tag.value.to_string());
+    }
+    for visual in revision.visuals() {
+        let picture = Picture {
+            media_type: visual.media_type.clone(),
+            data: visual.data.to_vec(),
+        };
+        metadata.add_picture(picture, visual.usage == Some(StandardVisualKey::FrontCover));
+    }
+}
+
+/// Name of the container for the extension given as hint. The probe does not report which
+/// format reader it picked, so without a hint the container is unknown.
+fn container_name(extension: &str) -> Option<&'static str> {
+    match extension {
+        "wav" => Some("wav"),
+        "ogg" => Some("ogg"),
+        "flac" => Some("flac"),
+        "mp4" | "m4a" | "m4p" | "m4b" | "m4r" | "m4v" | "mov" => Some("mp4"),
+        _ => None,
+    }
+}
+
 fn skip_back_a_tiny_bit(
     Time {
         mut seconds,
@@ -4,6 +4,10 @@ use std::time::Duration;
 use crate::source::SeekError;
 use crate::Source;
 
+use super::last_granule::read_last_granule;
+use super::metadata::AudioMetadata;
+use super::CodecInfo;
+
 use lewton::inside_ogg::OggStreamReader;
 
 /// Decoder for an OGG file that contains Vorbis sound format.
@@ -14,6 +18,10 @@ where
     stream_reader: OggStreamReader<R>,
     current_data: Vec<i16>,
     next: usize,
+    // Samples returned so far, of all channels.
+    samples_read: u64,
+    metadata: AudioMetadata,
+    total_duration: Option<Duration>,
 }
 
 impl<R> VorbisDecoder<R>
@@ -26,10 +34,29 @@ where
             return Err(data);
         }
 
-        let stream_reader = OggStreamReader::new(data).unwrap();
-        Ok(Self::from_stream_reader(stream_reader))
+        Ok(Self::open(data).unwrap())
+    }
+
+    /// Reads the ogg/vorbis data starting at the current position of `data`.
+    pub fn open(mut data: R) -> Result<VorbisDecoder<R>, lewton::VorbisError> {
+        let total_frames = read_last_granule(data.by_ref());
+        let stream_reader = OggStreamReader::new(data)?;
+        let mut decoder = Self::from_stream_reader(stream_reader);
+        let sample_rate = decoder.sample_rate() as u64;
+        decoder.total_duration = total_frames.filter(|_| sample_rate > 0).map(|frames| {
+            let nanos = (frames % sample_rate) * 1_000_000_000 / sample_rate;
+            Duration::new(frames / sample_rate, nanos as u32)
+        });
+        Ok(decoder)
     }
     pub fn from_stream_reader(mut stream_reader: OggStreamReader<R>) -> Self {
+        let comments = &stream_reader.comment_hdr.comment_list;
+        let metadata = AudioMetadata::from_vorbis_comments(
+            comments
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+
         let mut data = match stream_reader.read_dec_packet_itl() {
             Ok(Some(d)) => d,
             _ => Vec::new(),
@@ -45,11 +72,46 @@ where
             stream_reader,
             current_data: data,
             next: 0,
+            samples_read: 0,
+            metadata,
+            total_duration: None,
         }
     }
     pub fn into_inner(self) -> OggStreamReader<R> {
         self.stream_reader
     }
+
+    /// Returns true if `pos` lies before the current position, only forward seeks are
+    /// possible without starting over.
+    pub fn is_past(&self, pos: Duration) -> bool {
+        self.samples_at(pos) < self.samples_read
+    }
+
+    /// Returns the channel the next sample is for.
+    pub fn next_channel(&self) -> u16 {
+        (self.samples_read % self.channels() as u64) as u16
+    }
+
+    /// Returns the number of samples of all channels played before `pos`, keeping the
+    /// channel the next sample is for.
+    fn samples_at(&self, pos: Duration) -> u64 {
+        let frames = (pos.as_secs_f64() * self.sample_rate() as f64) as u64;
+        frames * self.channels() as u64 + self.next_channel() as u64
+    }
+
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    pub fn codec_info(&self) -> CodecInfo {
+        CodecInfo {
+            codec: "vorbis",
+            container: Some("ogg"),
+            bits_per_sample: None,
+            sample_rate: self.sample_rate(),
+            channels: self.channels(),
+        }
+    }
 }
 
 impl<R> Source for VorbisDecoder<R>
@@ -71,25 +133,44 @@ where
         self.stream_reader.ident_hdr.audio_sample_rate
     }
 
+    /// The duration follows from the granule position of the last page. It is unknown for
+    /// decoders built with [`from_stream_reader`](VorbisDecoder::from_stream_reader).
     #[inline]
     fn total_duration(&self) -> Option<Duration> {
-        None
-    }
-
-    /// seek is broken, https://github.com/RustAudio/lewton/issues/73.
-    // We could work around it by:
-    //  - using unsafe to create an instance of Self
-    //  - use mem::swap to turn the &mut self into a mut self
-    //  - take out the underlying Read+Seek
-    //  - make a new self and seek
-    //
-    // If this issue is fixed use the implementation in
-    // commit: 3bafe32388b4eb7a48c6701e6c65044dc8c555e6
-    #[inline]
-    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
-        Err(SeekError::NotSupported {
-            underlying_source: std::any::type_name::<Self>(),
-        })
+        self.total_duration
+    }
+
+    /// Seeks forward by decoding and discarding the samples up to `pos`. Seeking beyond the
+    /// end ends the source.
+    ///
+    /// Seeking backwards is not supported, the decoder has to start over from the beginning
+    /// of the file for that.
+    // lewton's own seek is broken, https://github.com/RustAudio/lewton/issues/73.
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        if self.is_past(pos) {
+            return Err(SeekError::NotSupported {
+                underlying_source: std::any::type_name::<Self>(),
+            });
+        }
+
+        let target = self.samples_at(pos);
+        while self.samples_read < target {
+            let left_in_packet = self.current_data.len().saturating_sub(self.next);
+            if left_in_packet == 0 {
+                match self.stream_reader.read_dec_packet_itl() {
+                    Ok(Some(data)) => {
+                        self.current_data = data;
+                        self.next = 0;
+                        continue;
+                    }
+                    _ => break,
+                }
+            }
+            let skip = left_in_packet.min((target - self.samples_read) as usize);
+            self.next += skip;
+            self.samples_read += skip as u64;
+        }
+        Ok(())
     }
 }
 
@@ -103,6 +184,7 @@ where
     fn next(&mut self) -> Option<i16> {
         if let Some(sample) = self.current_data.get(self.next).copied() {
             self.next += 1;
+            self.samples_read += 1;
             if self.current_data.is_empty() {
                 if let Ok(Some(data)) = self.stream_reader.read_dec_packet_itl() {
                     self.current_data = data;
@@ -117,6 +199,7 @@ where
             }
             let sample = self.current_data.get(self.next).copied();
             self.next += 1;
+            self.samples_read += sample.is_some() as u64;
             sample
         }
     }
@@ -1,9 +1,13 @@
 use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::time::Duration;
 
 use crate::source::SeekError;
 use crate::Source;
 
+use super::metadata::{AudioMetadata, Field};
+use super::CodecInfo;
+
 use hound::{SampleFormat, WavReader};
 
 /// Decoder for the WAV format.
@@ -15,6 +19,8 @@ where
     total_duration: Duration,
     sample_rate: u32,
     channels: u16,
+    loop_points: Option<Range<u64>>,
+    metadata: AudioMetadata,
 }
 
 impl<R> WavDecoder<R>
@@ -27,6 +33,8 @@ where
             return Err(data);
         }
 
+        let loop_points = read_loop_points(data.by_ref());
+        let metadata = read_info_tags(data.by_ref());
         let reader = WavReader::new(data).unwrap();
         let spec = reader.spec();
         let len = reader.len() as u64;
@@ -45,11 +53,37 @@ where
             total_duration,
             sample_rate,
             channels,
+            loop_points,
+            metadata,
         })
     }
     pub fn into_inner(self) -> R {
         self.reader.reader.into_inner()
     }
+
+    /// Returns the first loop of the `smpl` chunk, in frames.
+    pub fn loop_points(&self) -> Option<Range<u64>> {
+        self.loop_points.clone()
+    }
+
+    /// Returns the tags of the `INFO` list.
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    pub fn codec_info(&self) -> CodecInfo {
+        let spec = self.reader.reader.spec();
+        CodecInfo {
+            codec: match spec.sample_format {
+                SampleFormat::Int => "pcm",
+                SampleFormat::Float => "pcm_float",
+            },
+            container: Some("wav"),
+            bits_per_sample: Some(spec.bits_per_sample as u32),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
 }
 
 struct SamplesIterator<R>
@@ -189,6 +223,127 @@ where
     true
 }
 
+/// Reads the first loop of the `smpl` chunk, if any, and returns to where the data started.
+fn read_loop_points<R>(mut data: R) -> Option<Range<u64>>
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().unwrap();
+    let loop_points = find_smpl_loop(data.by_ref());
+    data.seek(SeekFrom::Start(stream_pos)).unwrap();
+    loop_points
+}
+
+fn find_smpl_loop<R>(mut data: R) -> Option<Range<u64>>
+where
+    R: Read + Seek,
+{
+    // Offsets in the `smpl` chunk, which is followed by a list of 24 byte loops.
+    const NUM_LOOPS: usize = 28;
+    const FIRST_LOOP_START: usize = 36 + 8;
+    const FIRST_LOOP_END: usize = 36 + 12;
+    const MIN_LEN: usize = 36 + 24;
+
+    let mut header = [0u8; 12];
+    data.read_exact(&mut header).ok()?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let read_u32 = |bytes: &[u8], at: usize| {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    };
+    loop {
+        let mut chunk = [0u8; 8];
+        data.read_exact(&mut chunk).ok()?;
+        let len = read_u32(&chunk, 4) as u64;
+        if &chunk[0..4] != b"smpl" {
+            // Chunks are padded to an even length.
+            data.seek(SeekFrom::Current((len + len % 2) as i64)).ok()?;
+            continue;
+        }
+
+        if len < MIN_LEN as u64 {
+            return None;
+        }
+        let mut smpl = [0u8; MIN_LEN];
+        data.read_exact(&mut smpl).ok()?;
+        if read_u32(&smpl, NUM_LOOPS) == 0 {
+            return None;
+        }
+        // The end of a loop is the last frame played, not the first one after it.
+        let start = read_u32(&smpl, FIRST_LOOP_START) as u64;
+        let end = read_u32(&smpl, FIRST_LOOP_END) as u64 + 1;
+        return (start < end).then_some(start..end);
+    }
+}
+
+/// Reads the tags of the `LIST` chunk of type `INFO`, if any, and returns to where the data
+/// started.
+fn read_info_tags<R>(mut data: R) -> AudioMetadata
+where
+    R: Read + Seek,
+{
+    let stream_pos = data.stream_position().unwrap();
+    let mut metadata = AudioMetadata::default();
+    find_info_tags(data.by_ref(), &mut metadata);
+    data.seek(SeekFrom::Start(stream_pos)).unwrap();
+    metadata
+}
+
+fn find_info_tags<R>(mut data: R, metadata: &mut AudioMetadata) -> Option<()>
+where
+    R: Read + Seek,
+{
+    let mut header = [0u8; 12];
+    data.read_exact(&mut header).ok()?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let read_u32 = |bytes: &[u8], at: usize| {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    };
+    let info = loop {
+        let mut chunk = [0u8; 12];
+        data.read_exact(&mut chunk[..8]).ok()?;
+        let len = read_u32(&chunk, 4) as u64;
+        if &chunk[0..4] == b"LIST" && len >= 4 {
+            data.read_exact(&mut chunk[8..]).ok()?;
+            if &chunk[8..12] == b"INFO" {
+                let mut info = vec![0u8; len as usize - 4];
+                data.read_exact(&mut info).ok()?;
+                break info;
+            }
+            data.seek(SeekFrom::Current((len - 4 + len % 2) as i64))
+                .ok()?;
+            continue;
+        }
+        // Chunks are padded to an even length.
+        data.seek(SeekFrom::Current((len + len % 2) as i64)).ok()?;
+    };
+
+    let mut rest = info.as_slice();
+    while rest.len() >= 8 {
+        let len = read_u32(rest, 4) as usize;
+        let value = rest.get(8..8 + len)?;
+        let field = match &rest[0..4] {
+            b"INAM" => Some(Field::Title),
+            b"IART" => Some(Field::Artist),
+            b"IPRD" => Some(Field::Album),
+            b"IGNR" => Some(Field::Genre),
+            b"ICRD" => Some(Field::Date),
+            b"ITRK" | b"IPRT" => Some(Field::TrackNumber),
+            _ => None,
+        };
+        if let Some(field) = field {
+            metadata.set(field, &String::from_utf8_lossy(value));
+        }
+        rest = rest.get(8 + len + len % 2..).unwrap_or_default();
+    }
+    Some(())
+}
+
 /// Returns a 32 bit WAV float as an i16. WAV floats are typically in the range of
 /// [-1.0, 1.0] while i16s are in the range [-32768, 32767]. Note that this
 /// function definitely causes precision loss but hopefully this isn't too
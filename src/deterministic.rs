@@ -0,0 +1,46 @@
+//! Reproducible output, for comparing rendered audio against golden files.
+//!
+//! Most of rodio always produces the same samples for the same input: decoding, resampling,
+//! channel conversion, mixing and all the filters are free of randomness. Rodio does not dither.
+//! The only randomized components are the noise generators, which seed themselves from system
+//! entropy. Once [`set_deterministic`] is enabled:
+//!
+//! - [`WhiteNoise::new`](crate::source::WhiteNoise::new), [`white`](crate::source::white),
+//!   [`PinkNoise::new`](crate::source::PinkNoise::new) and [`pink`](crate::source::pink) take
+//!   their seeds from a fixed sequence instead. The n-th generator created after enabling the
+//!   mode always gets the same seed, so different generators still produce different noise.
+//!
+//! Generators seeded explicitly, like
+//! [`WhiteNoise::new_with_seed`](crate::source::WhiteNoise::new_with_seed), are not affected.
+//!
+//! Playing through an output device is never reproducible, because where a source starts
+//! depends on when the audio callback runs. Render the chain by iterating over it instead.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// First seed of the sequence.
+const SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+static NEXT_SEED: AtomicU64 = AtomicU64::new(SEED);
+
+/// Enables or disables deterministic mode for the whole process, see the
+/// [module documentation](self).
+///
+/// Enabling it again restarts the sequence of seeds, so a chain rendered after each call
+/// produces identical samples.
+pub fn set_deterministic(enabled: bool) {
+    NEXT_SEED.store(SEED, Ordering::SeqCst);
+    DETERMINISTIC.store(enabled, Ordering::SeqCst);
+}
+
+/// Returns whether deterministic mode is enabled.
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::SeqCst)
+}
+
+/// Returns the seed for the next randomized component, `None` if it should use entropy.
+#[cfg_attr(not(feature = "noise"), allow(dead_code))]
+pub(crate) fn next_seed() -> Option<u64> {
+    is_deterministic().then(|| NEXT_SEED.fetch_add(1, Ordering::SeqCst))
+}
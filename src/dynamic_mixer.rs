@@ -1,18 +1,64 @@
 //! Mixer that plays multiple sounds at the same time.
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 
-use crate::source::{SeekError, Source, UniformSourceIterator};
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver, Sender};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::source::{AntiAlias, SeekError, Source, UniformSourceIterator};
 use crate::Sample;
 
+/// How long a stolen voice takes to fade out.
+const STEAL_FADE: Duration = Duration::from_millis(5);
+
+/// How fast the level of a voice, used to find the quietest one, decays per sample.
+const LEVEL_DECAY: f32 = 0.9995;
+
+/// How fast the limiter lowers the gain once a peak exceeds its threshold.
+const LIMITER_ATTACK: Duration = Duration::from_millis(1);
+
+/// Width of the range around the limiter threshold in which the gain is lowered gradually.
+const LIMITER_KNEE_DB: f32 = 6.0;
+
+/// Which voice is stopped when a source is added to a mixer that already plays its maximum
+/// number of voices. See [`DynamicMixerController::set_max_voices`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoiceStealing {
+    /// Stops the voice that has been playing the longest.
+    #[default]
+    Oldest,
+    /// Stops the voice with the lowest recent peak level, the one least likely to be missed.
+    Quietest,
+}
+
+/// How the mixer converts sources to its sample rate.
+/// See [`DynamicMixerController::set_resample_quality`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Interpolates linearly between frames. Downsampling folds the frequencies above half the
+    /// output rate back into the audible range as aliasing.
+    Linear,
+    /// Low-pass filters sources with a higher rate than the mixer before interpolating, which
+    /// removes the frequencies the output can not represent. This costs a fourth order filter
+    /// per channel of each downsampled source, other sources are not affected.
+    #[default]
+    AntiAliased,
+}
+
 /// Builds a new mixer.
 ///
 /// You can choose the characteristics of the output thanks to this constructor. All the sounds
 /// added to the mixer will be converted to these values.
 ///
 /// After creating a mixer, you can add new sounds with the controller.
+///
+/// The sounds are mixed in the sample type `S` without converting them to `f32`. Integer
+/// samples such as `i16` are summed with saturation, so a mix louder than full scale clips
+/// instead of wrapping around.
 pub fn mixer<S>(
     channels: u16,
     sample_rate: u32,
@@ -20,30 +66,80 @@ pub fn mixer<S>(
 where
     S: Sample + Send + 'static,
 {
+    let (commands_tx, commands_rx) = channel();
     let input = Arc::new(DynamicMixerController {
         has_pending: AtomicBool::new(false),
-        pending_sources: Mutex::new(Vec::new()),
+        commands: commands_tx,
+        next_id: AtomicU64::new(0),
         channels,
         sample_rate,
+        max_voices: AtomicUsize::new(usize::MAX),
+        voice_stealing: AtomicU8::new(VoiceStealing::Oldest as u8),
+        resample_quality: AtomicU8::new(ResampleQuality::AntiAliased as u8),
+        voices: AtomicUsize::new(0),
+        active: AtomicUsize::new(0),
+        volume: AtomicU32::new(1.0f32.to_bits()),
+        mono: AtomicBool::new(false),
+        limiter_threshold: AtomicU32::new(f32::INFINITY.to_bits()),
+        limiter_release: AtomicU32::new(0.0f32.to_bits()),
     });
 
     let output = DynamicMixer {
         current_sources: Vec::with_capacity(16),
+        steal_fade_len: (STEAL_FADE.as_secs_f32() * sample_rate as f32) as u32 * channels as u32,
         input: input.clone(),
+        commands: commands_rx,
         sample_count: 0,
+        pending_sources: vec![],
         still_pending: vec![],
         still_current: vec![],
+        mono_frame: None,
+        limited_frame: Vec::with_capacity(channels as usize),
+        limiter_gain_db: 0.0,
+        ran_dry: false,
     };
 
     (input, output)
 }
 
+/// Identifies a source added to the mixer, see [`DynamicMixerController::remove`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SourceId(u64);
+
+/// A source waiting to start playing in the mixer.
+type PendingSource<S> = (SourceId, Box<dyn Source<Item = S> + Send>);
+
+/// Changes to the sources of the mixer, applied in the order they were sent.
+enum Command<S> {
+    Add(PendingSource<S>),
+    // Sources that start on the same sample.
+    AddSynchronized(Vec<PendingSource<S>>),
+    Remove(SourceId),
+}
+
 /// The input of the mixer.
 pub struct DynamicMixerController<S> {
+    // Set after sending a command, or when the voice limit changed.
     has_pending: AtomicBool,
-    pending_sources: Mutex<Vec<Box<dyn Source<Item = S> + Send>>>,
+    // Never blocks either side, so adding sources does not hold up the audio thread.
+    commands: Sender<Command<S>>,
+    next_id: AtomicU64,
     channels: u16,
     sample_rate: u32,
+    // `usize::MAX` if there is no limit.
+    max_voices: AtomicUsize,
+    voice_stealing: AtomicU8,
+    resample_quality: AtomicU8,
+    // Number of voices playing, not counting stolen ones fading out. Updated by the mixer.
+    voices: AtomicUsize,
+    // Number of sources summed, including stolen ones. Updated by the mixer.
+    active: AtomicUsize,
+    volume: AtomicU32,
+    mono: AtomicBool,
+    // Infinite while the limiter is off.
+    limiter_threshold: AtomicU32,
+    // Release time in seconds.
+    limiter_release: AtomicU32,
 }
 
 impl<S> DynamicMixerController<S>
@@ -51,36 +147,390 @@ where
     S: Sample + Send + 'static,
 {
     /// Adds a new source to mix to the existing ones.
+    ///
+    /// Returns an id that can be passed to [`remove`](DynamicMixerController::remove) to stop
+    /// the source before it ends.
+    #[inline]
+    pub fn add<T>(&self, source: T) -> SourceId
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let id = self.next_id();
+        let uniform_source = self.uniform(source);
+        self.send(Command::Add((id, uniform_source)));
+        id
+    }
+
+    /// Stops a source added with [`add`](DynamicMixerController::add), it is dropped at the
+    /// start of the next sample the mixer produces.
+    ///
+    /// Can be called from any thread. Does nothing if the source already ended or was removed.
+    pub fn remove(&self, id: SourceId) {
+        self.send(Command::Remove(id));
+    }
+
+    fn next_id(&self) -> SourceId {
+        SourceId(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn send(&self, command: Command<S>) {
+        // Fails only once the mixer was dropped, then there is nothing left to play on.
+        let _ = self.commands.send(command);
+        self.has_pending.store(true, Ordering::Release);
+    }
+
+    /// Adds a new source to mix, and returns a handle that changes the volume of just this
+    /// source while it plays.
+    ///
+    /// The volume is read once per frame, so changes apply from the next frame on.
+    pub fn add_with_volume<T>(&self, source: T) -> SourceVolumeHandle
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        let volume = Arc::new(AtomicU32::new(1.0f32.to_bits()));
+        let handle = SourceVolumeHandle {
+            volume: Arc::downgrade(&volume),
+        };
+        self.add(SourceVolume {
+            input: source,
+            volume,
+            gain: 1.0,
+            sample_in_frame: 0,
+        });
+        handle
+    }
+
+    /// Adds a new source to mix without converting its channels or sample rate.
+    ///
+    /// The source must already produce samples in the mixer's format: its `channels()` and
+    /// `sample_rate()` have to match the mixer's for its entire lifetime. If the format does not
+    /// match when it is added the source is handed back in the `Err` variant.
+    #[inline]
+    pub fn add_direct<T>(&self, source: T) -> Result<(), T>
+    where
+        T: Source<Item = S> + Send + 'static,
+    {
+        if source.channels() != self.channels || source.sample_rate() != self.sample_rate {
+            return Err(source);
+        }
+        let id = self.next_id();
+        self.send(Command::Add((id, Box::new(source) as Box<_>)));
+        Ok(())
+    }
+
+    /// Returns the number of channels the mixer outputs.
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Returns the sample rate the mixer outputs.
     #[inline]
-    pub fn add<T>(&self, source: T)
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Changes the gain applied to the mixed output, on top of the volume of every source.
+    ///
+    /// Negative values are treated as zero.
+    pub fn set_volume(&self, volume: f32) {
+        self.volume
+            .store(volume.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the gain applied to the mixed output, 1.0 by default.
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+
+    /// Mixes all channels to one, and plays the result on every channel.
+    ///
+    /// For listeners who hear with one ear only, so that nothing panned to the other side is
+    /// lost. Takes effect at the start of the next frame.
+    pub fn set_mono(&self, mono: bool) {
+        self.mono.store(mono, Ordering::Relaxed);
+    }
+
+    /// Returns true while all channels are mixed to one, see
+    /// [`set_mono`](DynamicMixerController::set_mono).
+    pub fn is_mono(&self) -> bool {
+        self.mono.load(Ordering::Relaxed)
+    }
+
+    /// Turns on a limiter on the mixed output, which keeps its peaks below `threshold`.
+    ///
+    /// Instead of clipping, the limiter lowers the gain of whole frames, so all channels are
+    /// attenuated together. It starts lowering the gain 3 dB below the threshold and reacts
+    /// within about a millisecond; once the peaks fall again, the gain recovers over `release`.
+    /// A longer release avoids audible pumping. Peaks that rise faster than the limiter reacts
+    /// may briefly exceed the threshold.
+    ///
+    /// The threshold is an amplitude, 1.0 is full scale. The limiter is off by default.
+    pub fn set_limiter(&self, threshold: f32, release: Duration) {
+        self.limiter_release
+            .store(release.as_secs_f32().to_bits(), Ordering::Relaxed);
+        self.limiter_threshold.store(
+            threshold.max(f32::MIN_POSITIVE).to_bits(),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Turns off the limiter, see [`set_limiter`](DynamicMixerController::set_limiter).
+    pub fn disable_limiter(&self) {
+        self.limiter_threshold
+            .store(f32::INFINITY.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Limits how many sources play at the same time, `None` removes the limit.
+    ///
+    /// When a source is added while the limit is reached, an already playing voice is stolen
+    /// to make room: it fades out over 5 milliseconds and is then dropped. Which voice is
+    /// stolen is chosen by [`set_voice_stealing`](DynamicMixerController::set_voice_stealing),
+    /// by default the oldest one. Lowering the limit steals voices right away. A limit of zero
+    /// is treated as one.
+    ///
+    /// This keeps the mixing cost bounded, for example when a game triggers many sound effects
+    /// in a short time.
+    pub fn set_max_voices(&self, max_voices: Option<usize>) {
+        let max_voices = max_voices.map_or(usize::MAX, |max| max.max(1));
+        self.max_voices.store(max_voices, Ordering::Relaxed);
+        // Lets the mixer apply the new limit on its next sample.
+        self.has_pending.store(true, Ordering::Release);
+    }
+
+    /// Returns the maximum number of sources playing at the same time, if limited.
+    pub fn max_voices(&self) -> Option<usize> {
+        let max_voices = self.max_voices.load(Ordering::Relaxed);
+        (max_voices != usize::MAX).then_some(max_voices)
+    }
+
+    /// Chooses which voice is stolen when the voice limit is reached.
+    pub fn set_voice_stealing(&self, voice_stealing: VoiceStealing) {
+        self.voice_stealing
+            .store(voice_stealing as u8, Ordering::Relaxed);
+    }
+
+    /// Chooses how sources with another sample rate than the mixer are converted.
+    ///
+    /// Only applies to sources added afterwards. Defaults to [`ResampleQuality::AntiAliased`],
+    /// which matters most when the device runs at a low rate, for example when a fallback
+    /// configuration was picked while opening the output stream.
+    pub fn set_resample_quality(&self, quality: ResampleQuality) {
+        self.resample_quality
+            .store(quality as u8, Ordering::Relaxed);
+    }
+
+    /// Returns how sources with another sample rate than the mixer are converted.
+    pub fn resample_quality(&self) -> ResampleQuality {
+        if self.resample_quality.load(Ordering::Relaxed) == ResampleQuality::Linear as u8 {
+            ResampleQuality::Linear
+        } else {
+            ResampleQuality::AntiAliased
+        }
+    }
+
+    /// Converts a source to the channels and sample rate of the mixer.
+    fn uniform<T>(&self, source: T) -> Box<dyn Source<Item = S> + Send>
     where
         T: Source<Item = S> + Send + 'static,
     {
-        let uniform_source = UniformSourceIterator::new(source, self.channels, self.sample_rate);
-        self.pending_sources
-            .lock()
-            .unwrap()
-            .push(Box::new(uniform_source) as Box<_>);
-        self.has_pending.store(true, Ordering::SeqCst); // TODO: can we relax this ordering?
+        match self.resample_quality() {
+            ResampleQuality::Linear => Box::new(UniformSourceIterator::new(
+                source,
+                self.channels,
+                self.sample_rate,
+            )),
+            ResampleQuality::AntiAliased => Box::new(UniformSourceIterator::new(
+                AntiAlias::new(source, self.sample_rate),
+                self.channels,
+                self.sample_rate,
+            )),
+        }
+    }
+
+    /// Returns the number of sources currently playing.
+    ///
+    /// Stolen voices that are still fading out and sources that were just added but did not
+    /// start yet are not counted.
+    pub fn voices(&self) -> usize {
+        self.voices.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of sources the mixer summed in its last pass, to keep an eye on the
+    /// cost of mixing.
+    ///
+    /// Unlike [`voices`](DynamicMixerController::voices) this includes stolen voices that are
+    /// still fading out. Sources that were just added count once the mixer picked them up.
+    pub fn active_count(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Adds several sources that will all start playing on the same sample.
+    ///
+    /// The sources are handed to the mixer at once, so the mixer picks all of them up during
+    /// the same call to `next()`.
+    pub fn add_synchronized<I>(&self, sources: I)
+    where
+        I: IntoIterator<Item = Box<dyn Source<Item = S> + Send>>,
+    {
+        let uniform_sources: Vec<_> = sources
+            .into_iter()
+            .map(|source| (self.next_id(), self.uniform(source)))
+            .collect();
+        if uniform_sources.is_empty() {
+            return;
+        }
+        self.send(Command::AddSynchronized(uniform_sources));
+    }
+}
+
+/// Controls the volume of a single source in the mixer, see
+/// [`DynamicMixerController::add_with_volume`].
+///
+/// The handle does not keep the source alive. Once the source finished, or the mixer was
+/// dropped, it has no effect anymore.
+#[derive(Debug)]
+pub struct SourceVolumeHandle {
+    volume: Weak<AtomicU32>,
+}
+
+impl SourceVolumeHandle {
+    /// Changes the gain applied to the source, 1.0 plays it unchanged.
+    ///
+    /// Negative values are treated as zero.
+    pub fn set_volume(&self, volume: f32) {
+        if let Some(target) = self.volume.upgrade() {
+            target.store(volume.max(0.0).to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the gain applied to the source, `None` once it finished.
+    pub fn volume(&self) -> Option<f32> {
+        let volume = self.volume.upgrade()?;
+        Some(f32::from_bits(volume.load(Ordering::Relaxed)))
+    }
+}
+
+/// Scales a source by the volume of its [`SourceVolumeHandle`].
+struct SourceVolume<I> {
+    input: I,
+    volume: Arc<AtomicU32>,
+    // Volume of the current frame.
+    gain: f32,
+    sample_in_frame: u16,
+}
+
+impl<I> Iterator for SourceVolume<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            self.gain = f32::from_bits(self.volume.load(Ordering::Relaxed));
+        }
+
+        let value = self.input.next()?;
+        self.sample_in_frame = (self.sample_in_frame + 1) % self.input.channels().max(1);
+        if self.gain == 1.0 {
+            Some(value)
+        } else {
+            Some(value.amplify(self.gain))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for SourceVolume<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
     }
 }
 
+/// A source playing in the mixer.
+struct Voice<S> {
+    id: SourceId,
+    source: Box<dyn Source<Item = S> + Send>,
+    // Remaining samples of the fade out once the voice was stolen.
+    stolen: Option<u32>,
+    // Recent peak level, to find the quietest voice.
+    level: f32,
+}
+
 /// The output of the mixer. Implements `Source`.
 pub struct DynamicMixer<S> {
-    // The current iterator that produces samples.
-    current_sources: Vec<Box<dyn Source<Item = S> + Send>>,
+    // The current iterator that produces samples, oldest first.
+    current_sources: Vec<Voice<S>>,
+
+    // Length of the fade out of a stolen voice, in samples.
+    steal_fade_len: u32,
 
     // The pending sounds.
     input: Arc<DynamicMixerController<S>>,
 
+    // Sources added and removed through the controller.
+    commands: Receiver<Command<S>>,
+
     // The number of samples produced so far.
     sample_count: usize,
 
+    // Sources received that wait for their first channel to come up.
+    pending_sources: Vec<PendingSource<S>>,
+
     // A temporary vec used in start_pending_sources.
-    still_pending: Vec<Box<dyn Source<Item = S> + Send>>,
+    still_pending: Vec<PendingSource<S>>,
 
     // A temporary vec used in sum_current_sources.
-    still_current: Vec<Box<dyn Source<Item = S> + Send>>,
+    still_current: Vec<Voice<S>>,
+
+    // Average of the frame mixed to mono, and how many of its samples are still to return.
+    mono_frame: Option<(S, u16)>,
+
+    // Samples of the frame the limiter processed that are still to return, last one first.
+    limited_frame: Vec<S>,
+
+    // Smoothed gain of the limiter.
+    limiter_gain_db: f32,
+
+    // An infinite source ended since the last `take_ran_dry`.
+    ran_dry: bool,
 }
 
 impl<S> Source for DynamicMixer<S>
@@ -152,18 +602,20 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<S> {
-        if self.input.has_pending.load(Ordering::SeqCst) {
-            self.start_pending_sources();
+        if let Some(value) = self.limited_frame.pop() {
+            return Some(value);
         }
 
-        self.sample_count += 1;
-
-        let sum = self.sum_current_sources();
-
-        if self.current_sources.is_empty() {
-            None
+        let threshold = f32::from_bits(self.input.limiter_threshold.load(Ordering::Relaxed));
+        let frame_start = self.mono_frame.is_none()
+            && self
+                .sample_count
+                .is_multiple_of(self.input.channels as usize);
+        if threshold.is_finite() && frame_start {
+            self.next_limited_frame(threshold)
         } else {
-            Some(sum)
+            self.limiter_gain_db = 0.0;
+            self.next_mixed()
         }
     }
 
@@ -177,39 +629,216 @@ impl<S> DynamicMixer<S>
 where
     S: Sample + Send + 'static,
 {
+    /// Returns true if a source that reported to be infinite ended since the last call, meaning
+    /// it ran out of samples rather than reaching its end.
+    pub(crate) fn take_ran_dry(&mut self) -> bool {
+        std::mem::take(&mut self.ran_dry)
+    }
+
+    // Returns the next sample of the mix, before the limiter.
+    #[inline]
+    fn next_mixed(&mut self) -> Option<S> {
+        if let Some((value, left)) = self.mono_frame {
+            self.mono_frame = (left > 1).then_some((value, left - 1));
+            return Some(value);
+        }
+
+        if !self.pending_sources.is_empty() || self.input.has_pending.load(Ordering::Acquire) {
+            self.start_pending_sources();
+        }
+
+        let channels = self.input.channels;
+        let mono = channels > 1
+            && self.input.is_mono()
+            && self.sample_count.is_multiple_of(channels as usize);
+        let sum = if mono {
+            // Mixes the whole frame now, its other samples return the same average.
+            let gain = 1.0 / channels as f32;
+            let mut mean = S::zero_value();
+            for _ in 0..channels {
+                self.sample_count += 1;
+                mean = mean.saturating_add(self.sum_current_sources().amplify(gain));
+            }
+            mean
+        } else {
+            self.sample_count += 1;
+            self.sum_current_sources()
+        };
+
+        if self.current_sources.is_empty() {
+            None
+        } else {
+            let volume = self.input.volume();
+            let value = if volume == 1.0 {
+                sum
+            } else {
+                sum.amplify(volume)
+            };
+            if mono {
+                self.mono_frame = Some((value, channels - 1));
+            }
+            Some(value)
+        }
+    }
+
+    // Mixes a whole frame, lowers its gain so its peak stays below the threshold and returns
+    // its first sample.
+    fn next_limited_frame(&mut self, threshold: f32) -> Option<S> {
+        let mut peak = 0.0f32;
+        for _ in 0..self.input.channels {
+            let Some(value) = self.next_mixed() else {
+                break;
+            };
+            peak = peak.max(value.to_f32().abs());
+            self.limited_frame.push(value);
+        }
+
+        // Soft knee with an infinite ratio: above the knee the output stays at the threshold.
+        let over_db = 20.0 * (peak / threshold).log10();
+        let target_db = if over_db <= -LIMITER_KNEE_DB / 2.0 {
+            0.0
+        } else if over_db >= LIMITER_KNEE_DB / 2.0 {
+            -over_db
+        } else {
+            let into_knee = over_db + LIMITER_KNEE_DB / 2.0;
+            -into_knee * into_knee / (2.0 * LIMITER_KNEE_DB)
+        };
+
+        let time = if target_db < self.limiter_gain_db {
+            LIMITER_ATTACK.as_secs_f32()
+        } else {
+            f32::from_bits(self.input.limiter_release.load(Ordering::Relaxed))
+        };
+        let frames = time * self.input.sample_rate as f32;
+        let step = if frames > 1.0 {
+            1.0 - (-1.0 / frames).exp()
+        } else {
+            1.0
+        };
+        self.limiter_gain_db += (target_db - self.limiter_gain_db) * step;
+
+        if self.limiter_gain_db < 0.0 {
+            let gain = 10f32.powf(self.limiter_gain_db / 20.0);
+            for value in &mut self.limited_frame {
+                *value = value.amplify(gain);
+            }
+        }
+        self.limited_frame.reverse();
+        self.limited_frame.pop()
+    }
+
     // Samples from the #next() function are interlaced for each of the channels.
     // We need to ensure we start playing sources so that their samples are
     // in-step with the modulo of the samples produced so far. Otherwise, the
     // sound will play on the wrong channels, e.g. left / right will be reversed.
     fn start_pending_sources(&mut self) {
-        let mut pending = self.input.pending_sources.lock().unwrap(); // TODO: relax ordering?
+        // Cleared before receiving, a command sent meanwhile sets it again.
+        self.input.has_pending.swap(false, Ordering::Acquire);
+        while let Ok(command) = self.commands.try_recv() {
+            match command {
+                Command::Add(source) => self.pending_sources.push(source),
+                Command::AddSynchronized(sources) => self.pending_sources.extend(sources),
+                Command::Remove(id) => {
+                    self.current_sources.retain(|voice| voice.id != id);
+                    self.pending_sources.retain(|(pending, _)| *pending != id);
+                }
+            }
+        }
+        let started_before = self.current_sources.len();
 
-        for source in pending.drain(..) {
-            let in_step = self.sample_count % source.channels() as usize == 0;
+        for (id, source) in self.pending_sources.drain(..) {
+            let in_step = self.sample_count.is_multiple_of(source.channels() as usize);
 
             if in_step {
-                self.current_sources.push(source);
+                self.current_sources.push(Voice {
+                    id,
+                    source,
+                    stolen: None,
+                    level: 0.0,
+                });
+            } else {
+                self.still_pending.push((id, source));
+            }
+        }
+        std::mem::swap(&mut self.still_pending, &mut self.pending_sources);
+
+        self.steal_voices(started_before);
+    }
+
+    // Fades out voices until no more than the maximum number of voices play. Voices from
+    // `just_started` on were added now and are only stolen if there is no other choice.
+    fn steal_voices(&mut self, just_started: usize) {
+        let max_voices = self.input.max_voices.load(Ordering::Relaxed);
+        let mut playing = self.playing_voices();
+        while playing > max_voices {
+            let quietest =
+                self.input.voice_stealing.load(Ordering::Relaxed) == VoiceStealing::Quietest as u8;
+            let older_playing = self.current_sources[..just_started]
+                .iter()
+                .any(|voice| voice.stolen.is_none());
+            let range = if older_playing {
+                ..just_started
             } else {
-                self.still_pending.push(source);
+                ..self.current_sources.len()
+            };
+            let mut candidates = self.current_sources[range]
+                .iter_mut()
+                .filter(|voice| voice.stolen.is_none());
+            // Voices are kept in the order they started, the first one is the oldest.
+            let victim = if quietest {
+                candidates.min_by(|a, b| a.level.total_cmp(&b.level))
+            } else {
+                candidates.next()
+            };
+            if let Some(victim) = victim {
+                victim.stolen = Some(self.steal_fade_len);
             }
+            playing -= 1;
         }
-        std::mem::swap(&mut self.still_pending, &mut pending);
+        self.input.voices.store(playing, Ordering::Relaxed);
+        self.input
+            .active
+            .store(self.current_sources.len(), Ordering::Relaxed);
+    }
 
-        let has_pending = !pending.is_empty();
-        self.input.has_pending.store(has_pending, Ordering::SeqCst); // TODO: relax ordering?
+    fn playing_voices(&self) -> usize {
+        self.current_sources
+            .iter()
+            .filter(|voice| voice.stolen.is_none())
+            .count()
     }
 
     fn sum_current_sources(&mut self) -> S {
         let mut sum = S::zero_value();
+        let count = self.current_sources.len();
 
-        for mut source in self.current_sources.drain(..) {
-            if let Some(value) = source.next() {
-                sum = sum.saturating_add(value);
-                self.still_current.push(source);
+        for mut voice in self.current_sources.drain(..) {
+            let Some(mut value) = voice.source.next() else {
+                // Infinite sources do not end on their own, this one had nothing to play.
+                self.ran_dry |= voice.source.is_infinite();
+                continue;
+            };
+            voice.level = (voice.level * LEVEL_DECAY).max(value.to_f32().abs());
+            if let Some(remaining) = voice.stolen {
+                if remaining == 0 {
+                    continue;
+                }
+                value = value.amplify(remaining as f32 / self.steal_fade_len as f32);
+                voice.stolen = Some(remaining - 1);
             }
+            sum = sum.saturating_add(value);
+            self.still_current.push(voice);
         }
         std::mem::swap(&mut self.still_current, &mut self.current_sources);
 
+        if self.current_sources.len() != count {
+            let playing = self.playing_voices();
+            self.input.voices.store(playing, Ordering::Relaxed);
+            self.input
+                .active
+                .store(self.current_sources.len(), Ordering::Relaxed);
+        }
+
         sum
     }
 }
@@ -217,8 +846,103 @@ where
 #[cfg(test)]
 mod tests {
     use crate::buffer::SamplesBuffer;
-    use crate::dynamic_mixer;
-    use crate::source::Source;
+    use crate::dynamic_mixer::{self, ResampleQuality, VoiceStealing};
+    use crate::source::{SineWave, Source};
+    use std::time::Duration;
+
+    #[test]
+    fn volume_scales_the_mix() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.5f32, 0.5, 0.5]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![0.25f32, 0.25, 0.25]));
+
+        assert_eq!(rx.next(), Some(0.75));
+        tx.set_volume(0.5);
+        assert_eq!(rx.next(), Some(0.375));
+        tx.set_volume(-1.0);
+        assert_eq!(tx.volume(), 0.0);
+        assert_eq!(rx.next(), Some(0.0));
+    }
+
+    #[test]
+    fn source_volume_scales_one_source() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        let handle = tx.add_with_volume(SamplesBuffer::new(2, 48000, vec![0.5f32; 4]));
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.25f32; 6]));
+
+        assert_eq!(rx.next(), Some(0.75));
+        // Waits for the next frame.
+        handle.set_volume(0.5);
+        assert_eq!(handle.volume(), Some(0.5));
+        assert_eq!(rx.next(), Some(0.75));
+        assert_eq!(rx.next(), Some(0.5));
+        assert_eq!(rx.next(), Some(0.5));
+
+        // Inert once the source finished.
+        assert_eq!(rx.next(), Some(0.25));
+        assert_eq!(handle.volume(), None);
+        handle.set_volume(2.0);
+        assert_eq!(rx.next(), Some(0.25));
+    }
+
+    #[test]
+    fn removed_source_stops() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        let looping = tx.add(SamplesBuffer::new(1, 48000, vec![1i16, 2]).repeat_infinite());
+        let short = tx.add(SamplesBuffer::new(1, 48000, vec![10i16]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![100i16; 4]));
+
+        assert_eq!(rx.next(), Some(111));
+        tx.remove(looping);
+        assert_eq!(rx.next(), Some(100));
+        // The source already ended.
+        tx.remove(short);
+        assert_eq!(rx.next(), Some(100));
+        assert_eq!(tx.voices(), 1);
+    }
+
+    #[test]
+    fn limiter_keeps_peaks_below_threshold() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(2, 48000, vec![0.8f32; 20000]));
+        tx.add(SamplesBuffer::new(2, 48000, [0.8f32, -0.4].repeat(10000)));
+        assert_eq!(rx.next(), Some(1.6));
+        assert_eq!(rx.next(), Some(0.4));
+
+        tx.set_limiter(1.0, Duration::from_millis(50));
+        let frames: Vec<f32> = rx.by_ref().take(2000).collect();
+        let (left, right) = (frames[1998], frames[1999]);
+        assert!((0.95..1.0001).contains(&left), "{left}");
+        // Both channels are attenuated by the same gain.
+        assert!((right / left - 0.25).abs() < 1e-4, "{right}");
+        // Lowered smoothly.
+        assert!(frames
+            .chunks(2)
+            .map(|frame| frame[0])
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[1] <= pair[0]));
+
+        tx.disable_limiter();
+        assert_eq!(rx.next(), Some(1.6));
+    }
+
+    #[test]
+    fn mono_averages_every_frame() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+        tx.add(SamplesBuffer::new(
+            2,
+            48000,
+            vec![1.0f32, 0.0, 0.5, -0.5, 0.0, 1.0],
+        ));
+
+        assert_eq!(rx.next(), Some(1.0));
+        // Waits for the next frame.
+        tx.set_mono(true);
+        assert_eq!(rx.next(), Some(0.0));
+        let rest: Vec<f32> = rx.collect();
+        assert_eq!(rest, vec![0.0, 0.0, 0.5, 0.5]);
+    }
 
     #[test]
     fn basic() {
@@ -236,6 +960,25 @@ mod tests {
         assert_eq!(rx.next(), None);
     }
 
+    #[test]
+    fn integer_sums_saturate() {
+        let (tx, rx) = dynamic_mixer::mixer(1, 48000);
+
+        tx.add(SamplesBuffer::new(
+            1,
+            48000,
+            vec![20000i16, -20000, 30000, i16::MIN],
+        ));
+        tx.add(SamplesBuffer::new(
+            1,
+            48000,
+            vec![20000i16, -20000, -100, -1],
+        ));
+
+        let mixed: Vec<i16> = rx.collect();
+        assert_eq!(mixed, vec![i16::MAX, i16::MIN, 29900, i16::MIN]);
+    }
+
     #[test]
     fn channels_conv() {
         let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
@@ -275,6 +1018,100 @@ mod tests {
         assert_eq!(rx.next(), None);
     }
 
+    #[test]
+    fn synchronized_start() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        tx.add(SamplesBuffer::new(2, 48000, vec![1i16; 6]));
+        assert_eq!(rx.next(), Some(1));
+
+        tx.add_synchronized(vec![
+            Box::new(SamplesBuffer::new(2, 48000, vec![10i16, 10])) as Box<_>,
+            Box::new(SamplesBuffer::new(1, 48000, vec![100i16])) as Box<_>,
+        ]);
+
+        // Both sources wait for the next frame boundary and then start together.
+        assert_eq!(rx.next(), Some(1));
+        assert_eq!(rx.next(), Some(111));
+        assert_eq!(rx.next(), Some(111));
+        assert_eq!(rx.next(), Some(1));
+    }
+
+    #[test]
+    fn direct_requires_matching_format() {
+        let (tx, mut rx) = dynamic_mixer::mixer(2, 48000);
+
+        assert!(tx
+            .add_direct(SamplesBuffer::new(1, 48000, vec![10i16, -10]))
+            .is_err());
+        assert!(tx
+            .add_direct(SamplesBuffer::new(2, 44100, vec![10i16, -10]))
+            .is_err());
+        assert!(tx
+            .add_direct(SamplesBuffer::new(2, 48000, vec![10i16, -10]))
+            .is_ok());
+
+        assert_eq!(rx.next(), Some(10));
+        assert_eq!(rx.next(), Some(-10));
+        assert_eq!(rx.next(), None);
+    }
+
+    #[test]
+    fn voices_are_stolen() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.set_max_voices(Some(2));
+
+        // 5 ms fade out at 48 kHz.
+        tx.add(SamplesBuffer::new(1, 48000, vec![1.0f32; 1000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![10.0f32; 1000]));
+        assert_eq!(rx.next(), Some(11.0));
+        assert_eq!(tx.voices(), 2);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![100.0f32; 1000]));
+        // The oldest voice fades out while the new one plays.
+        assert_eq!(rx.next(), Some(111.0));
+        assert_eq!(tx.voices(), 2);
+        let fading: Vec<f32> = rx.by_ref().take(240).collect();
+        assert!(fading.windows(2).all(|pair| pair[1] < pair[0]));
+        assert_eq!(rx.next(), Some(110.0));
+    }
+
+    #[test]
+    fn active_count_includes_fading_voices() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.set_max_voices(Some(1));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1.0f32; 1000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![10.0f32; 2]));
+        assert_eq!(tx.active_count(), 0);
+
+        rx.next();
+        assert_eq!((tx.voices(), tx.active_count()), (1, 2));
+        // The stolen voice faded out, the other one ended.
+        rx.by_ref().take(300).for_each(drop);
+        assert_eq!((tx.voices(), tx.active_count()), (0, 0));
+    }
+
+    #[test]
+    fn quietest_voice_is_stolen() {
+        let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
+        tx.set_max_voices(Some(2));
+        tx.set_voice_stealing(VoiceStealing::Quietest);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![10.0f32; 1000]));
+        tx.add(SamplesBuffer::new(1, 48000, vec![1.0f32; 1000]));
+        rx.by_ref().take(10).for_each(drop);
+
+        tx.add(SamplesBuffer::new(1, 48000, vec![100.0f32; 1000]));
+        rx.by_ref().take(241).for_each(drop);
+        assert_eq!(rx.next(), Some(110.0));
+
+        // Lowering the limit steals right away.
+        tx.set_max_voices(Some(1));
+        rx.by_ref().take(241).for_each(drop);
+        assert_eq!(tx.voices(), 1);
+        assert_eq!(rx.next(), Some(100.0));
+    }
+
     #[test]
     fn start_afterwards() {
         let (tx, mut rx) = dynamic_mixer::mixer(1, 48000);
@@ -300,4 +1137,26 @@ mod tests {
 
         assert_eq!(rx.next(), None);
     }
+
+    #[test]
+    fn downsampling_is_anti_aliased() {
+        // Level of a 48 kHz tone after mixing at 8 kHz, past its first milliseconds.
+        let rms = |freq: f32, quality| {
+            let (tx, rx) = dynamic_mixer::mixer::<f32>(1, 8000);
+            tx.set_resample_quality(quality);
+            tx.add(SineWave::new(freq).take_duration(Duration::from_millis(500)));
+            let output: Vec<f32> = rx.skip(400).take(3200).collect();
+            (output.iter().map(|s| s * s).sum::<f32>() / output.len() as f32).sqrt()
+        };
+
+        // 7 kHz can not be represented at 8 kHz, it aliases to 1 kHz.
+        let aliased = rms(7000.0, ResampleQuality::Linear);
+        let filtered = rms(7000.0, ResampleQuality::AntiAliased);
+        assert!(aliased > 0.5, "{aliased}");
+        assert!(filtered < aliased * 0.1, "{filtered}");
+
+        // Frequencies below the cutoff pass.
+        let passed = rms(1000.0, ResampleQuality::AntiAliased);
+        assert!(passed > 0.65, "{passed}");
+    }
 }
@@ -1,7 +1,13 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use crate::dynamic_mixer::{self, DynamicMixerController};
 
-use crate::stream::{OutputStreamHandle, StreamError, OutputStreamTrait};
+#[cfg(feature = "wav")]
+use crate::recording::{RecordingFormat, RecordingHandle};
+use crate::stream::{
+    OutputControls, OutputProcessor, OutputStreamHandle, OutputStreamTrait, StreamError,
+};
+use crate::tap::OutputTap;
 use byte_slice_cast::AsMutSliceOf;
 
 /// `cpal::Stream` container. Also see the more useful `OutputStreamHandle`.
@@ -9,6 +15,7 @@ use byte_slice_cast::AsMutSliceOf;
 /// If this is dropped playback will end & attached `OutputStreamHandle`s will no longer work.
 pub struct GstOutputStream {
     mixer: Arc<DynamicMixerController<f32>>,
+    controls: Arc<OutputControls>,
     //_stream: cpal::Stream,
 }
 
@@ -29,8 +36,11 @@ impl OutputStreamTrait for GstOutputStream {
         let rate = new_pad_struct.get::<i32>("rate").unwrap();
         let channels = new_pad_struct.get::<i32>("channels").unwrap();
         
-        let (mixer_tx, mut mixer_rx) =
+        let (mixer_tx, mixer_rx) =
             dynamic_mixer::mixer::<f32>(channels as _ , rate as _ );
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let overflow_controls = controls.clone();
 
         device.set_callbacks(
             gst_app::AppSrcCallbacks::builder()
@@ -41,24 +51,87 @@ impl OutputStreamTrait for GstOutputStream {
                         let ref_buf = buffer.make_mut();
                         let mut buf_map = ref_buf.map_writable().unwrap();
                         let buf_slice = buf_map.as_mut_slice_of::<f32>().unwrap();
-
-                        for sample in buf_slice {
-                            let a = mixer_rx.next().unwrap_or(0.0);
-                            *sample = a;
-                        }
+                        buf_slice.copy_from_slice(output.fill_f32(buf_slice.len()));
                     }
 
                     appsrc.push_buffer(buffer).unwrap();
                 })
+                .enough_data(move |_| {
+                    overflow_controls.overruns.fetch_add(1, Ordering::Relaxed);
+                })
                 .build(),
         );
 
-        let out = Self { mixer: mixer_tx };
+        let out = Self {
+            mixer: mixer_tx,
+            controls,
+        };
         let handle = OutputStreamHandle {
             mixer: Arc::downgrade(&out.mixer),
+            controls: Arc::downgrade(&out.controls),
         };
 
         Ok((out, handle))
     }
 }
 
+impl GstOutputStream {
+    /// Returns a tap that receives a copy of everything this stream plays.
+    ///
+    /// See [`OutputTap`] for how buffers are delivered.
+    pub fn output_tap(&self) -> OutputTap {
+        self.controls.taps.tap()
+    }
+
+    /// Returns how many times the appsrc asked for data and the mixer ran dry because a source
+    /// that should still be playing, such as an infinite stream, had no samples left. The
+    /// missing samples are sent as silence.
+    ///
+    /// Buffers requested while nothing is playing, or after the last sound ended, are not
+    /// counted.
+    pub fn underruns(&self) -> u64 {
+        self.controls.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many times the appsrc signalled that its queue is full, meaning the
+    /// downstream elements (for example a `queue2`) do not consume data fast enough.
+    pub fn overruns(&self) -> u64 {
+        self.controls.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Installs a callback that processes the mixed output right before it is sent to the device.
+    ///
+    /// The callback receives the interleaved `f32` samples of one device buffer, the channel
+    /// count and the sample rate of the stream, and may change the samples in place. Use it for
+    /// effects on the master bus that do not fit a [`Source`](crate::Source). Output taps and
+    /// recordings receive the processed samples. Installing a new callback replaces the previous
+    /// one.
+    ///
+    /// The callback runs on the realtime audio thread. It must not allocate, lock or block in
+    /// any other way, and must return quickly, otherwise the output glitches. The audio thread
+    /// hands the replaced callback back instead of dropping it, it is dropped by the next call
+    /// to this method or along with the stream.
+    pub fn set_post_process<F>(&self, post_process: F)
+    where
+        F: FnMut(&mut [f32], u16, u32) + Send + 'static,
+    {
+        self.controls.set_post_process(Box::new(post_process));
+    }
+
+    /// Starts recording everything this stream plays to a WAV file at `path`.
+    ///
+    /// See [`RecordingHandle`] for the format of the file.
+    #[cfg(feature = "wav")]
+    pub fn start_recording<P>(&self, path: P) -> std::io::Result<RecordingHandle>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        RecordingHandle::start(
+            self.output_tap(),
+            self.mixer.channels(),
+            self.mixer.sample_rate(),
+            path.as_ref(),
+            RecordingFormat::F32,
+        )
+    }
+}
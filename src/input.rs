@@ -0,0 +1,418 @@
+//! Capture from an input device, such as a microphone, as a [`Source`].
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::SupportedStreamConfig;
+
+use crate::source::SeekError;
+use crate::stream::StreamError;
+use crate::Source;
+
+/// How much captured audio is held for the source before new samples are dropped.
+const BUFFER_DURATION: Duration = Duration::from_millis(200);
+
+/// Opens the default input device, see [`InputStream::try_default`].
+pub fn input_stream() -> Result<(InputStream, InputSource), StreamError> {
+    InputStream::try_default()
+}
+
+/// `cpal::Stream` container for an input device. The captured audio is played by the
+/// [`InputSource`] returned with it.
+///
+/// If this is dropped capturing ends, the source plays what it already received and then
+/// ends too.
+pub struct InputStream {
+    ring: Arc<RingBuffer>,
+    config: SupportedStreamConfig,
+    _stream: cpal::Stream,
+}
+
+impl InputStream {
+    /// Opens the default input device with its default input configuration.
+    pub fn try_default() -> Result<(InputStream, InputSource), StreamError> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or(StreamError::NoDevice)?;
+        InputStream::try_from_device(&device)
+    }
+
+    /// Opens the given input device with its default input configuration.
+    pub fn try_from_device(
+        device: &cpal::Device,
+    ) -> Result<(InputStream, InputSource), StreamError> {
+        let default_config = device
+            .default_input_config()
+            .map_err(StreamError::DefaultStreamConfigError)?;
+        InputStream::try_from_device_config(device, default_config)
+    }
+
+    /// Opens the given input device with the given stream config.
+    ///
+    /// If the device can not be opened with the config, the other configs it supports are
+    /// tried, see [`config`](InputStream::config) for the one that was used.
+    pub fn try_from_device_config(
+        device: &cpal::Device,
+        config: SupportedStreamConfig,
+    ) -> Result<(InputStream, InputSource), StreamError> {
+        let (ring, config, _stream) =
+            build_input_stream(device, config.clone()).or_else(|err| {
+                let mut supported: Vec<_> = device
+                    .supported_input_configs()
+                    .map_err(StreamError::SupportedStreamConfigsError)?
+                    .collect();
+                supported.sort_by(|a, b| b.cmp_default_heuristics(a));
+                supported
+                    .into_iter()
+                    .find_map(|config| {
+                        build_input_stream(device, config.with_max_sample_rate()).ok()
+                    })
+                    // return original error if nothing works
+                    .ok_or(StreamError::BuildStreamError(err))
+            })?;
+        _stream.play().map_err(StreamError::PlayStreamError)?;
+
+        let source = InputSource {
+            ring: ring.clone(),
+            channels: config.channels(),
+            sample_rate: config.sample_rate().0,
+            channel: 0,
+            silent: false,
+        };
+        let stream = InputStream {
+            ring,
+            config,
+            _stream,
+        };
+        Ok((stream, source))
+    }
+
+    /// Returns the configuration the device was actually opened with.
+    ///
+    /// Captured samples are converted to `f32`, the [`InputSource`] has the channel count and
+    /// sample rate of this config.
+    #[inline]
+    pub fn config(&self) -> &SupportedStreamConfig {
+        &self.config
+    }
+
+    /// Returns the number of times captured audio was dropped because the source did not
+    /// read it in time.
+    pub fn overruns(&self) -> usize {
+        self.ring.overruns.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for InputStream {
+    fn drop(&mut self) {
+        self.ring.closed.store(true, Ordering::Release);
+    }
+}
+
+/// Plays the audio captured by an [`InputStream`].
+///
+/// The samples are interleaved `f32` with the channel count and sample rate of the input
+/// device; a [`Sink`](crate::Sink) converts them to the output format like any other source.
+///
+/// The audio callback never waits for the source: captured samples go through a ring buffer
+/// that holds 200 ms. When the source falls that far behind, for example because it was not
+/// played yet, newer samples are dropped and the source skips everything it buffered to catch
+/// up with the device. When it runs ahead of the device it plays silence until the next
+/// samples arrive.
+pub struct InputSource {
+    ring: Arc<RingBuffer>,
+    channels: u16,
+    sample_rate: u32,
+    // Position in the current frame, and whether that frame is silence.
+    channel: u16,
+    silent: bool,
+}
+
+impl Iterator for InputSource {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 0 {
+            if self.ring.overflowed.swap(false, Ordering::Relaxed) {
+                self.ring.skip_all();
+            }
+            // Whole frames are written at once, so a frame is either there or not.
+            if self.ring.available() == 0 {
+                if self.ring.closed.load(Ordering::Acquire) {
+                    return None;
+                }
+                self.silent = true;
+            } else {
+                self.silent = false;
+            }
+        }
+        self.channel = (self.channel + 1) % self.channels.max(1);
+
+        if self.silent {
+            Some(0.0)
+        } else {
+            self.ring.pop()
+        }
+    }
+}
+
+impl Source for InputSource {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    // Only ends when the input stream is dropped, never on its own.
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+/// Lock free buffer between the audio callback, the only writer, and the [`InputSource`], the
+/// only reader.
+struct RingBuffer {
+    // Bits of the `f32` samples.
+    samples: Box<[AtomicU32]>,
+    channels: usize,
+    // Number of samples written and read since the start, the difference is what is buffered.
+    written: AtomicUsize,
+    read: AtomicUsize,
+    overflowed: AtomicBool,
+    overruns: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl RingBuffer {
+    fn new(channels: u16, sample_rate: u32) -> RingBuffer {
+        let channels = channels.max(1) as usize;
+        let frames = (BUFFER_DURATION.as_secs_f32() * sample_rate as f32) as usize;
+        RingBuffer {
+            samples: (0..frames.max(1) * channels)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+            channels,
+            written: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            overflowed: AtomicBool::new(false),
+            overruns: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Converts and writes as many whole frames of `data` as fit. Never blocks.
+    fn push<T>(&self, data: &[T])
+    where
+        T: cpal::SizedSample,
+        f32: cpal::FromSample<T>,
+    {
+        let written = self.written.load(Ordering::Relaxed);
+        let free = self.samples.len() - (written - self.read.load(Ordering::Acquire));
+        let len = if data.len() > free {
+            self.overflowed.store(true, Ordering::Relaxed);
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+            free - free % self.channels
+        } else {
+            data.len()
+        };
+        for (i, &sample) in data[..len].iter().enumerate() {
+            let sample: f32 = cpal::FromSample::from_sample_(sample);
+            self.samples[(written + i) % self.samples.len()]
+                .store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.written.store(written + len, Ordering::Release);
+    }
+
+    /// Number of samples that can be read.
+    fn available(&self) -> usize {
+        self.written.load(Ordering::Acquire) - self.read.load(Ordering::Relaxed)
+    }
+
+    fn pop(&self) -> Option<f32> {
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.written.load(Ordering::Acquire) {
+            return None;
+        }
+        let sample = self.samples[read % self.samples.len()].load(Ordering::Relaxed);
+        self.read.store(read + 1, Ordering::Release);
+        Some(f32::from_bits(sample))
+    }
+
+    /// Drops everything buffered.
+    fn skip_all(&self) {
+        let written = self.written.load(Ordering::Acquire);
+        self.read.store(written, Ordering::Release);
+    }
+}
+
+fn build_input_stream(
+    device: &cpal::Device,
+    format: SupportedStreamConfig,
+) -> Result<(Arc<RingBuffer>, SupportedStreamConfig, cpal::Stream), cpal::BuildStreamError> {
+    let ring = Arc::new(RingBuffer::new(format.channels(), format.sample_rate().0));
+    let error_callback = |err| {
+        #[cfg(feature = "tracing")]
+        tracing::error!("an error occurred on input stream: {err}");
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("an error occurred on input stream: {err}");
+    };
+
+    let config = format.config();
+    let callback_ring = ring.clone();
+    match format.sample_format() {
+        cpal::SampleFormat::F32 => device.build_input_stream::<f32, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::F64 => device.build_input_stream::<f64, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::I8 => device.build_input_stream::<i8, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream::<i16, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::I32 => device.build_input_stream::<i32, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::I64 => device.build_input_stream::<i64, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::U8 => device.build_input_stream::<u8, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_input_stream::<u16, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::U32 => device.build_input_stream::<u32, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        cpal::SampleFormat::U64 => device.build_input_stream::<u64, _, _>(
+            &config,
+            move |data, _| callback_ring.push(data),
+            error_callback,
+            None,
+        ),
+        _ => return Err(cpal::BuildStreamError::StreamConfigNotSupported),
+    }
+    .map(|stream| (ring, format, stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::{InputSource, RingBuffer};
+    use crate::Source;
+
+    fn source(channels: u16, sample_rate: u32) -> (Arc<RingBuffer>, InputSource) {
+        let ring = Arc::new(RingBuffer::new(channels, sample_rate));
+        let source = InputSource {
+            ring: ring.clone(),
+            channels,
+            sample_rate,
+            channel: 0,
+            silent: false,
+        };
+        (ring, source)
+    }
+
+    #[test]
+    fn converts_captured_samples() {
+        let (ring, mut source) = source(2, 1000);
+        ring.push(&[i16::MIN, 16384, 0, i16::MAX]);
+        let samples: Vec<f32> = source.by_ref().take(4).collect();
+        assert_eq!(&samples[..3], &[-1.0, 0.5, 0.0]);
+        assert!((samples[3] - 1.0).abs() < 1e-4);
+        assert_eq!((source.channels(), source.sample_rate()), (2, 1000));
+        assert!(source.is_infinite());
+    }
+
+    #[test]
+    fn plays_silence_until_frames_arrive() {
+        let (ring, mut source) = source(2, 1000);
+        assert_eq!(source.next(), Some(0.0));
+        // A frame that arrives halfway through a silent one starts with the next frame.
+        ring.push(&[0.25f32, 0.5]);
+        assert_eq!(source.next(), Some(0.0));
+        assert_eq!(source.by_ref().take(2).collect::<Vec<_>>(), [0.25, 0.5]);
+    }
+
+    #[test]
+    fn skips_ahead_after_an_overrun() {
+        // 200 ms at 100 Hz hold 20 frames.
+        let (ring, mut source) = source(1, 100);
+        ring.push(&[0.5f32; 15]);
+        ring.push(&[0.25f32; 10]);
+        assert!(ring.overflowed.load(Ordering::Relaxed));
+        assert_eq!(ring.overruns.load(Ordering::Relaxed), 1);
+        // What was buffered is dropped, playback continues with the next capture.
+        assert_eq!(source.next(), Some(0.0));
+        ring.push(&[1.0f32; 3]);
+        assert_eq!(
+            source.by_ref().take(4).collect::<Vec<_>>(),
+            [1.0, 1.0, 1.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn ends_once_the_stream_is_closed() {
+        let (ring, source) = source(1, 100);
+        ring.push(&[0.5f32; 4]);
+        ring.closed.store(true, Ordering::Release);
+        assert_eq!(source.count(), 4);
+    }
+}
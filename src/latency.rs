@@ -0,0 +1,114 @@
+//! Measures the round-trip latency of an audio setup, for example to calibrate audio-video sync.
+//!
+//! Play the [`probe`] through an output stream while capturing an input that hears it, through
+//! a microphone or a loopback cable, then pass both to [`measure`]. The input can be captured
+//! with an [`InputStream`](crate::InputStream), collecting the samples of its
+//! [`InputSource`](crate::InputSource).
+//!
+//! [`measure`] returns where the probe starts in the captured signal, so the result is only the
+//! latency if the capture started exactly when the probe was handed to the output. When that
+//! moment is not known, start reading an [`OutputTap`](crate::OutputTap) together with the
+//! capture and measure the probe in both recordings: the difference between the two results
+//! is the round-trip latency.
+
+use std::time::Duration;
+
+use crate::buffer::SamplesBuffer;
+
+/// Length of the probe signal.
+const PROBE_DURATION: Duration = Duration::from_millis(10);
+
+/// Minimum normalized correlation for the probe to count as found.
+const MIN_CORRELATION: f32 = 0.5;
+
+/// Returns a short mono burst of noise that is easy to find again in a recording.
+///
+/// Unlike a single click, noise correlates strongly only with itself at exactly the right
+/// offset, which makes the measurement robust against room echoes and background sound. The
+/// burst is 10 milliseconds long, at half of full scale, and the same on every call.
+pub fn probe(sample_rate: u32) -> SamplesBuffer<f32> {
+    let len = (PROBE_DURATION.as_secs_f64() * sample_rate as f64).round() as usize;
+    // Xorshift, so the probe does not depend on the `noise` feature.
+    let mut state = 0x2545_f491_u32;
+    let samples = (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32 - 0.5
+        })
+        .collect::<Vec<_>>();
+    SamplesBuffer::new(1, sample_rate, samples)
+}
+
+/// Finds `reference` in `captured` and returns how late it starts there.
+///
+/// Both signals are mono and at `sample_rate`. The offset with the highest normalized
+/// cross-correlation wins, so the level of the captured signal does not matter. Returns `None`
+/// if `captured` is shorter than `reference` or if the reference could not be found with
+/// reasonable confidence, for example because the input did not pick it up.
+pub fn measure(reference: &[f32], captured: &[f32], sample_rate: u32) -> Option<Duration> {
+    if reference.is_empty() || captured.len() < reference.len() {
+        return None;
+    }
+
+    let reference_energy: f32 = reference.iter().map(|s| s * s).sum();
+    // Energy of the window of `captured` the reference is compared with, updated as it slides.
+    let mut window_energy: f32 = captured[..reference.len()].iter().map(|s| s * s).sum();
+    let mut best = (0, 0.0f32);
+    for offset in 0..=captured.len() - reference.len() {
+        if offset > 0 {
+            let left = captured[offset - 1];
+            let entered = captured[offset + reference.len() - 1];
+            window_energy = (window_energy - left * left + entered * entered).max(0.0);
+        }
+        let energy = reference_energy * window_energy;
+        if energy <= f32::EPSILON {
+            continue;
+        }
+        let window = &captured[offset..offset + reference.len()];
+        let dot: f32 = reference.iter().zip(window).map(|(r, c)| r * c).sum();
+        let correlation = dot / energy.sqrt();
+        if correlation > best.1 {
+            best = (offset, correlation);
+        }
+    }
+
+    if best.1 < MIN_CORRELATION {
+        return None;
+    }
+    Some(Duration::from_secs_f64(best.0 as f64 / sample_rate as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{measure, probe};
+
+    #[test]
+    fn finds_simulated_loopback_delay() {
+        let reference: Vec<f32> = probe(48000).collect();
+        // 23 ms of delay, a quieter copy of the probe and some low level hum on top.
+        let delay = 1104;
+        let mut captured = vec![0.0f32; 48000 / 10];
+        for (i, sample) in captured.iter_mut().enumerate() {
+            *sample = 0.05 * (i as f32 * 0.01).sin();
+        }
+        for (i, sample) in reference.iter().enumerate() {
+            captured[delay + i] += sample * 0.3;
+        }
+
+        let latency = measure(&reference, &captured, 48000).unwrap();
+        let error = latency.abs_diff(Duration::from_millis(23));
+        assert!(error < Duration::from_micros(20), "{latency:?}");
+    }
+
+    #[test]
+    fn missing_probe_is_not_found() {
+        let reference: Vec<f32> = probe(48000).collect();
+        let captured: Vec<f32> = (0..4800).map(|i| (i as f32 * 0.01).sin()).collect();
+        assert_eq!(measure(&reference, &captured, 48000), None);
+        assert_eq!(measure(&reference, &captured[..100], 48000), None);
+    }
+}
@@ -18,7 +18,7 @@
 //! ```no_run
 //! use std::fs::File;
 //! use std::io::BufReader;
-//! use rodio::{Decoder, OutputStream, source::Source};
+//! use rodio::{Decoder, OutputStream, OutputStreamTrait, source::Source};
 //!
 //! // Get an output stream handle to the default physical sound device.
 //! // Note that no sound will be played if _stream is dropped
@@ -49,7 +49,7 @@
 //! use std::fs::File;
 //! use std::io::BufReader;
 //! use std::time::Duration;
-//! use rodio::{Decoder, OutputStream, Sink};
+//! use rodio::{Decoder, OutputStream, OutputStreamTrait, Sink};
 //! use rodio::source::{SineWave, Source};
 //!
 //! // _stream must live as long as the sink
@@ -115,6 +115,12 @@
 //! The "noise" feature adds support for white and pink noise sources. This feature requires the
 //! "rand" crate.
 //!
+//! ### Feature "opus"
+//!
+//! The "opus" feature adds support for Ogg Opus files, decoded by libopus through the
+//! "audiopus" crate. libopus is linked from the system if it is installed, otherwise it is built
+//! from source, which needs CMake.
+//!
 //! ## How it works under the hood
 //!
 //! Rodio spawns a background thread that is dedicated to reading from the sources and sending
@@ -133,9 +139,17 @@ pub use cpal::{
 };
 
 mod conversions;
+mod crossfader;
+mod input;
+mod meter;
+#[cfg(feature = "wav")]
+mod recording;
 mod sink;
+mod source_control;
 mod spatial_sink;
 mod stream;
+mod sync_group;
+mod tap;
 
 #[cfg(feature = "gstreamer")]
 mod gst_stream;
@@ -145,14 +159,31 @@ pub use crate::gst_stream::GstOutputStream;
 
 pub mod buffer;
 pub mod decoder;
+pub mod deterministic;
 pub mod dynamic_mixer;
+pub mod latency;
+pub mod multi_output;
+pub mod output_manager;
 pub mod queue;
 pub mod source;
 pub mod static_buffer;
 
-pub use crate::conversions::Sample;
+pub use crate::conversions::{ChannelLayout, ChannelPosition, Sample};
+pub use crate::crossfader::Crossfader;
 pub use crate::decoder::Decoder;
-pub use crate::sink::Sink;
+pub use crate::input::{input_stream, InputSource, InputStream};
+pub use crate::meter::{ChannelLevel, MeterHandle};
+pub use crate::multi_output::MultiOutputStream;
+pub use crate::output_manager::{AutoReconnectStream, OutputManager};
+#[cfg(feature = "wav")]
+pub use crate::recording::{RecordingFormat, RecordingHandle};
+pub use crate::sink::{RepeatMode, Sink, SinkEvent, SinkState, TrackHandle};
 pub use crate::source::Source;
+pub use crate::source_control::SourceControl;
 pub use crate::spatial_sink::SpatialSink;
-pub use crate::stream::{OutputStreamTrait, OutputStream, OutputStreamHandle, PlayError, StreamError};
+pub use crate::stream::{
+    output_devices, DeviceInfo, NativeOutputStream, OutputStream, OutputStreamHandle,
+    OutputStreamTrait, PlayError, StreamError,
+};
+pub use crate::sync_group::SyncGroup;
+pub use crate::tap::OutputTap;
@@ -0,0 +1,96 @@
+//! Levels of the mixed output as it is sent to the device.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{OnceLock, Weak};
+
+use crate::stream::OutputControls;
+
+/// Peak and RMS level of one channel, as linear amplitudes where 1.0 is full scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChannelLevel {
+    /// Largest absolute sample value.
+    pub peak: f32,
+    /// Root mean square of the samples.
+    pub rms: f32,
+}
+
+/// Reads the level of an output stream, see [`OutputStreamHandle::peak_meter`].
+///
+/// [`OutputStreamHandle::peak_meter`]: crate::OutputStreamHandle::peak_meter
+#[derive(Clone)]
+pub struct MeterHandle {
+    pub(crate) controls: Weak<OutputControls>,
+}
+
+impl MeterHandle {
+    /// Returns the level of each channel over the last buffer sent to the device.
+    ///
+    /// The levels are measured after the master volume and the post-processor, they are zero
+    /// until the device asked for its first buffer. The list is empty once the stream was
+    /// dropped, and for handles that do not belong to a stream played on a single device, such
+    /// as those of a [`MultiOutputStream`](crate::MultiOutputStream).
+    pub fn read(&self) -> Vec<ChannelLevel> {
+        let Some(controls) = self.controls.upgrade() else {
+            return Vec::new();
+        };
+        let Some(channels) = controls.meter.channels.get() else {
+            return Vec::new();
+        };
+        channels
+            .iter()
+            .map(|channel| ChannelLevel {
+                peak: f32::from_bits(channel.peak.load(Ordering::Relaxed)),
+                rms: f32::from_bits(channel.rms.load(Ordering::Relaxed)),
+            })
+            .collect()
+    }
+}
+
+/// Levels written by the audio callback.
+#[derive(Default)]
+pub(crate) struct OutputMeter {
+    // Sized before the audio callback starts, so it never allocates.
+    channels: OnceLock<Box<[ChannelMeter]>>,
+}
+
+#[derive(Default)]
+struct ChannelMeter {
+    peak: AtomicU32,
+    rms: AtomicU32,
+}
+
+impl OutputMeter {
+    /// Sets the number of channels to measure, only the first call has an effect.
+    pub(crate) fn set_channels(&self, channels: u16) {
+        self.channels
+            .get_or_init(|| (0..channels).map(|_| ChannelMeter::default()).collect());
+    }
+
+    /// Measures one buffer of interleaved samples.
+    pub(crate) fn update(&self, samples: &[f32]) {
+        let Some(meters) = self.channels.get() else {
+            return;
+        };
+        let channels = meters.len();
+        if channels == 0 || samples.is_empty() {
+            return;
+        }
+        for (channel, meter) in meters.iter().enumerate() {
+            let mut peak = 0f32;
+            let mut sum = 0f32;
+            let mut count = 0usize;
+            for &sample in samples.iter().skip(channel).step_by(channels) {
+                peak = peak.max(sample.abs());
+                sum += sample * sample;
+                count += 1;
+            }
+            let rms = if count > 0 {
+                (sum / count as f32).sqrt()
+            } else {
+                0.0
+            };
+            meter.peak.store(peak.to_bits(), Ordering::Relaxed);
+            meter.rms.store(rms.to_bits(), Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Output of the same sounds on several devices at once.
+//!
+//! A [`MultiOutputStream`] mixes everything played through its handle once, and copies the
+//! mixed samples to a stream on every device. Each stream converts them to the channels and
+//! sample rate of its device, so the devices do not need to share a format.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use cpal::traits::DeviceTrait;
+
+use crate::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use crate::stream::{OutputStream, OutputStreamHandle, StreamError};
+use crate::Source;
+
+/// Frames taken from the mixer at a time, by whichever device needs samples first.
+const CHUNK_FRAMES: usize = 256;
+
+/// Most audio a device may lag behind the fastest one before its oldest samples are dropped.
+const MAX_LAG: Duration = Duration::from_millis(200);
+
+/// Plays the same sounds on several output devices, for example the built-in speakers and a
+/// USB speaker.
+///
+/// Sinks and sources are played through the single [`OutputStreamHandle`] returned with it.
+/// The mixer takes the format of the first device. The devices run on clocks of their own,
+/// so they drift apart slowly; once one lags more than 200 milliseconds behind the others,
+/// its oldest samples are skipped to catch up.
+///
+/// If this is dropped playback ends on all devices.
+pub struct MultiOutputStream {
+    streams: Vec<OutputStream>,
+}
+
+impl MultiOutputStream {
+    /// Opens a stream on every device with its default configuration.
+    ///
+    /// Returns [`StreamError::NoDevice`] for an empty list, and the error of the first device
+    /// that can not be opened otherwise.
+    pub fn try_from_devices(
+        devices: &[cpal::Device],
+    ) -> Result<(MultiOutputStream, OutputStreamHandle), StreamError> {
+        let first = devices.first().ok_or(StreamError::NoDevice)?;
+        let config = first
+            .default_output_config()
+            .map_err(StreamError::DefaultStreamConfigError)?;
+        let (controller, mixer) = dynamic_mixer::mixer(config.channels(), config.sample_rate().0);
+        let tee = Tee::new(controller.clone(), mixer, devices.len());
+
+        let mut streams = Vec::with_capacity(devices.len());
+        for (index, device) in devices.iter().enumerate() {
+            let (stream, stream_handle) = OutputStream::try_from_device(device)?;
+            stream_handle
+                .play_raw(tee.branch(index))
+                .map_err(StreamError::PlayError)?;
+            streams.push(stream);
+        }
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&controller),
+            controls: Weak::new(),
+        };
+        Ok((MultiOutputStream { streams }, handle))
+    }
+
+    /// Returns the stream of every device, in the order the devices were given.
+    pub fn streams(&self) -> &[OutputStream] {
+        &self.streams
+    }
+}
+
+/// Mixed samples not yet played by every branch.
+struct TeeState {
+    mixer: DynamicMixer<f32>,
+    queues: Vec<VecDeque<f32>>,
+    // Keeps the handle working as long as any device plays.
+    _controller: Arc<DynamicMixerController<f32>>,
+}
+
+/// Copies the output of one mixer to several branches.
+///
+/// The audio callbacks of all devices share one lock: a device that needs samples while
+/// another one mixes the next chunk waits for it, up to the time it takes to mix 256 frames.
+/// The queues are allocated up front, so nothing allocates while the lock is held.
+#[derive(Clone)]
+struct Tee {
+    state: Arc<Mutex<TeeState>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Tee {
+    fn new(
+        controller: Arc<DynamicMixerController<f32>>,
+        mixer: DynamicMixer<f32>,
+        branches: usize,
+    ) -> Tee {
+        let (channels, sample_rate) = (mixer.channels(), mixer.sample_rate());
+        let capacity = max_queued(channels, sample_rate) + CHUNK_FRAMES * channels as usize;
+        Tee {
+            state: Arc::new(Mutex::new(TeeState {
+                mixer,
+                queues: (0..branches)
+                    .map(|_| VecDeque::with_capacity(capacity))
+                    .collect(),
+                _controller: controller,
+            })),
+            channels,
+            sample_rate,
+        }
+    }
+
+    fn branch(&self, index: usize) -> Branch {
+        Branch {
+            tee: self.clone(),
+            index,
+            chunk: Vec::with_capacity(CHUNK_FRAMES * self.channels as usize),
+            position: 0,
+        }
+    }
+
+    /// Most samples a queue holds before its oldest frames are dropped.
+    fn max_queued(&self) -> usize {
+        max_queued(self.channels, self.sample_rate)
+    }
+
+    /// Moves the next samples of branch `index` into `chunk`.
+    fn take(&self, index: usize, chunk: &mut Vec<f32>) {
+        let mut state = self.state.lock().unwrap();
+        if state.queues[index].is_empty() {
+            let len = CHUNK_FRAMES * self.channels as usize;
+            let TeeState { mixer, queues, .. } = &mut *state;
+            for _ in 0..len {
+                // Silence while nothing is playing.
+                let value = mixer.next().unwrap_or(0.0);
+                queues.iter_mut().for_each(|queue| queue.push_back(value));
+            }
+            let max_queued = self.max_queued();
+            for queue in queues.iter_mut() {
+                if queue.len() > max_queued {
+                    // Whole frames, so the channels stay in place.
+                    let excess = queue.len() - max_queued;
+                    let frames = excess.div_ceil(self.channels as usize);
+                    queue.drain(..frames * self.channels as usize);
+                }
+            }
+        }
+        let queue = &mut state.queues[index];
+        let len = queue.len().min(CHUNK_FRAMES * self.channels as usize);
+        chunk.clear();
+        chunk.extend(queue.drain(..len));
+    }
+}
+
+/// Most samples a queue of a [`Tee`] holds before its oldest frames are dropped.
+fn max_queued(channels: u16, sample_rate: u32) -> usize {
+    let frames = (MAX_LAG.as_secs_f32() * sample_rate as f32) as usize;
+    frames.max(CHUNK_FRAMES) * channels as usize
+}
+
+/// Plays the copy of the mixed samples for one device.
+struct Branch {
+    tee: Tee,
+    index: usize,
+    chunk: Vec<f32>,
+    position: usize,
+}
+
+impl Iterator for Branch {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.position == self.chunk.len() {
+            self.tee.take(self.index, &mut self.chunk);
+            self.position = 0;
+        }
+        let value = self.chunk[self.position];
+        self.position += 1;
+        Some(value)
+    }
+}
+
+impl Source for Branch {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.tee.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.tee.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tee, CHUNK_FRAMES};
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+
+    #[test]
+    fn branches_play_the_same_samples() {
+        let (controller, mixer) = dynamic_mixer::mixer::<f32>(2, 44100);
+        let tee = Tee::new(controller.clone(), mixer, 2);
+        let samples: Vec<f32> = (1..=2000).map(|n| n as f32 / 2000.0).collect();
+        controller.add(SamplesBuffer::new(2, 44100, samples.clone()));
+
+        let (mut a, mut b) = (tee.branch(0), tee.branch(1));
+        // One device ahead of the other.
+        let played_a: Vec<f32> = a.by_ref().take(1500).collect();
+        let played_b: Vec<f32> = b.by_ref().take(2000).collect();
+        let rest_a: Vec<f32> = a.take(500).collect();
+        assert_eq!([played_a, rest_a].concat(), samples);
+        assert_eq!(played_b, samples);
+    }
+
+    #[test]
+    fn lagging_branch_skips_whole_frames() {
+        let (controller, mixer) = dynamic_mixer::mixer::<f32>(2, 1000);
+        let tee = Tee::new(controller.clone(), mixer, 2);
+        let samples: Vec<f32> = (1..=4000).map(|n| n as f32).collect();
+        controller.add(SamplesBuffer::new(2, 1000, samples));
+
+        let mut fast = tee.branch(0);
+        fast.by_ref().take(4 * CHUNK_FRAMES * 2).for_each(drop);
+        let mut slow = tee.branch(1);
+        let first = slow.next().unwrap();
+        // The oldest samples were dropped, starting on the left channel again.
+        assert!(first > 1.0);
+        assert_eq!(first as usize % 2, 1);
+        assert!(tee.state.lock().unwrap().queues[1].len() <= tee.max_queued());
+    }
+
+    #[test]
+    fn queues_do_not_grow() {
+        let (controller, mixer) = dynamic_mixer::mixer::<f32>(2, 1000);
+        let tee = Tee::new(controller.clone(), mixer, 2);
+        let capacity = tee.state.lock().unwrap().queues[1].capacity();
+
+        // Only one device plays, the queue of the other one fills up to its limit.
+        tee.branch(0).take(16 * CHUNK_FRAMES * 2).for_each(drop);
+        assert_eq!(tee.state.lock().unwrap().queues[1].capacity(), capacity);
+    }
+}
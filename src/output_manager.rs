@@ -0,0 +1,356 @@
+//! Output that can move to another device while playing.
+//!
+//! An [`OutputManager`] mixes everything played through its handle in a mixer of its own,
+//! and plays that mixer on the current device. Switching the device opens a stream on the new
+//! one and moves the mixer over, so [`Sink`](crate::Sink)s keep their queues, positions and
+//! settings. An [`AutoReconnectStream`] does that by itself when a device goes away.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait};
+
+use crate::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use crate::stream::{OutputStream, OutputStreamHandle, StreamError};
+use crate::Source;
+
+/// Plays on one output device at a time, and can switch to another without dropping sinks.
+///
+/// Create sinks and play sources through [`handle`](OutputManager::handle) as with an
+/// [`OutputStream`]. After [`switch_to`](OutputManager::switch_to) they continue on the new
+/// device where they left off: nothing is skipped, paused sinks stay paused, and the volume,
+/// speed and queue of every sink are kept.
+///
+/// Playback pauses for a moment during the switch, from when the old device stops until the
+/// new one picks up the mixer. That is usually a few milliseconds, plus the time the new
+/// device needs to start, during which it plays silence. The mixer keeps the format of the
+/// first device, so on a device with another channel count or sample rate its output is
+/// converted.
+pub struct OutputManager {
+    stream: OutputStream,
+    device_name: Option<String>,
+    controller: Arc<DynamicMixerController<f32>>,
+    handle: OutputStreamHandle,
+    parking: Parking,
+}
+
+impl OutputManager {
+    /// Plays on the default output device.
+    pub fn try_default() -> Result<OutputManager, StreamError> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(StreamError::NoDevice)?;
+        OutputManager::try_from_device(&device)
+    }
+
+    /// Plays on the given output device, the mixer takes on its default configuration.
+    pub fn try_from_device(device: &cpal::Device) -> Result<OutputManager, StreamError> {
+        let config = device
+            .default_output_config()
+            .map_err(StreamError::DefaultStreamConfigError)?;
+        let (controller, mixer) = dynamic_mixer::mixer(config.channels(), config.sample_rate().0);
+        let parking = Parking::new(mixer);
+        let (stream, stream_handle) = OutputStream::try_from_device_config(device, config)?;
+        stream_handle
+            .play_raw(parking.relay())
+            .map_err(StreamError::PlayError)?;
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&controller),
+            controls: Weak::new(),
+        };
+        Ok(OutputManager {
+            stream,
+            device_name: device.name().ok(),
+            controller,
+            handle,
+            parking,
+        })
+    }
+
+    /// Returns the output devices of the default host, to pick one for
+    /// [`switch_to`](OutputManager::switch_to).
+    pub fn devices(&self) -> Result<crate::OutputDevices<crate::Devices>, crate::DevicesError> {
+        cpal::default_host().output_devices()
+    }
+
+    /// Returns the name of the device currently played on, if it has one.
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// Returns the handle to play through, it stays valid across device switches.
+    pub fn handle(&self) -> &OutputStreamHandle {
+        &self.handle
+    }
+
+    /// Returns the stream of the current device, for example to tap its output.
+    ///
+    /// Taps, recordings and post-processing belong to that stream and end with a switch.
+    pub fn stream(&self) -> &OutputStream {
+        &self.stream
+    }
+
+    /// Moves playback to `device`, using its default configuration.
+    ///
+    /// On error playback continues on the current device.
+    pub fn switch_to(&mut self, device: &cpal::Device) -> Result<(), StreamError> {
+        let (stream, stream_handle) = OutputStream::try_from_device(device)?;
+        stream_handle
+            .play_raw(self.parking.relay())
+            .map_err(StreamError::PlayError)?;
+        // Dropping the old stream releases the mixer, the new stream picks it up.
+        self.stream = stream;
+        self.device_name = device.name().ok();
+        Ok(())
+    }
+
+    /// Returns the number of channels of the mixer.
+    pub fn channels(&self) -> u16 {
+        self.controller.channels()
+    }
+
+    /// Returns the sample rate of the mixer.
+    pub fn sample_rate(&self) -> u32 {
+        self.controller.sample_rate()
+    }
+}
+
+/// How often the reconnection thread checks for errors and retries a failed reconnection.
+const RECONNECT_POLL: Duration = Duration::from_millis(100);
+
+/// Keeps playing on the default output device, also after the device it played on is gone.
+///
+/// Returned by [`OutputStream::try_default_auto_reconnect`]. When the device reports that it
+/// is no longer available, for example because a headset was unplugged, playback moves to
+/// the device that is the default then, like [`OutputManager::switch_to`] does: handles and
+/// sinks stay valid and continue where they left off. Until a device is available again
+/// reconnecting is retried every 100 milliseconds.
+///
+/// A thread owns the streams. Dropping this stops that thread and ends playback.
+pub struct AutoReconnectStream {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoReconnectStream {
+    pub(crate) fn try_default() -> Result<(AutoReconnectStream, OutputStreamHandle), StreamError> {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("rodio reconnect".to_owned())
+            .spawn(move || {
+                // cpal streams can not move between threads, so they are opened on this one.
+                let manager = match OutputManager::try_default() {
+                    Ok(manager) => manager,
+                    Err(err) => {
+                        let _ = started_tx.send(Err(err));
+                        return;
+                    }
+                };
+                let _ = started_tx.send(Ok(manager.handle().clone()));
+                reconnect(manager, stopped);
+            })
+            .expect("failed to spawn the reconnect thread");
+        let handle = started_rx
+            .recv()
+            .expect("the reconnect thread reports how it started")?;
+        let stream = AutoReconnectStream {
+            stop: Some(stop),
+            thread: Some(thread),
+        };
+        Ok((stream, handle))
+    }
+}
+
+impl Drop for AutoReconnectStream {
+    fn drop(&mut self) {
+        // Closing the channel stops the thread.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Moves playback to the default device whenever the current device is gone, until stopped.
+fn reconnect(mut manager: OutputManager, stopped: Receiver<()>) {
+    let mut errors = manager.stream().error_events();
+    let mut lost = false;
+    loop {
+        match stopped.recv_timeout(RECONNECT_POLL) {
+            Err(RecvTimeoutError::Timeout) => {}
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+        }
+        lost |= errors.try_iter().any(|err| {
+            matches!(
+                err,
+                StreamError::PlaybackError(cpal::StreamError::DeviceNotAvailable)
+            )
+        });
+        if !lost {
+            continue;
+        }
+        let Some(device) = cpal::default_host().default_output_device() else {
+            continue;
+        };
+        if manager.switch_to(&device).is_ok() {
+            errors = manager.stream().error_events();
+            lost = false;
+        }
+    }
+}
+
+/// Where the mixer waits between two devices.
+struct Parking {
+    park: Sender<DynamicMixer<f32>>,
+    parked: Arc<Mutex<Receiver<DynamicMixer<f32>>>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Parking {
+    fn new(mixer: DynamicMixer<f32>) -> Parking {
+        let (park, parked) = mpsc::channel();
+        let (channels, sample_rate) = (mixer.channels(), mixer.sample_rate());
+        park.send(mixer).expect("the receiver is alive");
+        Parking {
+            park,
+            parked: Arc::new(Mutex::new(parked)),
+            channels,
+            sample_rate,
+        }
+    }
+
+    /// Returns a source that plays the mixer once it is parked.
+    fn relay(&self) -> Relay {
+        Relay {
+            mixer: None,
+            park: self.park.clone(),
+            parked: self.parked.clone(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            sample_in_frame: 0,
+        }
+    }
+}
+
+/// Plays the mixer on a device, and parks it again when the device's stream is dropped.
+struct Relay {
+    mixer: Option<DynamicMixer<f32>>,
+    park: Sender<DynamicMixer<f32>>,
+    parked: Arc<Mutex<Receiver<DynamicMixer<f32>>>>,
+    channels: u16,
+    sample_rate: u32,
+    sample_in_frame: u16,
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        if let Some(mut mixer) = self.mixer.take() {
+            // Finish the frame, so the next device starts on the first channel.
+            while self.sample_in_frame != 0 {
+                mixer.next();
+                self.sample_in_frame = (self.sample_in_frame + 1) % self.channels;
+            }
+            let _ = self.park.send(mixer);
+        }
+    }
+}
+
+impl Iterator for Relay {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.mixer.is_none() && self.sample_in_frame == 0 {
+            // Only polled during a switch, never wait for the user thread.
+            if let Ok(parked) = self.parked.try_lock() {
+                self.mixer = parked.try_recv().ok();
+            }
+        }
+        self.sample_in_frame = (self.sample_in_frame + 1) % self.channels;
+        // Silence while waiting for the mixer, or while nothing is playing.
+        Some(self.mixer.as_mut().and_then(Iterator::next).unwrap_or(0.0))
+    }
+}
+
+impl Source for Relay {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Weak};
+
+    use super::Parking;
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::stream::OutputStreamHandle;
+    use crate::Sink;
+
+    #[test]
+    fn sinks_move_to_the_new_device() {
+        let (controller, mixer) = dynamic_mixer::mixer::<f32>(2, 44100);
+        let parking = Parking::new(mixer);
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&controller),
+            controls: Weak::new(),
+        };
+        // Mock devices: mixers of their own that play the relay, pulled like a device would.
+        let (device_a, mut output_a) = dynamic_mixer::mixer::<f32>(2, 44100);
+        device_a.add(parking.relay());
+
+        let sink = Sink::try_new(&handle).unwrap();
+        let samples: Vec<f32> = (1..=4000).map(|n| n as f32 / 4000.0).collect();
+        sink.append(SamplesBuffer::new(1, 44100, samples.clone()));
+        // Stop mid-frame, the relay finishes that frame when it is dropped.
+        let mut played: Vec<f32> = output_a.by_ref().take(2001).collect();
+        played.retain(|&value| value != 0.0);
+        assert!(played.len() % 2 == 1);
+
+        let (device_b, mut output_b) = dynamic_mixer::mixer::<f32>(2, 44100);
+        device_b.add(parking.relay());
+        // The old device still holds the mixer, the new one plays silence meanwhile.
+        assert!(output_b.by_ref().take(10).all(|value| value == 0.0));
+        drop(output_a);
+        let mut switched: Vec<f32> = output_b.by_ref().take(8000).collect();
+        switched.retain(|&value| value != 0.0);
+        // Both channels of the mono sound, starting on the left again.
+        assert!(switched.chunks(2).all(|frame| frame[0] == frame[1]));
+
+        played.extend(switched);
+        played.dedup();
+        assert_eq!(played, samples);
+        assert_eq!(sink.len(), 0);
+        // The sink still plays what is appended afterwards.
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32]));
+        let mut output: Vec<f32> = output_b.take(4000).collect();
+        output.retain(|&value| value != 0.0);
+        assert_eq!(output, vec![0.5, 0.5]);
+    }
+}
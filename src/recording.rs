@@ -0,0 +1,214 @@
+//! Records the output of a stream to a WAV file.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::tap::{OutputTap, TapDrops};
+
+/// How long the writer thread waits for a buffer before checking whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Encoding of the samples in a recorded WAV file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// 32 bit float, the samples exactly as the stream mixed them.
+    #[default]
+    F32,
+    /// 16 bit integer, half the size. Samples beyond full scale are clipped.
+    I16,
+}
+
+impl RecordingFormat {
+    fn spec(self, channels: u16, sample_rate: u32) -> hound::WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            RecordingFormat::F32 => (32, hound::SampleFormat::Float),
+            RecordingFormat::I16 => (16, hound::SampleFormat::Int),
+        };
+        hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+/// Handle to a recording started with `start_recording` on an output stream.
+///
+/// The file is a WAV in the [`RecordingFormat`] asked for, 32 bit float by default, with the
+/// channel count and sample rate the device was actually opened with. It is written on a
+/// separate thread fed by an [`OutputTap`], so recording never blocks playback; if writing
+/// falls too far behind buffers are skipped, see [`dropped`](RecordingHandle::dropped).
+///
+/// Call [`stop`](RecordingHandle::stop) to finish the file and learn about write errors.
+/// Dropping the handle also finishes the file but ignores errors. If the stream is dropped
+/// first the recording ends with the last buffer it played.
+pub struct RecordingHandle {
+    stop: Arc<AtomicBool>,
+    drops: TapDrops,
+    writer: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl RecordingHandle {
+    /// Creates the file at `path` and starts writing the buffers received by `tap` to it.
+    pub(crate) fn start(
+        tap: OutputTap,
+        channels: u16,
+        sample_rate: u32,
+        path: &Path,
+        format: RecordingFormat,
+    ) -> io::Result<RecordingHandle> {
+        let spec = format.spec(channels, sample_rate);
+        let writer = hound::WavWriter::create(path, spec).map_err(wav_to_io_error)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let drops = tap.drops();
+        let writer = thread::Builder::new()
+            .name("rodio recording".to_owned())
+            .spawn(move || write_wav(tap, writer, format, &thread_stop))?;
+        Ok(RecordingHandle {
+            stop,
+            drops,
+            writer: Some(writer),
+        })
+    }
+
+    /// Stops recording and finishes the file.
+    ///
+    /// Everything the stream played up to this call is in the file once it returns.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.finish()
+    }
+
+    /// Returns the number of output buffers missing from the file so far.
+    ///
+    /// Each one is a gap in the recording, left when the writer thread fell more than 32
+    /// buffers behind the stream.
+    pub fn dropped(&self) -> usize {
+        self.drops.count()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.writer.take() {
+            Some(writer) => writer
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("recording thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for RecordingHandle {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn write_wav(
+    tap: OutputTap,
+    mut writer: hound::WavWriter<BufWriter<File>>,
+    format: RecordingFormat,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    while !stop.load(Ordering::Relaxed) && !tap.is_closed() {
+        if let Some(buffer) = tap.recv_timeout(POLL_INTERVAL) {
+            write_buffer(&mut writer, &buffer, format)?;
+        }
+    }
+    while let Some(buffer) = tap.try_recv() {
+        write_buffer(&mut writer, &buffer, format)?;
+    }
+    writer.finalize().map_err(wav_to_io_error)
+}
+
+fn write_buffer(
+    writer: &mut hound::WavWriter<BufWriter<File>>,
+    buffer: &[f32],
+    format: RecordingFormat,
+) -> io::Result<()> {
+    for &sample in buffer {
+        match format {
+            RecordingFormat::F32 => writer.write_sample(sample),
+            RecordingFormat::I16 => writer.write_sample(i16::from_sample(sample)),
+        }
+        .map_err(wav_to_io_error)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn wav_to_io_error(error: hound::Error) -> io::Error {
+    match error {
+        hound::Error::IoError(error) => error,
+        other => io::Error::other(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{RecordingFormat, RecordingHandle};
+    use crate::dynamic_mixer;
+    use crate::source::{SineWave, Source};
+    use crate::stream::{OutputControls, OutputProcessor};
+
+    #[test]
+    fn records_one_second_of_playback() {
+        let path = std::env::temp_dir().join("rodio-recording-test.wav");
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let recording =
+            RecordingHandle::start(controls.taps.tap(), 2, 48000, &path, RecordingFormat::F32)
+                .unwrap();
+
+        mixer.add(SineWave::new(440.0));
+        // One second of stereo output, in fewer buffers than the tap can hold.
+        let mut device = [0.0f32; 9600];
+        for _ in 0..10 {
+            output.fill(&mut device);
+        }
+        assert_eq!(recording.dropped(), 0);
+        recording.stop().unwrap();
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 48000);
+        assert_eq!(reader.duration(), 48000);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn dropping_the_handle_finishes_an_i16_file() {
+        let path = std::env::temp_dir().join("rodio-recording-i16-test.wav");
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(1, 8000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let recording =
+            RecordingHandle::start(controls.taps.tap(), 1, 8000, &path, RecordingFormat::I16)
+                .unwrap();
+
+        mixer.add(SineWave::with_sample_rate(1000.0, 8000).amplify(2.0));
+        let mut device = [0.0f32; 800];
+        output.fill(&mut device);
+        drop(recording);
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        assert_eq!(reader.spec().sample_format, hound::SampleFormat::Int);
+        let samples: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), 800);
+        // The sine is played at twice full scale and clipped.
+        assert_eq!(samples.iter().max(), Some(&i16::MAX));
+        assert_eq!(samples.iter().min(), Some(&i16::MIN));
+        std::fs::remove_file(path).unwrap();
+    }
+}
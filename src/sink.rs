@@ -1,5 +1,6 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::io::{Read, Seek};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::Duration;
 
 #[cfg(feature = "crossbeam-channel")]
@@ -7,9 +8,9 @@ use crossbeam_channel::{Receiver, Sender};
 #[cfg(not(feature = "crossbeam-channel"))]
 use std::sync::mpsc::{Receiver, Sender};
 
-use crate::source::SeekError;
+use crate::source::{cues_to, AtomicFadeDirection, Fadeable, PlaybackHandle, SeekError, Tracked};
 use crate::stream::{OutputStreamHandle, PlayError};
-use crate::{queue, source::Done, Sample, Source};
+use crate::{queue, source::Done, Decoder, Sample, Source};
 use cpal::FromSample;
 
 /// Handle to a device that outputs sounds.
@@ -57,14 +58,159 @@ impl SeekOrder {
     }
 }
 
+/// Playback event of a sound appended with [`Sink::append_with_id`],
+/// [`Sink::append_with_cues`] or [`Sink::append_tracked`].
+///
+/// Received through [`Sink::events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkEvent {
+    /// The first sample of the sound was played.
+    TrackStarted {
+        /// Id the sound was appended with.
+        id: u64,
+    },
+    /// The sound ended, or was removed from the sink before it ended.
+    TrackEnded {
+        /// Id the sound was appended with.
+        id: u64,
+    },
+    /// Playback reached a cue of a sound appended with [`Sink::append_with_cues`].
+    Cue {
+        /// Id the sound was appended with.
+        id: u64,
+        /// The cue that was reached.
+        cue: u64,
+    },
+}
+
+/// What a [`Sink`] is doing, returned by [`Sink::state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SinkState {
+    /// A sound is playing.
+    Playing,
+    /// Sounds are queued, but the sink is paused.
+    Paused,
+    /// The sink was stopped and nothing was appended since.
+    Stopped,
+    /// All sounds ended, the sink waits for new ones.
+    Empty,
+}
+
+/// How often the playing sound of a [`Sink`] is repeated, see [`Sink::set_repeat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RepeatMode {
+    /// Every sound plays once.
+    #[default]
+    Off,
+    /// The playing sound is repeated this many more times, then the mode turns off.
+    Count(u32),
+    /// The playing sound is repeated until the mode changes.
+    Forever,
+}
+
+/// Controls of a single sound appended with [`Sink::append_controllable`].
+///
+/// Every method does nothing once the sound ended or was removed from the sink.
+#[derive(Clone, Debug)]
+pub struct TrackHandle {
+    controls: Weak<TrackControls>,
+    fade: AtomicFadeDirection,
+}
+
+impl TrackHandle {
+    /// Stops the sound, the sink continues with the next one in its queue.
+    pub fn stop(&self) {
+        if let Some(controls) = self.controls.upgrade() {
+            controls.stopped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Changes the volume of this sound only, on top of the volume of the sink.
+    pub fn set_volume(&self, value: f32) {
+        if let Some(controls) = self.controls.upgrade() {
+            controls.volume.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Fades the sound to silence over `duration`, then stops it.
+    pub fn fade_out(&self, duration: Duration) {
+        if let Some(controls) = self.controls.upgrade() {
+            self.fade.fade_to(0.0, duration);
+            controls.stop_when_silent.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns true once the sound ended or was removed from the sink.
+    pub fn is_finished(&self) -> bool {
+        self.controls.strong_count() == 0
+    }
+}
+
+#[derive(Debug)]
+struct TrackControls {
+    stopped: AtomicBool,
+    volume: AtomicU32,
+    // Set by a fade-out, the sound stops once it is silent.
+    stop_when_silent: AtomicBool,
+}
+
+/// How often the playing sound picks up changes to the controls.
+// if you change the duration update the docs for try_seek!
+const CONTROL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Time a change of the crossfader gain glides over, see [`Crossfader`](crate::Crossfader).
+const FADER_RAMP: Duration = Duration::from_millis(30);
+
+/// Glide of the speed towards a target, see [`Sink::ramp_speed`].
+struct SpeedRamp {
+    target: f32,
+    // Change of the speed per control update.
+    step: f32,
+}
+
 struct Controls {
     pause: AtomicBool,
     volume: Mutex<f32>,
+    // Set by `set_volume_smoothed`, `None` changes the volume at once.
+    volume_ramp: Mutex<Option<Duration>>,
+    // Gain of a `Crossfader` the sink is a deck of, on top of the volume.
+    fader: Mutex<f32>,
+    balance: Mutex<f32>,
     stopped: AtomicBool,
     speed: Mutex<f32>,
+    speed_ramp: Mutex<Option<SpeedRamp>>,
+    repeat: Mutex<RepeatMode>,
     to_clear: Mutex<u32>,
     seek: Mutex<Option<SeekOrder>>,
     position: Mutex<Duration>,
+    events: Mutex<Vec<Sender<SinkEvent>>>,
+    next_tracked_id: AtomicU64,
+    on_end: Mutex<Option<Box<dyn FnMut() + Send>>>,
+}
+
+impl Controls {
+    /// Sends an event to every receiver returned by [`Sink::events`] that still exists.
+    fn send_event(&self, event: SinkEvent) {
+        self.events
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.send(event).is_ok());
+    }
+
+    /// Moves the speed one step along a running ramp, and returns the speed to play at.
+    fn step_speed(&self) -> f32 {
+        let mut speed = self.speed.lock().unwrap();
+        let mut ramp = self.speed_ramp.lock().unwrap();
+        if let Some(SpeedRamp { target, step }) = *ramp {
+            if (target - *speed).abs() <= step.abs() {
+                *speed = target;
+                *ramp = None;
+            } else {
+                *speed += step;
+            }
+        }
+        *speed
+    }
 }
 
 impl Sink {
@@ -87,11 +233,19 @@ impl Sink {
             controls: Arc::new(Controls {
                 pause: AtomicBool::new(false),
                 volume: Mutex::new(1.0),
+                volume_ramp: Mutex::new(None),
+                fader: Mutex::new(1.0),
+                balance: Mutex::new(0.0),
                 stopped: AtomicBool::new(false),
                 speed: Mutex::new(1.0),
+                speed_ramp: Mutex::new(None),
+                repeat: Mutex::new(RepeatMode::Off),
                 to_clear: Mutex::new(0),
                 seek: Mutex::new(None),
                 position: Mutex::new(Duration::ZERO),
+                events: Mutex::new(Vec::new()),
+                next_tracked_id: AtomicU64::new(u64::MAX),
+                on_end: Mutex::new(None),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
             detached: false,
@@ -118,50 +272,228 @@ impl Sink {
         let controls = self.controls.clone();
 
         let start_played = AtomicBool::new(false);
+        // Volume and crossfader gain the amplifier was last set to.
+        let mut applied = (1.0, 1.0);
 
-        let source = source
+        let tracked = Replayable::new(source, controls.clone())
+            .pan(0.0)
+            .stereo_only()
             .speed(1.0)
             // must be placed before pausable but after speed & delay
-            .track_position()
-            .pausable(false)
-            .amplify(1.0)
-            .skippable()
-            .stoppable()
-            // if you change the duration update the docs for try_seek!
-            .periodic_access(Duration::from_millis(5), move |src| {
-                if controls.stopped.load(Ordering::SeqCst) {
-                    src.stop();
+            .track_position();
+        // Starting over seeks the position back as well.
+        let source = Repeating {
+            input: tracked,
+            controls: controls.clone(),
+        }
+        .pausable(false)
+        .amplify(1.0)
+        .skippable()
+        .stoppable()
+        .periodic_access(CONTROL_INTERVAL, move |src| {
+            if controls.stopped.load(Ordering::SeqCst) {
+                src.stop();
+                *controls.position.lock().unwrap() = Duration::ZERO;
+            }
+            {
+                let mut to_clear = controls.to_clear.lock().unwrap();
+                if *to_clear > 0 {
+                    src.inner_mut().skip();
+                    *to_clear -= 1;
                     *controls.position.lock().unwrap() = Duration::ZERO;
+                } else {
+                    *controls.position.lock().unwrap() =
+                        src.inner().inner().inner().inner().inner().get_pos();
                 }
-                {
-                    let mut to_clear = controls.to_clear.lock().unwrap();
-                    if *to_clear > 0 {
-                        src.inner_mut().skip();
-                        *to_clear -= 1;
-                        *controls.position.lock().unwrap() = Duration::ZERO;
-                    } else {
-                        *controls.position.lock().unwrap() = src.inner().inner().inner().inner().get_pos();
-                    }
-                }
-                let amp = src.inner_mut().inner_mut();
-                amp.set_factor(*controls.volume.lock().unwrap());
-                amp.inner_mut()
-                    .set_paused(controls.pause.load(Ordering::SeqCst));
-                amp.inner_mut()
-                    .inner_mut()
-                    .inner_mut()
-                    .set_factor(*controls.speed.lock().unwrap());
-                if let Some(seek) = controls.seek.lock().unwrap().take() {
-                    seek.attempt(amp)
-                }
-                start_played.store(true, Ordering::SeqCst);
-            })
-            .convert_samples();
+            }
+            let amp = src.inner_mut().inner_mut();
+            let volume = *controls.volume.lock().unwrap();
+            let fader = *controls.fader.lock().unwrap();
+            // A new sound starts at the volume right away, crossfader moves always glide.
+            match *controls.volume_ramp.lock().unwrap() {
+                _ if !start_played.load(Ordering::SeqCst) => amp.set_factor(volume * fader),
+                Some(ramp) => amp.set_factor_smoothed(volume * fader, ramp),
+                None if volume != applied.0 => amp.set_factor(volume * fader),
+                None if fader != applied.1 => amp.set_factor_smoothed(volume * fader, FADER_RAMP),
+                None => {}
+            }
+            applied = (volume, fader);
+            amp.inner_mut()
+                .set_paused(controls.pause.load(Ordering::SeqCst));
+            let speed = amp.inner_mut().inner_mut().inner_mut().inner_mut();
+            speed.set_factor(controls.step_speed());
+            speed
+                .inner_mut()
+                .set_position(*controls.balance.lock().unwrap());
+            if let Some(seek) = controls.seek.lock().unwrap().take() {
+                seek.attempt(amp)
+            }
+            start_played.store(true, Ordering::SeqCst);
+        })
+        .convert_samples();
         self.sound_count.fetch_add(1, Ordering::Relaxed);
-        let source = Done::new(source, self.sound_count.clone());
+        let source = Drained {
+            input: Done::new(source, self.sound_count.clone()),
+            controls: self.controls.clone(),
+            sound_count: self.sound_count.clone(),
+            ended: false,
+        };
         *self.sleep_until_end.lock().unwrap() = Some(self.queue_tx.append_with_signal(source));
     }
 
+    /// Appends a sound to the queue and returns a handle to stop, fade or change the volume of
+    /// that sound only, while the others in the queue are not affected.
+    pub fn append_controllable<S>(&self, source: S) -> TrackHandle
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        let (input, fade) = source.fadeable(Duration::ZERO);
+        let controls = Arc::new(TrackControls {
+            stopped: AtomicBool::new(false),
+            volume: AtomicU32::new(1.0f32.to_bits()),
+            stop_when_silent: AtomicBool::new(false),
+        });
+        let handle = TrackHandle {
+            controls: Arc::downgrade(&controls),
+            fade,
+        };
+        self.append(Controlled {
+            input,
+            controls,
+            channel: 0,
+        });
+        handle
+    }
+
+    /// Appends a sound to the queue and returns a handle to follow its playback.
+    ///
+    /// Unlike [`sleep_until_end`](Sink::sleep_until_end), which waits for the whole queue, the
+    /// [`PlaybackHandle`] waits for this sound only:
+    /// [`wait_for_start`](PlaybackHandle::wait_for_start) returns once its first sample is
+    /// played, for example to start an animation in sync, and
+    /// [`wait_for_end`](PlaybackHandle::wait_for_end) once it ended or was removed from the queue.
+    /// Both follow the [`SinkEvent`]s of the sound, the ones [`events`](Sink::events) receives,
+    /// which the audio thread sends as it pulls the sound; there is no polling involved.
+    ///
+    /// The sound's events carry the id returned by [`PlaybackHandle::id`]. Tracked sounds get
+    /// ids counting down from `u64::MAX`, so they do not clash with the small ids usually
+    /// passed to [`append_with_id`](Sink::append_with_id).
+    pub fn append_tracked<S>(&self, source: S) -> PlaybackHandle
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        let id = self
+            .controls
+            .next_tracked_id
+            .fetch_sub(1, Ordering::Relaxed);
+        let handle = PlaybackHandle::new(id, self.events());
+        let controls = self.controls.clone();
+        self.append(Tracked::with_events(source, id, move |event| {
+            controls.send_event(event)
+        }));
+        handle
+    }
+
+    /// Appends a sound to the queue, tagged with an id that is reported in its [`SinkEvent`]s.
+    ///
+    /// The id lets the events be matched with the application's own data, without relying on
+    /// the order sounds were appended in. Ids are echoed verbatim and need not be unique.
+    pub fn append_with_id<S>(&self, source: S, id: u64)
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        let controls = self.controls.clone();
+        self.append(Tracked::with_events(source, id, move |event| {
+            controls.send_event(event)
+        }));
+    }
+
+    /// Appends a sound tagged with an id, like [`append_with_id`](Sink::append_with_id), that
+    /// sends a [`SinkEvent::Cue`] when playback reaches each of the timestamps in `cues`.
+    ///
+    /// The events carry the id of the sound along with the cue, so cues of different sounds
+    /// can be told apart. They fire as described for [`Source::with_cues`].
+    pub fn append_with_cues<S>(&self, source: S, id: u64, cues: Vec<(Duration, u64)>)
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        let controls = self.controls.clone();
+        let source = Tracked::with_events(source, id, move |event| controls.send_event(event));
+        let controls = self.controls.clone();
+        self.append(cues_to(source, cues, move |cue| {
+            controls.send_event(SinkEvent::Cue { id, cue })
+        }));
+    }
+
+    /// Returns a receiver for the [`SinkEvent`]s of the sounds appended with an id or tracked.
+    ///
+    /// Every receiver gets all events sent after it was created.
+    pub fn events(&self) -> Receiver<SinkEvent> {
+        #[cfg(not(feature = "crossbeam-channel"))]
+        let (tx, rx) = std::sync::mpsc::channel();
+        #[cfg(feature = "crossbeam-channel")]
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.controls.events.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Sets a callback that is called every time the last sound in the queue ended, replacing
+    /// the previous one.
+    ///
+    /// It is not called when another sound was appended before the last one ended, the queue
+    /// then keeps playing. Sounds removed with [`clear`](Sink::clear) or
+    /// [`skip_one`](Sink::skip_one) end as well. The callback runs on the audio thread, so it
+    /// should return quickly, for example by sending a message to the thread that loads the
+    /// next sounds.
+    pub fn set_on_end(&self, callback: Box<dyn FnMut() + Send>) {
+        *self.controls.on_end.lock().unwrap() = Some(callback);
+    }
+
+    /// Appends a sound that loops forever but is only heard while `should_play` is true.
+    ///
+    /// This is meant for installations that should only play during certain hours. Rodio does
+    /// not know about the time of day, the caller drives the schedule by setting `should_play`
+    /// whenever the window opens or closes, for example from a timer thread. Outside the window
+    /// silence is played and the sound holds its position; it fades in and out over a few
+    /// milliseconds at the edges of the window (see [`Source::gated_by`]).
+    ///
+    /// Since the sound never ends, the sink never becomes empty by itself. Use
+    /// [`skip_one`](Sink::skip_one), [`clear`](Sink::clear) or [`stop`](Sink::stop) to remove it.
+    pub fn append_scheduled<S>(&self, source: S, should_play: Arc<AtomicBool>)
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send + Sync,
+    {
+        self.append(source.repeat_infinite().gated_by(should_play, false));
+    }
+
+    /// Appends a decoded sound that loops forever, between its embedded loop points if it has
+    /// any.
+    ///
+    /// Files with [loop points](Decoder::loop_points) play up to the end of the loop once and
+    /// then repeat the loop seamlessly, other files repeat as a whole. A `crossfade` other than
+    /// zero blends the end of the loop into its start, which hides a seam that does not line up
+    /// perfectly. See [`Source::loop_region`] for details.
+    ///
+    /// Since the sound never ends, the sink never becomes empty by itself. Use
+    /// [`skip_one`](Sink::skip_one), [`clear`](Sink::clear) or [`stop`](Sink::stop) to remove it.
+    pub fn append_looped<R>(&self, decoder: Decoder<R>, crossfade: Duration)
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let frames = decoder.loop_points().unwrap_or(0..u64::MAX);
+        self.append(decoder.loop_region(frames, crossfade));
+    }
+
     /// Gets the volume of the sound.
     ///
     /// The value `1.0` is the "normal" volume (unfiltered input). Any value other than 1.0 will
@@ -178,6 +510,42 @@ impl Sink {
     #[inline]
     pub fn set_volume(&self, value: f32) {
         *self.controls.volume.lock().unwrap() = value;
+        *self.controls.volume_ramp.lock().unwrap() = None;
+    }
+
+    /// Changes the volume of the sound gradually, over `ramp` of playback.
+    ///
+    /// A sudden change in volume clicks, gliding over a short time such as 15 milliseconds
+    /// avoids that, for example while a volume slider is dragged. A new change takes over from
+    /// the volume reached so far. [`volume`](Sink::volume) returns the target right away.
+    #[inline]
+    pub fn set_volume_smoothed(&self, value: f32, ramp: Duration) {
+        *self.controls.volume.lock().unwrap() = value;
+        *self.controls.volume_ramp.lock().unwrap() = Some(ramp);
+    }
+
+    /// Changes the gain of the crossfader this sink is a deck of, applied on top of the volume.
+    /// The change glides over 30 milliseconds.
+    #[inline]
+    pub(crate) fn set_fader(&self, gain: f32) {
+        *self.controls.fader.lock().unwrap() = gain;
+    }
+
+    /// Gets the stereo balance, from -1.0 for fully left to 1.0 for fully right.
+    #[inline]
+    pub fn balance(&self) -> f32 {
+        *self.controls.balance.lock().unwrap()
+    }
+
+    /// Changes the stereo balance, from -1.0 for fully left over 0.0 for centered to 1.0 for
+    /// fully right. Values out of that range are clamped.
+    ///
+    /// Stereo sounds are balanced with the equal-power law of [`Source::pan`], which keeps the
+    /// perceived loudness constant while moving the sound; the remaining channel gets up to
+    /// 3 dB louder at the sides. Sounds that are not stereo are not affected.
+    #[inline]
+    pub fn set_balance(&self, value: f32) {
+        *self.controls.balance.lock().unwrap() = value.clamp(-1.0, 1.0);
     }
 
     /// Changes the play speed of the sound. Does not adjust the samples, only the playback speed.
@@ -214,6 +582,44 @@ impl Sink {
     #[inline]
     pub fn set_speed(&self, value: f32) {
         *self.controls.speed.lock().unwrap() = value;
+        *self.controls.speed_ramp.lock().unwrap() = None;
+    }
+
+    /// Changes the speed gradually, reaching `target` after `duration` of playback.
+    ///
+    /// Unlike [`set_speed`](Sink::set_speed), which changes the speed and with it the pitch at
+    /// once, the speed glides towards the target in steps of 5 milliseconds. A new ramp, or a
+    /// call to `set_speed`, replaces one that is still running: a new ramp starts from the
+    /// speed reached so far, so there is no jump. While paused the ramp continues.
+    ///
+    /// [`speed`](Sink::speed) returns the speed reached so far.
+    pub fn ramp_speed(&self, target: f32, duration: Duration) {
+        let speed = self.controls.speed.lock().unwrap();
+        let steps = (duration.as_secs_f32() / CONTROL_INTERVAL.as_secs_f32()).max(1.0);
+        *self.controls.speed_ramp.lock().unwrap() = Some(SpeedRamp {
+            target,
+            step: (target - *speed) / steps,
+        });
+    }
+
+    /// Gets the repeat mode, a [`RepeatMode::Count`] counts down with every repetition.
+    #[inline]
+    pub fn repeat(&self) -> RepeatMode {
+        *self.controls.repeat.lock().unwrap()
+    }
+
+    /// Repeats the playing sound, a number of times or until the mode changes.
+    ///
+    /// The mode is checked every time the playing sound ends, so a change takes effect when
+    /// the sound would start over rather than restarting it right away. The mode applies to
+    /// whichever sound is playing, once it is off the queue continues with the next sound.
+    ///
+    /// A sound starts over by seeking it back to its start. Sounds that do not support
+    /// [`try_seek`](Sink::try_seek) are kept in memory while they play so they can be replayed,
+    /// but only if the mode was already on when they started.
+    #[inline]
+    pub fn set_repeat(&self, mode: RepeatMode) {
+        *self.controls.repeat.lock().unwrap() = mode;
     }
 
     /// Resumes playback of a paused sink.
@@ -286,7 +692,9 @@ impl Sink {
 
     /// Removes all currently loaded `Source`s from the `Sink`, and pauses it.
     ///
-    /// See `pause()` for information about pausing a `Sink`.
+    /// Unlike [`stop`](Sink::stop) the sink stays ready for new sounds, which play with the
+    /// same volume, speed and balance once the sink is resumed with `play()`. See `pause()` for
+    /// information about pausing a `Sink`.
     pub fn clear(&self) {
         let len = self.sound_count.load(Ordering::SeqCst) as u32;
         *self.controls.to_clear.lock().unwrap() = len;
@@ -340,9 +748,34 @@ impl Sink {
         self.sound_count.load(Ordering::Relaxed)
     }
 
+    /// Returns the number of sounds waiting behind the one that is playing.
+    ///
+    /// Unlike [`len`](Sink::len) this does not count the current sound.
+    #[inline]
+    pub fn queued_len(&self) -> usize {
+        self.len().saturating_sub(1)
+    }
+
+    /// Returns whether the sink is playing, paused, stopped or empty.
+    ///
+    /// A stopped sink reports [`SinkState::Stopped`] until a sound is appended, also once the
+    /// sounds it held are gone. A paused sink without sounds reports [`SinkState::Empty`].
+    pub fn state(&self) -> SinkState {
+        if self.controls.stopped.load(Ordering::SeqCst) {
+            SinkState::Stopped
+        } else if self.empty() {
+            SinkState::Empty
+        } else if self.is_paused() {
+            SinkState::Paused
+        } else {
+            SinkState::Playing
+        }
+    }
+
     /// Returns the position of the sound that's being played.
     ///
-    /// This takes into account any speedup or delay applied.
+    /// This takes into account any speedup or delay applied. The position starts at zero again
+    /// when the next sound in the queue starts, and is updated every 5 milliseconds of playback.
     ///
     /// Example: if you apply a speedup of *2* to an mp3 decoder source and
     /// [`get_pos()`](Sink::get_pos) returns *5s* then the position in the mp3
@@ -353,6 +786,413 @@ impl Sink {
     }
 }
 
+/// Applies the controls of a [`TrackHandle`].
+struct Controlled<I> {
+    input: Fadeable<I>,
+    controls: Arc<TrackControls>,
+    // Position in the current frame, the sound only stops between frames.
+    channel: u16,
+}
+
+impl<I> Iterator for Controlled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            if self.controls.stop_when_silent.load(Ordering::Relaxed)
+                && self.input.current_factor() == 0.0
+            {
+                self.controls.stopped.store(true, Ordering::Relaxed);
+            }
+            if self.controls.stopped.load(Ordering::Relaxed) {
+                return None;
+            }
+        }
+        let value = self.input.next()?;
+        self.channel = (self.channel + 1) % self.input.channels().max(1);
+        let volume = f32::from_bits(self.controls.volume.load(Ordering::Relaxed));
+        Some(value.amplify(volume))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for Controlled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// Lets a sound start over even if it cannot seek, by keeping a copy of what it played and
+/// replaying that copy.
+///
+/// Only sounds that start while the [`RepeatMode`] is on are copied, and only until they end.
+struct Replayable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    controls: Arc<Controls>,
+    started: bool,
+    // Whether `spans` holds what was played from the start.
+    recording: bool,
+    // What was played so far, split wherever the format changes.
+    spans: Vec<Span<I::Item>>,
+    span: usize,
+    position: usize,
+}
+
+struct Span<S> {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<S>,
+}
+
+impl<I> Replayable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn new(input: I, controls: Arc<Controls>) -> Self {
+        Replayable {
+            input,
+            controls,
+            started: false,
+            recording: false,
+            spans: Vec::new(),
+            span: 0,
+            position: 0,
+        }
+    }
+
+    /// The span being replayed, `None` once playing catches up with the input.
+    fn replaying(&self) -> Option<&Span<I::Item>> {
+        self.spans
+            .get(self.span)
+            .filter(|span| self.position < span.samples.len())
+    }
+
+    /// Forgets the copy, the input plays on by itself.
+    fn stop_recording(&mut self) {
+        self.recording = false;
+        self.spans = Vec::new();
+        self.span = 0;
+        self.position = 0;
+    }
+}
+
+impl<I> Iterator for Replayable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.started {
+            self.started = true;
+            // Infinite sounds never end, so there is nothing to start over.
+            let repeat = *self.controls.repeat.lock().unwrap();
+            self.recording = !matches!(repeat, RepeatMode::Off | RepeatMode::Count(0))
+                && !self.input.is_infinite();
+        }
+        if !self.recording {
+            return self.input.next();
+        }
+
+        if let Some(span) = self.replaying() {
+            let (value, len) = (span.samples[self.position], span.samples.len());
+            self.position += 1;
+            if self.position == len && self.span + 1 < self.spans.len() {
+                self.span += 1;
+                self.position = 0;
+            }
+            return Some(value);
+        }
+
+        let channels = self.input.channels();
+        let sample_rate = self.input.sample_rate();
+        let value = self.input.next()?;
+        match self.spans.last_mut() {
+            Some(span) if span.channels == channels && span.sample_rate == sample_rate => {
+                span.samples.push(value)
+            }
+            _ => self.spans.push(Span {
+                channels,
+                sample_rate,
+                samples: vec![value],
+            }),
+        }
+        self.span = self.spans.len() - 1;
+        self.position = self.spans[self.span].samples.len();
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Replayable<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.replaying() {
+            Some(span) if self.span + 1 < self.spans.len() => {
+                Some(span.samples.len() - self.position)
+            }
+            // The last span continues with what the input has left of its frame.
+            Some(span) => self
+                .input
+                .current_frame_len()
+                .map(|len| len + span.samples.len() - self.position),
+            None => self.input.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self.replaying() {
+            Some(span) => span.channels,
+            None => self.input.channels(),
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        match self.replaying() {
+            Some(span) => span.sample_rate,
+            None => self.input.sample_rate(),
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        match self.input.try_seek(pos) {
+            // The input seeks by itself, a copy is of no use.
+            Ok(()) => {
+                self.stop_recording();
+                Ok(())
+            }
+            Err(_) if self.recording && pos.is_zero() => {
+                self.span = 0;
+                self.position = 0;
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Starts the sound over when it ends, as long as the [`RepeatMode`] of the sink asks for it.
+struct Repeating<I> {
+    input: I,
+    controls: Arc<Controls>,
+}
+
+impl<I> Repeating<I> {
+    fn inner(&self) -> &I {
+        &self.input
+    }
+
+    fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+}
+
+impl<I> Iterator for Repeating<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.input.next() {
+            return Some(value);
+        }
+        {
+            let mut repeat = self.controls.repeat.lock().unwrap();
+            match *repeat {
+                RepeatMode::Off | RepeatMode::Count(0) => return None,
+                RepeatMode::Count(1) => *repeat = RepeatMode::Off,
+                RepeatMode::Count(left) => *repeat = RepeatMode::Count(left - 1),
+                RepeatMode::Forever => {}
+            }
+        }
+        self.input.try_seek(Duration::ZERO).ok()?;
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.input.size_hint().0, None)
+    }
+}
+
+impl<I> Source for Repeating<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+/// Calls the callback set with [`Sink::set_on_end`] when the queue ran empty with this sound.
+struct Drained<I> {
+    input: I,
+    controls: Arc<Controls>,
+    sound_count: Arc<AtomicUsize>,
+    ended: bool,
+}
+
+impl<I> Iterator for Drained<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.input.next();
+        if next.is_none() && !self.ended {
+            self.ended = true;
+            // Appending counts the sound before queueing it, a zero count means nothing follows.
+            if self.sound_count.load(Ordering::Acquire) == 0 {
+                if let Some(callback) = self.controls.on_end.lock().unwrap().as_mut() {
+                    callback();
+                }
+            }
+        }
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Drained<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
 impl Drop for Sink {
     #[inline]
     fn drop(&mut self) {
@@ -367,8 +1207,13 @@ impl Drop for Sink {
 #[cfg(test)]
 mod tests {
     use crate::buffer::SamplesBuffer;
+    use crate::sink::{RepeatMode, SinkEvent, SinkState};
+    use crate::source::{chirp, SeekError};
     use crate::{Sink, Source};
-    use std::sync::atomic::Ordering;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn test_pause_and_stop() {
@@ -398,7 +1243,403 @@ mod tests {
 
         assert_eq!(queue_rx.next(), Some(0.0));
 
-        assert_eq!(sink.empty(), true);
+        assert!(sink.empty());
+    }
+
+    #[test]
+    fn test_append_scheduled() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let should_play = Arc::new(AtomicBool::new(false));
+        sink.append_scheduled(
+            SamplesBuffer::new(1, 48000, vec![0.5f32, -0.5]),
+            should_play.clone(),
+        );
+
+        assert!(queue_rx.by_ref().take(1000).all(|value| value == 0.0));
+
+        should_play.store(true, Ordering::Relaxed);
+        // Skip the fade in, then the sound loops.
+        let playing: Vec<f32> = queue_rx.by_ref().skip(1000).take(4).collect();
+        assert_eq!(playing, vec![0.5, -0.5, 0.5, -0.5]);
+        assert!(!sink.empty());
+    }
+
+    #[test]
+    fn test_append_tracked() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.append(SamplesBuffer::new(1, 48000, vec![0.5f32; 4]));
+        let second = sink.append_tracked(SamplesBuffer::new(1, 48000, vec![-0.5f32; 4]));
+
+        let waiter = {
+            let second = second.clone();
+            std::thread::spawn(move || second.wait_for_start())
+        };
+        assert!(queue_rx.by_ref().take(4).all(|value| value == 0.5));
+        assert!(!second.has_started());
+        assert_eq!(queue_rx.next(), Some(-0.5));
+        assert!(waiter.join().unwrap());
+        assert!(!second.has_ended());
+
+        queue_rx.by_ref().take(4).for_each(drop);
+        assert!(second.wait_for_end());
+        assert_eq!(second.id(), u64::MAX);
+    }
+
+    #[test]
+    fn test_append_with_id() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let events = sink.events();
+        sink.append_with_id(SamplesBuffer::new(1, 48000, vec![0.5f32; 4]), 7);
+        sink.append_with_id(SamplesBuffer::new(1, 48000, vec![-0.5f32; 4]), 3);
+
+        queue_rx.by_ref().take(9).for_each(drop);
+        let received: Vec<SinkEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                SinkEvent::TrackStarted { id: 7 },
+                SinkEvent::TrackEnded { id: 7 },
+                SinkEvent::TrackStarted { id: 3 },
+                SinkEvent::TrackEnded { id: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_append_with_cues() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let events = sink.events();
+        let cues = |at| vec![(Duration::from_secs(at), 1), (Duration::from_secs(1), 2)];
+        sink.append_with_cues(SamplesBuffer::new(1, 4, vec![0.5f32; 8]), 7, cues(0));
+        sink.append_with_cues(SamplesBuffer::new(1, 4, vec![-0.5f32; 8]), 3, cues(1));
+
+        queue_rx.by_ref().take(17).for_each(drop);
+        let received: Vec<SinkEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                SinkEvent::TrackStarted { id: 7 },
+                SinkEvent::Cue { id: 7, cue: 1 },
+                SinkEvent::Cue { id: 7, cue: 2 },
+                SinkEvent::TrackEnded { id: 7 },
+                SinkEvent::TrackStarted { id: 3 },
+                SinkEvent::Cue { id: 3, cue: 1 },
+                SinkEvent::Cue { id: 3, cue: 2 },
+                SinkEvent::TrackEnded { id: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_seek() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let playing = Arc::new(AtomicBool::new(true));
+        // Seeks are carried out by the audio thread, which this one stands in for.
+        let output = {
+            let playing = playing.clone();
+            thread::spawn(move || {
+                while playing.load(Ordering::Relaxed) {
+                    queue_rx.next();
+                }
+            })
+        };
+
+        let (fadeable, _) =
+            SamplesBuffer::new(1, 44100, vec![0.5f32; 44100]).fadeable(Duration::ZERO);
+        sink.append(fadeable);
+        assert!(sink.try_seek(Duration::from_millis(500)).is_ok());
+        assert!(sink.get_pos() >= Duration::from_millis(500));
+
+        sink.clear();
+        sink.play();
+        sink.append(chirp(
+            cpal::SampleRate(44100),
+            200.0,
+            400.0,
+            Duration::from_secs(1),
+        ));
+        assert!(matches!(
+            sink.try_seek(Duration::from_millis(500)),
+            Err(SeekError::NotSupported { .. })
+        ));
+
+        playing.store(false, Ordering::Relaxed);
+        output.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_pos() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.set_speed(2.0);
+        // Two sounds of one second, played at 88200 samples per second.
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 44100]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 44100]));
+
+        queue_rx.by_ref().take(22050).for_each(drop);
+        let pos = sink.get_pos().as_secs_f32();
+        assert!((pos - 0.25).abs() < 0.01, "{pos}");
+
+        // The position restarts with the next sound.
+        queue_rx.by_ref().take(22050 + 4410).for_each(drop);
+        let pos = sink.get_pos().as_secs_f32();
+        assert!((pos - 0.05).abs() < 0.01, "{pos}");
+    }
+
+    #[test]
+    fn test_ramp_speed() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 441000]));
+        sink.ramp_speed(2.0, Duration::from_millis(100));
+
+        queue_rx.by_ref().take(2205).for_each(drop);
+        let reached = sink.speed();
+        assert!(reached > 1.0 && reached < 2.0, "{reached}");
+
+        // Retargeting continues from the speed reached so far.
+        sink.ramp_speed(1.0, Duration::from_millis(100));
+        assert_eq!(sink.speed(), reached);
+        let mut speeds = Vec::new();
+        for _ in 0..40 {
+            queue_rx.by_ref().take(441).for_each(drop);
+            speeds.push(sink.speed());
+        }
+        assert!(speeds.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(sink.speed(), 1.0);
+    }
+
+    #[test]
+    fn test_on_end() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let ends = Arc::new(AtomicUsize::new(0));
+        {
+            let ends = ends.clone();
+            sink.set_on_end(Box::new(move || {
+                ends.fetch_add(1, Ordering::Relaxed);
+            }));
+        }
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 4]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 4]));
+
+        // The second sound follows the first, the queue did not run empty.
+        queue_rx.by_ref().take(6).for_each(drop);
+        assert_eq!(ends.load(Ordering::Relaxed), 0);
+        queue_rx.by_ref().take(4).for_each(drop);
+        assert_eq!(ends.load(Ordering::Relaxed), 1);
+
+        // Picked up once the silence played meanwhile ends.
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 4]));
+        queue_rx.by_ref().take(1024).for_each(drop);
+        assert_eq!(ends.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_skip_one() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 44100]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32; 4]));
+        assert_eq!(queue_rx.next(), Some(0.5));
+
+        // The current sound is skipped on the next control update.
+        sink.skip_one();
+        let played: Vec<f32> = queue_rx.by_ref().take(1000).collect();
+        assert!(played.iter().filter(|&&value| value == 0.5).count() < 441);
+        assert_eq!(played.iter().filter(|&&value| value == -0.5).count(), 4);
+        assert!(sink.empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let playing = Arc::new(AtomicBool::new(true));
+        let output = {
+            let playing = playing.clone();
+            thread::spawn(move || {
+                while playing.load(Ordering::Relaxed) {
+                    queue_rx.next();
+                }
+            })
+        };
+        sink.set_volume(0.5);
+        sink.set_speed(1.5);
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 441000]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 441000]));
+
+        sink.clear();
+        assert!(sink.empty());
+        assert!(sink.is_paused());
+        // The settings stay for the sounds appended next.
+        assert_eq!(sink.volume(), 0.5);
+        assert_eq!(sink.speed(), 1.5);
+
+        playing.store(false, Ordering::Relaxed);
+        output.join().unwrap();
+    }
+
+    #[test]
+    fn test_append_controllable() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let handle = sink.append_controllable(SamplesBuffer::new(1, 44100, vec![0.5f32; 44100]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32; 4]));
+
+        assert_eq!(queue_rx.next(), Some(0.5));
+        handle.set_volume(0.5);
+        assert_eq!(queue_rx.next(), Some(0.25));
+
+        // Fades over 441 samples, then the next sound starts.
+        handle.fade_out(Duration::from_millis(10));
+        let played: Vec<f32> = queue_rx
+            .by_ref()
+            .take_while(|&value| value >= 0.0)
+            .collect();
+        assert!(played.len() > 400 && played.len() < 450);
+        assert!(played.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(handle.is_finished());
+        assert_eq!(queue_rx.by_ref().take(3).collect::<Vec<_>>(), vec![-0.5; 3]);
+
+        // No effect on the sounds that follow.
+        handle.stop();
+        assert_eq!(sink.len(), 1);
+    }
+
+    #[test]
+    fn test_track_stops_between_frames() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let stereo = |left, right| SamplesBuffer::new(2, 44100, [left, right].repeat(100));
+        let handle = sink.append_controllable(stereo(0.1f32, 0.2));
+        sink.append(stereo(0.3, 0.4));
+
+        // Stopped after an odd number of samples, the frame is finished first.
+        assert_eq!(
+            queue_rx.by_ref().take(3).collect::<Vec<_>>(),
+            [0.1, 0.2, 0.1]
+        );
+        handle.stop();
+        assert_eq!(queue_rx.next(), Some(0.2));
+        assert_eq!(
+            queue_rx.by_ref().take(4).collect::<Vec<_>>(),
+            [0.3, 0.4, 0.3, 0.4]
+        );
+    }
+
+    #[test]
+    fn test_set_volume_smoothed() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.append(SamplesBuffer::new(1, 44100, vec![1.0f32; 44100]));
+        assert_eq!(queue_rx.next(), Some(1.0));
+
+        // Picked up on the next control update, then glides over 441 samples.
+        sink.set_volume_smoothed(0.0, Duration::from_millis(10));
+        let played: Vec<f32> = queue_rx.by_ref().take(1000).collect();
+        assert!(played.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(played.windows(2).all(|pair| pair[0] - pair[1] < 0.01));
+        assert_eq!(played[999], 0.0);
+
+        // Changed at once.
+        sink.set_volume(1.0);
+        let played: Vec<f32> = queue_rx.by_ref().take(1000).collect();
+        assert!(played.iter().all(|&value| value == 0.0 || value == 1.0));
+        assert_eq!(played[999], 1.0);
+    }
+
+    #[test]
+    fn test_state() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        assert_eq!(sink.state(), SinkState::Empty);
+
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 4]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32; 4]));
+        assert_eq!(sink.state(), SinkState::Playing);
+        assert_eq!(sink.queued_len(), 1);
+
+        sink.pause();
+        assert_eq!(sink.state(), SinkState::Paused);
+        sink.play();
+        queue_rx.by_ref().take(6).for_each(drop);
+        assert_eq!(sink.queued_len(), 0);
+        queue_rx.by_ref().take(4).for_each(drop);
+        assert_eq!(sink.state(), SinkState::Empty);
+
+        sink.stop();
+        assert_eq!(sink.state(), SinkState::Stopped);
+    }
+
+    #[test]
+    fn test_set_repeat() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.set_repeat(RepeatMode::Count(2));
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32]));
+
+        let played: Vec<f32> = queue_rx.by_ref().take(7).collect();
+        assert_eq!(played, vec![0.5, 0.25, 0.5, 0.25, 0.5, 0.25, -0.5]);
+        assert_eq!(sink.repeat(), RepeatMode::Off);
+        queue_rx.by_ref().take(10).for_each(drop);
+        assert!(sink.empty());
+
+        // Turning the mode off lets the playing repetition finish.
+        sink.set_repeat(RepeatMode::Forever);
+        sink.append(SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]));
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32]));
+        let played: Vec<f32> = queue_rx
+            .by_ref()
+            .skip_while(|&value| value == 0.0)
+            .take(5)
+            .collect();
+        assert_eq!(played, vec![0.5, 0.25, 0.5, 0.25, 0.5]);
+        sink.set_repeat(RepeatMode::Off);
+        let played: Vec<f32> = queue_rx.by_ref().take(2).collect();
+        assert_eq!(played, vec![0.25, -0.5]);
+    }
+
+    #[test]
+    fn test_repeat_without_seeking() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        sink.set_repeat(RepeatMode::Count(2));
+        // Buffered sources do not support seeking.
+        let source = SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]).buffered();
+        assert!(matches!(
+            source.clone().try_seek(Duration::ZERO),
+            Err(SeekError::NotSupported { .. })
+        ));
+        sink.append(source);
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32]));
+
+        let played: Vec<f32> = queue_rx.by_ref().take(7).collect();
+        assert_eq!(played, vec![0.5, 0.25, 0.5, 0.25, 0.5, 0.25, -0.5]);
+    }
+
+    #[cfg(feature = "wav")]
+    #[test]
+    fn test_append_keeps_the_position_of_a_seeked_sound() {
+        use crate::Decoder;
+        use std::fs::File;
+        use std::io::BufReader;
+
+        let decoder = || Decoder::new(BufReader::new(File::open("assets/music.wav").unwrap()));
+        let mut seeked = decoder().unwrap();
+        seeked.try_seek(Duration::from_secs(1)).unwrap();
+        let (sink, queue_rx) = Sink::new_idle();
+        sink.append(seeked);
+
+        let mut expected = decoder().unwrap();
+        expected.try_seek(Duration::from_secs(1)).unwrap();
+        let expected: Vec<f32> = expected.convert_samples().take(1000).collect();
+        let played: Vec<f32> = queue_rx.take(1000).collect();
+        assert_eq!(played, expected);
+    }
+
+    #[test]
+    fn test_repeat_turned_on_while_playing() {
+        let (sink, mut queue_rx) = Sink::new_idle();
+        let source = SamplesBuffer::new(1, 44100, vec![0.5f32, 0.25]).buffered();
+        sink.append(source);
+        assert_eq!(queue_rx.next(), Some(0.5));
+        // The sound can not seek and was not copied, it ends as if the mode was off.
+        sink.set_repeat(RepeatMode::Forever);
+        sink.append(SamplesBuffer::new(1, 44100, vec![-0.5f32]));
+
+        let played: Vec<f32> = queue_rx.by_ref().take(2).collect();
+        assert_eq!(played, vec![0.25, -0.5]);
     }
 
     #[test]
@@ -0,0 +1,254 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Adsr` object.
+pub fn adsr<I>(
+    input: I,
+    attack: Duration,
+    decay: Duration,
+    sustain: f32,
+    release: Duration,
+) -> (Adsr<I>, AdsrHandle)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let note_off = Arc::new(AtomicBool::new(false));
+    let adsr = Adsr {
+        input,
+        attack,
+        decay,
+        sustain,
+        release,
+        note_off: note_off.clone(),
+        stage: Stage::Attack,
+        frames_in_stage: 0,
+        level: 0.0,
+        release_from: 0.0,
+        sample_in_frame: 0,
+    };
+    (adsr, AdsrHandle(note_off))
+}
+
+/// Handle to release the note of an [`Adsr`] from another thread.
+#[derive(Clone, Debug)]
+pub struct AdsrHandle(Arc<AtomicBool>);
+
+impl AdsrHandle {
+    /// Triggers the release stage. The envelope ramps from its current level to silence
+    /// over the release duration, after which the source ends.
+    pub fn note_off(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Done,
+}
+
+/// Filter that applies an attack-decay-sustain-release envelope to the source.
+#[derive(Clone, Debug)]
+pub struct Adsr<I> {
+    input: I,
+    attack: Duration,
+    decay: Duration,
+    sustain: f32,
+    release: Duration,
+    note_off: Arc<AtomicBool>,
+    stage: Stage,
+    frames_in_stage: u64,
+    level: f32,
+    release_from: f32,
+    sample_in_frame: u16,
+}
+
+impl<I> Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Computes the envelope level for the next frame. Returns `None` once the release
+    /// stage has finished.
+    fn next_level(&mut self) -> Option<f32> {
+        if self.note_off.load(Ordering::Relaxed)
+            && !matches!(self.stage, Stage::Release | Stage::Done)
+        {
+            self.release_from = self.level;
+            self.stage = Stage::Release;
+            self.frames_in_stage = 0;
+        }
+
+        let rate = self.input.sample_rate() as f32;
+        let frames = |duration: Duration| (duration.as_secs_f32() * rate) as u64;
+
+        loop {
+            let elapsed = self.frames_in_stage as f32;
+            match self.stage {
+                Stage::Attack => {
+                    let total = frames(self.attack);
+                    if self.frames_in_stage < total {
+                        return Some(elapsed / total as f32);
+                    }
+                    self.stage = Stage::Decay;
+                }
+                Stage::Decay => {
+                    let total = frames(self.decay);
+                    if self.frames_in_stage < total {
+                        return Some(1.0 - (1.0 - self.sustain) * elapsed / total as f32);
+                    }
+                    self.stage = Stage::Sustain;
+                }
+                Stage::Sustain => return Some(self.sustain),
+                Stage::Release => {
+                    let total = frames(self.release);
+                    if self.frames_in_stage < total {
+                        return Some(self.release_from * (1.0 - elapsed / total as f32));
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+            // The stage changed, restart counting for the new one.
+            self.frames_in_stage = 0;
+        }
+    }
+}
+
+impl<I> Iterator for Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            self.level = self.next_level()?;
+            self.frames_in_stage += 1;
+        }
+
+        let value = self.input.next()?;
+        self.sample_in_frame = (self.sample_in_frame + 1) % self.input.channels().max(1);
+        Some(value.amplify(self.level))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for Adsr<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        // Depends on when the note is released.
+        None
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::*;
+    use crate::buffer::SamplesBuffer;
+
+    #[test]
+    fn envelope_stages() {
+        let source = SamplesBuffer::new(1, 10, vec![1.0f32; 100]);
+        let (mut env, handle) = adsr(
+            source,
+            Duration::from_millis(200),
+            Duration::from_millis(200),
+            0.5,
+            Duration::from_millis(400),
+        );
+
+        let attack: Vec<f32> = env.by_ref().take(2).collect();
+        assert_eq!(attack, vec![0.0, 0.5]);
+        let decay: Vec<f32> = env.by_ref().take(2).collect();
+        assert_eq!(decay, vec![1.0, 0.75]);
+        assert_eq!(env.next(), Some(0.5));
+        assert_eq!(env.next(), Some(0.5));
+
+        handle.note_off();
+        let release: Vec<f32> = env.collect();
+        assert_eq!(release.len(), 4);
+        for (value, expected) in release.iter().zip([0.5, 0.375, 0.25, 0.125]) {
+            assert_abs_diff_eq!(*value, expected);
+        }
+    }
+
+    #[test]
+    fn release_during_attack_starts_from_current_level() {
+        let source = SamplesBuffer::new(2, 10, vec![1.0f32; 100]);
+        let (mut env, handle) = adsr(
+            source,
+            Duration::from_millis(400),
+            Duration::ZERO,
+            1.0,
+            Duration::from_millis(200),
+        );
+
+        // Two stereo frames into the attack.
+        let attack: Vec<f32> = env.by_ref().take(4).collect();
+        assert_eq!(attack, vec![0.0, 0.0, 0.25, 0.25]);
+
+        handle.note_off();
+        let release: Vec<f32> = env.collect();
+        assert_eq!(release, vec![0.25, 0.25, 0.125, 0.125]);
+    }
+}
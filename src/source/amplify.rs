@@ -10,7 +10,12 @@ where
     I: Source,
     I::Item: Sample,
 {
-    Amplify { input, factor }
+    Amplify {
+        input,
+        factor,
+        target: factor,
+        step: 0.0,
+    }
 }
 
 /// Filter that modifies each sample by a given value.
@@ -18,6 +23,9 @@ where
 pub struct Amplify<I> {
     input: I,
     factor: f32,
+    // Factor a glide started by `set_factor_smoothed` ends at, and its change per sample.
+    target: f32,
+    step: f32,
 }
 
 impl<I> Amplify<I> {
@@ -25,6 +33,8 @@ impl<I> Amplify<I> {
     #[inline]
     pub fn set_factor(&mut self, factor: f32) {
         self.factor = factor;
+        self.target = factor;
+        self.step = 0.0;
     }
 
     /// Returns a reference to the inner source.
@@ -46,6 +56,31 @@ impl<I> Amplify<I> {
     }
 }
 
+impl<I> Amplify<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes the amplification factor gradually, sample by sample, over `duration`.
+    ///
+    /// Avoids the click of a sudden jump in volume. A glide to the factor already targeted
+    /// continues as it is, a glide to another factor starts from the factor reached so far.
+    #[inline]
+    pub fn set_factor_smoothed(&mut self, factor: f32, duration: Duration) {
+        if factor == self.target {
+            return;
+        }
+        let samples =
+            duration.as_secs_f32() * self.input.sample_rate() as f32 * self.input.channels() as f32;
+        if samples < 1.0 {
+            self.set_factor(factor);
+        } else {
+            self.target = factor;
+            self.step = (factor - self.factor) / samples;
+        }
+    }
+}
+
 impl<I> Iterator for Amplify<I>
 where
     I: Source,
@@ -55,6 +90,12 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
+        if self.factor != self.target {
+            self.factor += self.step;
+            if (self.target - self.factor) * self.step <= 0.0 {
+                self.factor = self.target;
+            }
+        }
         self.input.next().map(|value| value.amplify(self.factor))
     }
 
@@ -96,6 +137,11 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.input.try_seek(pos)
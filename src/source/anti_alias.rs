@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::biquad::{Biquad, BiquadState};
+use super::SeekError;
+
+/// Cutoff of the filter relative to the target rate, just below its Nyquist frequency.
+const CUTOFF: f32 = 0.45;
+
+/// Quality factors of the two stages of a fourth order Butterworth low-pass.
+const STAGE_Q: [f32; 2] = [0.541_196_1, 1.306_563];
+
+/// Low-pass filter applied before a source is downsampled, so that frequencies the target
+/// rate can not represent do not fold back into the audible range.
+///
+/// Sources whose rate does not exceed the target pass through untouched.
+#[derive(Clone, Debug)]
+pub struct AntiAlias<I> {
+    input: I,
+    target_rate: u32,
+    // Format the coefficients were computed for, checked at every frame boundary.
+    sample_rate: u32,
+    channels: u16,
+    // `None` while the source is not downsampled.
+    stages: Option<[Biquad; 2]>,
+    state: Vec<[BiquadState; 2]>,
+    channel: usize,
+}
+
+impl<I> AntiAlias<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Filters `input` for resampling to `target_rate`.
+    pub fn new(input: I, target_rate: u32) -> AntiAlias<I> {
+        let mut filter = AntiAlias {
+            input,
+            target_rate,
+            sample_rate: 0,
+            channels: 0,
+            stages: None,
+            state: Vec::new(),
+            channel: 0,
+        };
+        filter.update_format();
+        filter
+    }
+
+    /// Recomputes the filter if the format of the input changed.
+    fn update_format(&mut self) {
+        let (sample_rate, channels) = (self.input.sample_rate(), self.input.channels());
+        if (sample_rate, channels) == (self.sample_rate, self.channels) {
+            return;
+        }
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        self.stages = (sample_rate > self.target_rate).then(|| {
+            let cutoff = self.target_rate as f32 * CUTOFF;
+            STAGE_Q.map(|q| Biquad::low_pass(cutoff, sample_rate, q))
+        });
+        self.state = vec![[[0.0; 4]; 2]; channels.max(1) as usize];
+    }
+}
+
+impl<I> Iterator for AntiAlias<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update_format();
+        }
+
+        let value = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.state.len();
+        let Some(stages) = &self.stages else {
+            return Some(value);
+        };
+
+        let mut x = value.to_f32();
+        for (stage, state) in stages.iter().zip(&mut self.state[channel]) {
+            x = stage.process(x, state);
+        }
+        let filtered: <I::Item as CpalSample>::Float = CpalSample::from_sample(x);
+        Some(filtered.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for AntiAlias<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.state
+            .iter_mut()
+            .for_each(|state| *state = [[0.0; 4]; 2]);
+        self.channel = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `AutoFadeOut` object.
+pub fn auto_fade_out<I>(input: I, duration: Duration) -> AutoFadeOut<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    AutoFadeOut {
+        input,
+        duration,
+        frames_played: 0,
+        sample_in_frame: 0,
+        factor: 1.0,
+    }
+}
+
+/// Filter that fades out the end of a source of known length.
+#[derive(Clone, Debug)]
+pub struct AutoFadeOut<I> {
+    input: I,
+    duration: Duration,
+    frames_played: u64,
+    sample_in_frame: u16,
+    factor: f32,
+}
+
+impl<I> AutoFadeOut<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Gain of the frame about to be played.
+    fn next_factor(&self) -> f32 {
+        let Some(total) = self.input.total_duration() else {
+            return 1.0;
+        };
+        let rate = self.input.sample_rate() as f64;
+        let total_frames = (total.as_secs_f64() * rate) as u64;
+        let fade_frames = (self.duration.as_secs_f64() * rate) as u64;
+        // Frames left after this one, so the last frame is silent.
+        let remaining = total_frames.saturating_sub(self.frames_played + 1);
+        if fade_frames == 0 || remaining >= fade_frames {
+            1.0
+        } else {
+            remaining as f32 / fade_frames as f32
+        }
+    }
+}
+
+impl<I> Iterator for AutoFadeOut<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            self.factor = self.next_factor();
+        }
+
+        let value = self.input.next()?;
+        self.sample_in_frame += 1;
+        if self.sample_in_frame >= self.input.channels().max(1) {
+            self.sample_in_frame = 0;
+            self.frames_played += 1;
+        }
+        Some(value.amplify(self.factor))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for AutoFadeOut<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for AutoFadeOut<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        let pos = self
+            .input
+            .total_duration()
+            .map_or(pos, |total| pos.min(total));
+        self.frames_played = (pos.as_secs_f64() * self.input.sample_rate() as f64) as u64;
+        self.sample_in_frame = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn tail_fades_to_silence() {
+        // One second of stereo audio at 10 Hz.
+        let source = SamplesBuffer::new(2, 10, vec![1.0f32; 20]);
+        let output: Vec<f32> = source.auto_fade_out(Duration::from_millis(400)).collect();
+
+        assert_eq!(output[..12], [1.0; 12]);
+        for (frame, expected) in output[12..].chunks(2).zip([0.75, 0.5, 0.25, 0.0]) {
+            assert_abs_diff_eq!(frame[0], expected);
+            assert_abs_diff_eq!(frame[1], expected);
+        }
+    }
+
+    #[test]
+    fn unknown_length_is_unchanged() {
+        let faded: Vec<f32> = SineWave::new(440.0)
+            .auto_fade_out(Duration::from_secs(1))
+            .take(100)
+            .collect();
+        let plain: Vec<f32> = SineWave::new(440.0).take(100).collect();
+        assert_eq!(faded, plain);
+    }
+}
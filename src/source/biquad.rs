@@ -0,0 +1,143 @@
+//! Second order filter sections shared by the filters of this module.
+
+use std::f32::consts::PI;
+
+/// Normalized coefficients of one second order section.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// Last two inputs and outputs of one section for one channel.
+pub(crate) type BiquadState = [f32; 4];
+
+impl Biquad {
+    // Implemented following http://www.musicdsp.org/files/Audio-EQ-Cookbook.txt
+    pub(crate) fn low_pass(cutoff: f32, sample_rate: u32, q: f32) -> Biquad {
+        let (cos_w0, alpha) = Self::angles(cutoff, sample_rate, q);
+        Biquad::normalized(
+            [(1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0],
+            [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+        )
+    }
+
+    pub(crate) fn high_pass(cutoff: f32, sample_rate: u32, q: f32) -> Biquad {
+        let (cos_w0, alpha) = Self::angles(cutoff, sample_rate, q);
+        Biquad::normalized(
+            [(1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0],
+            [1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha],
+        )
+    }
+
+    pub(crate) fn peaking(freq: f32, sample_rate: u32, q: f32, gain_db: f32) -> Biquad {
+        let (cos_w0, alpha) = Self::angles(freq, sample_rate, q);
+        let a = 10f32.powf(gain_db / 40.0);
+        Biquad::normalized(
+            [1.0 + alpha * a, -2.0 * cos_w0, 1.0 - alpha * a],
+            [1.0 + alpha / a, -2.0 * cos_w0, 1.0 - alpha / a],
+        )
+    }
+
+    pub(crate) fn low_shelf(freq: f32, sample_rate: u32, q: f32, gain_db: f32) -> Biquad {
+        let (cos_w0, alpha) = Self::angles(freq, sample_rate, q);
+        let a = 10f32.powf(gain_db / 40.0);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+        Biquad::normalized(
+            [
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha),
+            ],
+            [
+                (a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                (a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha,
+            ],
+        )
+    }
+
+    pub(crate) fn high_shelf(freq: f32, sample_rate: u32, q: f32, gain_db: f32) -> Biquad {
+        let (cos_w0, alpha) = Self::angles(freq, sample_rate, q);
+        let a = 10f32.powf(gain_db / 40.0);
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+        Biquad::normalized(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - sqrt_a_alpha),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos_w0 + sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - sqrt_a_alpha,
+            ],
+        )
+    }
+
+    /// First stage of the K-weighting of ITU-R BS.1770, a high shelf modelling the head.
+    ///
+    /// The standard only gives coefficients for 48 kHz, these are derived for any rate the
+    /// way libebur128 does.
+    pub(crate) fn k_weighting_shelf(sample_rate: u32) -> Biquad {
+        let k = (PI * 1_681.974_5 / sample_rate as f32).tan();
+        let q = 0.707_175_24;
+        let vh = 10f32.powf(3.999_843_9 / 20.0);
+        let vb = vh.powf(0.499_666_78);
+        Biquad::normalized(
+            [
+                vh + vb * k / q + k * k,
+                2.0 * (k * k - vh),
+                vh - vb * k / q + k * k,
+            ],
+            [
+                1.0 + k / q + k * k,
+                2.0 * (k * k - 1.0),
+                1.0 - k / q + k * k,
+            ],
+        )
+    }
+
+    /// Second stage of the K-weighting of ITU-R BS.1770, a high-pass at 38 Hz.
+    pub(crate) fn k_weighting_high_pass(sample_rate: u32) -> Biquad {
+        let k = (PI * 38.135_47 / sample_rate as f32).tan();
+        let q = 0.500_327;
+        Biquad::normalized(
+            [1.0, -2.0, 1.0],
+            [
+                1.0 + k / q + k * k,
+                2.0 * (k * k - 1.0),
+                1.0 - k / q + k * k,
+            ],
+        )
+    }
+
+    /// Returns the cosine of the normalized frequency and the alpha of the cookbook.
+    fn angles(freq: f32, sample_rate: u32, q: f32) -> (f32, f32) {
+        let w0 = 2.0 * PI * freq / sample_rate as f32;
+        (w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    /// Divides the coefficients by `a0`.
+    fn normalized([b0, b1, b2]: [f32; 3], [a0, a1, a2]: [f32; 3]) -> Biquad {
+        Biquad {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Filters one sample, `state` holds the last inputs and outputs of its channel.
+    #[inline]
+    pub(crate) fn process(&self, x: f32, state: &mut BiquadState) -> f32 {
+        let [x1, x2, y1, y2] = *state;
+        let y = self.b0 * x + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+        *state = [x, x1, y, y1];
+        y
+    }
+}
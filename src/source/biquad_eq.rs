@@ -0,0 +1,341 @@
+use std::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::biquad::{Biquad, BiquadState};
+use super::SeekError;
+
+/// Internal function that builds a `BiquadEq` object.
+pub fn biquad<I>(
+    input: I,
+    filter_type: EqFilterType,
+    freq: f32,
+    q: f32,
+    gain_db: f32,
+) -> BiquadEq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let params = Arc::new(EqParams {
+        filter_type: AtomicU8::new(filter_type as u8),
+        freq: AtomicU32::new(freq.to_bits()),
+        q: AtomicU32::new(q.to_bits()),
+        gain_db: AtomicU32::new(gain_db.to_bits()),
+    });
+    BiquadEq {
+        input,
+        params,
+        current: None,
+        sample_rate: 0,
+        biquad: None,
+        state: Vec::new(),
+        channel: 0,
+    }
+}
+
+/// Shape of the frequency response of a [`BiquadEq`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqFilterType {
+    /// Boosts or cuts a band around the frequency, the q sets how narrow it is.
+    Peaking,
+    /// Boosts or cuts everything below the frequency.
+    LowShelf,
+    /// Boosts or cuts everything above the frequency.
+    HighShelf,
+}
+
+impl EqFilterType {
+    fn from_u8(value: u8) -> EqFilterType {
+        match value {
+            v if v == EqFilterType::LowShelf as u8 => EqFilterType::LowShelf,
+            v if v == EqFilterType::HighShelf as u8 => EqFilterType::HighShelf,
+            _ => EqFilterType::Peaking,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EqParams {
+    filter_type: AtomicU8,
+    freq: AtomicU32,
+    q: AtomicU32,
+    gain_db: AtomicU32,
+}
+
+impl EqParams {
+    fn band(&self) -> Band {
+        Band {
+            filter_type: EqFilterType::from_u8(self.filter_type.load(Ordering::Relaxed)),
+            freq: f32::from_bits(self.freq.load(Ordering::Relaxed)),
+            q: f32::from_bits(self.q.load(Ordering::Relaxed)),
+            gain_db: f32::from_bits(self.gain_db.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Parameters of a band, compared to find out if the coefficients are stale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Band {
+    filter_type: EqFilterType,
+    freq: f32,
+    q: f32,
+    gain_db: f32,
+}
+
+/// Changes the parameters of a [`BiquadEq`] while it plays, see [`BiquadEq::handle`].
+///
+/// Changes are picked up at the start of the next frame.
+#[derive(Clone, Debug)]
+pub struct BiquadHandle(Arc<EqParams>);
+
+impl BiquadHandle {
+    /// Changes the shape of the frequency response.
+    pub fn set_filter_type(&self, filter_type: EqFilterType) {
+        self.0
+            .filter_type
+            .store(filter_type as u8, Ordering::Relaxed);
+    }
+
+    /// Returns the shape of the frequency response.
+    pub fn filter_type(&self) -> EqFilterType {
+        self.0.band().filter_type
+    }
+
+    /// Changes the center or corner frequency in Hz.
+    pub fn set_frequency(&self, freq: f32) {
+        self.0.freq.store(freq.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the center or corner frequency in Hz.
+    pub fn frequency(&self) -> f32 {
+        self.0.band().freq
+    }
+
+    /// Changes the q.
+    pub fn set_q(&self, q: f32) {
+        self.0.q.store(q.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the q.
+    pub fn q(&self) -> f32 {
+        self.0.band().q
+    }
+
+    /// Changes the gain in dB, negative values cut.
+    pub fn set_gain_db(&self, gain_db: f32) {
+        self.0.gain_db.store(gain_db.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the gain in dB.
+    pub fn gain_db(&self) -> f32 {
+        self.0.band().gain_db
+    }
+}
+
+/// One band of a parametric equalizer, a second order filter with a separate state for each
+/// channel.
+#[derive(Clone, Debug)]
+pub struct BiquadEq<I> {
+    input: I,
+    params: Arc<EqParams>,
+    // Band and rate the coefficients were computed for, checked at every frame boundary.
+    current: Option<Band>,
+    sample_rate: u32,
+    biquad: Option<Biquad>,
+    state: Vec<BiquadState>,
+    channel: usize,
+}
+
+impl<I> BiquadEq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the parameters from another thread.
+    pub fn handle(&self) -> BiquadHandle {
+        BiquadHandle(self.params.clone())
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Recomputes the coefficients if a parameter or the format of the input changed.
+    fn update(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        if self.state.len() != channels {
+            self.state = vec![[0.0; 4]; channels];
+        }
+
+        let band = self.params.band();
+        let sample_rate = self.input.sample_rate();
+        if Some(band) == self.current && sample_rate == self.sample_rate {
+            return;
+        }
+        self.current = Some(band);
+        self.sample_rate = sample_rate;
+        if sample_rate == 0 {
+            self.biquad = None;
+            return;
+        }
+        // The filter is only stable below the Nyquist frequency.
+        let freq = band.freq.clamp(1.0, sample_rate as f32 / 2.0 * 0.99);
+        let q = band.q.max(0.01);
+        self.biquad = Some(match band.filter_type {
+            EqFilterType::Peaking => Biquad::peaking(freq, sample_rate, q, band.gain_db),
+            EqFilterType::LowShelf => Biquad::low_shelf(freq, sample_rate, q, band.gain_db),
+            EqFilterType::HighShelf => Biquad::high_shelf(freq, sample_rate, q, band.gain_db),
+        });
+    }
+}
+
+impl<I> Iterator for BiquadEq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let value = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.state.len();
+        let Some(biquad) = &self.biquad else {
+            return Some(value);
+        };
+
+        let filtered = biquad.process(value.to_f32(), &mut self.state[channel]);
+        let filtered: <I::Item as CpalSample>::Float = CpalSample::from_sample(filtered);
+        Some(filtered.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for BiquadEq<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for BiquadEq<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.state.iter_mut().for_each(|state| *state = [0.0; 4]);
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EqFilterType;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak(samples: impl Iterator<Item = f32>) -> f32 {
+        samples.fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    fn gain_at(freq: f32, eq: impl Fn(SineWave) -> Box<dyn Iterator<Item = f32>>) -> f32 {
+        peak(eq(SineWave::new(freq)).skip(9600).take(9600))
+    }
+
+    #[test]
+    fn peaking_boosts_only_its_band() {
+        let eq = |s: SineWave| {
+            Box::new(s.biquad(EqFilterType::Peaking, 1000.0, 2.0, 6.0))
+                as Box<dyn Iterator<Item = _>>
+        };
+        assert!((gain_at(1000.0, eq) - 1.995).abs() < 0.02);
+        assert!((gain_at(100.0, eq) - 1.0).abs() < 0.02);
+        assert!((gain_at(10000.0, eq) - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn shelves_are_chainable() {
+        let eq = |s: SineWave| {
+            Box::new(
+                s.biquad(EqFilterType::LowShelf, 200.0, 0.707, -12.0)
+                    .biquad(EqFilterType::HighShelf, 5000.0, 0.707, 6.0),
+            ) as Box<dyn Iterator<Item = _>>
+        };
+        assert!((gain_at(30.0, eq) - 0.251).abs() < 0.02);
+        assert!((gain_at(1000.0, eq) - 1.0).abs() < 0.1);
+        assert!((gain_at(18000.0, eq) - 1.995).abs() < 0.05);
+    }
+
+    #[test]
+    fn parameters_change_while_playing() {
+        let mut eq = SineWave::new(1000.0).biquad(EqFilterType::Peaking, 1000.0, 1.0, 0.0);
+        let handle = eq.handle();
+        assert!((peak(eq.by_ref().skip(4800).take(4800)) - 1.0).abs() < 0.01);
+
+        handle.set_gain_db(-20.0);
+        assert_eq!(handle.gain_db(), -20.0);
+        assert!((peak(eq.by_ref().skip(4800).take(4800)) - 0.1).abs() < 0.01);
+
+        handle.set_filter_type(EqFilterType::HighShelf);
+        handle.set_frequency(10000.0);
+        assert_eq!(handle.filter_type(), EqFilterType::HighShelf);
+        assert!((peak(eq.skip(4800).take(4800)) - 1.0).abs() < 0.02);
+    }
+}
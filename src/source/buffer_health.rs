@@ -0,0 +1,364 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Most samples the worker reads from the source before handing them over.
+const CHUNK_LEN: usize = 1024;
+
+/// Internal function that builds a `Prebuffered` object.
+pub fn with_buffer_health<I>(mut input: I, target: Duration) -> (Prebuffered<I::Item>, BufferHealth)
+where
+    I: Source + Send + 'static,
+    I::Item: Sample + Send,
+{
+    let channels = input.channels().max(1);
+    let sample_rate = input.sample_rate().max(1);
+    let total_duration = input.total_duration();
+    let is_infinite = input.is_infinite();
+    let frame = channels as usize;
+    let capacity = (target.as_secs_f64() * sample_rate as f64) as usize * frame;
+    // Chunks hold at least two whole frames, at least eight chunks fit in the buffer.
+    let chunk_len = (capacity / 8).clamp(2 * frame, CHUNK_LEN.max(2 * frame)) / frame * frame;
+    let chunks = (capacity / chunk_len).max(2);
+
+    // Read here so that an empty source plays as empty, rather than as silence until the
+    // worker finds out.
+    let first = input.next();
+    let level = Arc::new(Level {
+        buffered: AtomicUsize::new(usize::from(first.is_some())),
+        ended: AtomicBool::new(first.is_none()),
+    });
+
+    // Every chunk is allocated here. The playing side hands each chunk back once it was
+    // played, and the worker only reads ahead while it holds an empty one.
+    let (filled_tx, filled_rx) = sync_channel(chunks);
+    let (empty_tx, empty_rx) = sync_channel(chunks);
+    for _ in 0..chunks {
+        empty_tx
+            .send(Vec::with_capacity(chunk_len))
+            .expect("the receiver is alive");
+    }
+
+    if first.is_some() {
+        let level = level.clone();
+        thread::Builder::new()
+            .name("rodio prebuffer".to_owned())
+            .spawn(move || fill(input, chunk_len, &empty_rx, &filled_tx, &level))
+            .expect("failed to spawn the prebuffer thread");
+    }
+
+    let health = BufferHealth {
+        level: level.clone(),
+        samples_per_second: sample_rate as f64 * channels as f64,
+        target,
+    };
+    let source = Prebuffered {
+        level,
+        filled: filled_rx,
+        empty: empty_tx,
+        first,
+        current: Vec::new(),
+        position: 0,
+        silence_left: 0,
+        channels,
+        sample_rate,
+        total_duration,
+        is_infinite,
+    };
+    (source, health)
+}
+
+/// Fill level of the buffer, readable without knowing the sample type.
+struct Level {
+    buffered: AtomicUsize,
+    // The source returned `None`, nothing is added anymore.
+    ended: AtomicBool,
+}
+
+/// Reads the source ahead of playback into the empty chunks it gets back, until the source
+/// ends or the playing side is dropped. The first chunk is one sample short, it completes the
+/// first frame with the sample that was read already.
+fn fill<I>(
+    mut input: I,
+    chunk_len: usize,
+    empty: &Receiver<Vec<I::Item>>,
+    filled: &SyncSender<Vec<I::Item>>,
+    level: &Level,
+) where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut wanted = chunk_len - 1;
+    // Waits for a chunk to be played, or returns once the playing side is gone.
+    while let Ok(mut chunk) = empty.recv() {
+        chunk.clear();
+        chunk.extend(input.by_ref().take(wanted));
+        let len = chunk.len();
+        // Counted before the samples can be played, so the level never drops below zero.
+        level.buffered.fetch_add(len, Ordering::Relaxed);
+        // There is room for every chunk, this never blocks.
+        if filled.send(chunk).is_err() {
+            return;
+        }
+        if len < wanted {
+            level.ended.store(true, Ordering::Release);
+            return;
+        }
+        wanted = chunk_len;
+    }
+}
+
+/// Reports how much of a [`Prebuffered`] source is buffered ahead of playback.
+///
+/// The worker thread fills the buffer up to the target given to
+/// [`Source::with_buffer_health`] and then pauses until some of it was played, so a healthy
+/// stream stays close to the target. When the source can not keep up, for example because a
+/// download stalls, the buffered duration drops. Below the low-water mark of a quarter of the
+/// target [`is_low`](BufferHealth::is_low) returns true, which is the moment to show a
+/// buffering indicator.
+#[derive(Clone)]
+pub struct BufferHealth {
+    level: Arc<Level>,
+    samples_per_second: f64,
+    target: Duration,
+}
+
+impl BufferHealth {
+    /// Returns the duration of audio currently buffered ahead of playback.
+    pub fn buffered_duration(&self) -> Duration {
+        let samples = self.level.buffered.load(Ordering::Relaxed);
+        Duration::from_secs_f64(samples as f64 / self.samples_per_second)
+    }
+
+    /// Returns the duration the buffer is filled up to.
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+
+    /// Returns true while less than a quarter of the target is buffered and the source has
+    /// not been read completely.
+    pub fn is_low(&self) -> bool {
+        !self.is_complete() && self.buffered_duration() < self.target / 4
+    }
+
+    /// Returns true once the whole source was read, the rest plays from the buffer.
+    pub fn is_complete(&self) -> bool {
+        self.level.ended.load(Ordering::Relaxed)
+    }
+}
+
+/// Plays a source that is read ahead on a worker thread, see [`Source::with_buffer_health`].
+///
+/// Playing never waits for the worker. The samples are handed over in chunks of whole frames
+/// and the worker is woken at most once per chunk played. When the buffer ran dry, whole
+/// frames of silence are played until the source catches up.
+pub struct Prebuffered<S> {
+    level: Arc<Level>,
+    filled: Receiver<Vec<S>>,
+    empty: SyncSender<Vec<S>>,
+    // Sample read to find out whether the source is empty, played first.
+    first: Option<S>,
+    // Chunk being played.
+    current: Vec<S>,
+    position: usize,
+    // Rest of a frame of silence played while starved.
+    silence_left: u16,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+    is_infinite: bool,
+}
+
+impl<S> Iterator for Prebuffered<S>
+where
+    S: Sample,
+{
+    type Item = S;
+
+    #[inline]
+    fn next(&mut self) -> Option<S> {
+        if let Some(value) = self.first.take() {
+            self.level.buffered.fetch_sub(1, Ordering::Relaxed);
+            return Some(value);
+        }
+        if self.silence_left > 0 {
+            self.silence_left -= 1;
+            return Some(S::zero_value());
+        }
+
+        if self.position == self.current.len() {
+            // Checked before looking for a chunk, the worker may send the last one in between.
+            let ended = self.level.ended.load(Ordering::Acquire);
+            match self.filled.try_recv() {
+                Ok(chunk) => {
+                    let played = std::mem::replace(&mut self.current, chunk);
+                    // Never blocks, there is room for every chunk. Nothing was allocated for
+                    // the initial empty chunk, it is not handed to the worker.
+                    if played.capacity() > 0 {
+                        let _ = self.empty.try_send(played);
+                    }
+                    self.position = 0;
+                }
+                Err(_) if ended => return None,
+                // Starved, silence keeps the output running until the source catches up.
+                Err(_) => {
+                    self.silence_left = self.channels - 1;
+                    return Some(S::zero_value());
+                }
+            }
+            if self.current.is_empty() {
+                return None;
+            }
+        }
+
+        let value = self.current[self.position];
+        self.position += 1;
+        self.level.buffered.fetch_sub(1, Ordering::Relaxed);
+        Some(value)
+    }
+}
+
+impl<S> Source for Prebuffered<S>
+where
+    S: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.is_infinite
+    }
+
+    /// Seeking is not supported, the worker thread owns the source.
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{BufferHealth, Source};
+
+    fn wait_until(health: &BufferHealth, condition: impl Fn(&BufferHealth) -> bool) {
+        let start = Instant::now();
+        while !condition(health) {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn health_drops_while_upstream_stalls() {
+        let stalled = Arc::new(AtomicBool::new(false));
+        let upstream = {
+            let stalled = stalled.clone();
+            // One second at 10 kHz, the reader blocks while `stalled` is set.
+            SamplesBuffer::new(1, 10000, vec![0.5f32; 10000]).periodic_access(
+                Duration::from_micros(100),
+                move |_| {
+                    while stalled.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                },
+            )
+        };
+        let (mut source, health) = upstream.with_buffer_health(Duration::from_millis(200));
+
+        wait_until(&health, |health| {
+            health.buffered_duration() >= Duration::from_millis(199)
+        });
+        assert!(!health.is_low());
+
+        stalled.store(true, Ordering::Relaxed);
+        assert!(source.by_ref().take(1800).all(|value| value == 0.5));
+        assert!(health.buffered_duration() <= Duration::from_millis(21));
+        assert!(health.is_low());
+        // Once the buffer ran dry silence is played instead of blocking.
+        assert!(source.by_ref().skip(200).take(10).all(|value| value == 0.0));
+
+        stalled.store(false, Ordering::Relaxed);
+        wait_until(&health, |health| !health.is_low());
+        // The rest of the second, nothing was lost to the silence played during the stall.
+        assert_eq!(source.filter(|&value| value == 0.5).count(), 10000 - 2000);
+        assert!(health.is_complete());
+    }
+
+    #[test]
+    fn silence_is_played_in_whole_frames() {
+        let stalled = Arc::new(AtomicBool::new(false));
+        let upstream = {
+            let stalled = stalled.clone();
+            let samples = [0.25f32, 0.75].repeat(10000);
+            SamplesBuffer::new(2, 10000, samples).periodic_access(
+                Duration::from_micros(100),
+                move |_| {
+                    while stalled.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                },
+            )
+        };
+        let (mut source, health) = upstream.with_buffer_health(Duration::from_millis(100));
+        wait_until(&health, |health| {
+            health.buffered_duration() >= Duration::from_millis(99)
+        });
+
+        stalled.store(true, Ordering::Relaxed);
+        let buffered: Vec<f32> = source.by_ref().take_while(|&value| value != 0.0).collect();
+        assert!(buffered.chunks(2).all(|frame| frame == [0.25, 0.75]));
+
+        // The source catches up halfway through a frame of silence, the frame is finished.
+        stalled.store(false, Ordering::Relaxed);
+        wait_until(&health, |health| !health.is_low());
+        let mut silence = 1;
+        while source.next() == Some(0.0) {
+            silence += 1;
+        }
+        // The sample that ended the silence was the left one.
+        assert_eq!(silence % 2, 0);
+        assert_eq!(
+            source.by_ref().take(3).collect::<Vec<_>>(),
+            [0.75, 0.25, 0.75]
+        );
+    }
+
+    #[test]
+    fn forwards_is_infinite() {
+        let (source, _) =
+            crate::source::SineWave::new(440.0).with_buffer_health(Duration::from_millis(10));
+        assert!(source.is_infinite());
+    }
+}
@@ -15,12 +15,14 @@ where
     I::Item: Sample,
 {
     let total_duration = input.total_duration();
+    let is_infinite = input.is_infinite();
     let first_frame = extract(input);
 
     Buffered {
         current_frame: first_frame,
         position_in_frame: 0,
         total_duration,
+        is_infinite,
     }
 }
 
@@ -38,6 +40,9 @@ where
 
     /// Obtained once at creation and never modified again.
     total_duration: Option<Duration>,
+
+    /// Obtained once at creation and never modified again.
+    is_infinite: bool,
 }
 
 enum Frame<I>
@@ -242,6 +247,11 @@ where
         self.total_duration
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.is_infinite
+    }
+
     /// Can not support seek, in the end state we lose the underlying source
     /// which makes seeking back impossible.
     #[inline]
@@ -263,6 +273,7 @@ where
             current_frame: self.current_frame.clone(),
             position_in_frame: self.position_in_frame,
             total_duration: self.total_duration,
+            is_infinite: self.is_infinite,
         }
     }
 }
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Stored instead of a position while nothing clipped.
+const NO_CLIP: u64 = u64::MAX;
+
+/// Internal function that builds a `ClipDetect` object.
+pub fn clip_detect<I>(input: I) -> (ClipDetect<I>, ClipHandle)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let state = Arc::new(ClipState {
+        count: AtomicU64::new(0),
+        first_nanos: AtomicU64::new(NO_CLIP),
+    });
+    // The largest positive sample, which is a little below 1.0 for integer samples.
+    let one: <I::Item as CpalSample>::Float = CpalSample::from_sample(1.0f32);
+    let full_scale = one.to_sample::<I::Item>().to_f32();
+    let source = ClipDetect {
+        input,
+        state: state.clone(),
+        full_scale,
+        clamp: false,
+        offset: Duration::ZERO,
+        frames: 0,
+        rate: 0,
+        sample_in_frame: 0,
+    };
+    (source, ClipHandle(state))
+}
+
+#[derive(Debug)]
+struct ClipState {
+    count: AtomicU64,
+    // Position of the first clipped sample in nanoseconds, `NO_CLIP` until then.
+    first_nanos: AtomicU64,
+}
+
+/// Handle to read the clipping found by a [`ClipDetect`] from another thread.
+#[derive(Clone, Debug)]
+pub struct ClipHandle(Arc<ClipState>);
+
+impl ClipHandle {
+    /// Returns the number of samples that reached or exceeded full scale so far.
+    pub fn clipped_samples(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    /// Returns the position of the first clipped sample, if any sample clipped yet.
+    pub fn first_clip(&self) -> Option<Duration> {
+        match self.0.first_nanos.load(Ordering::Relaxed) {
+            NO_CLIP => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    /// Forgets the clipping found so far.
+    pub fn reset(&self) {
+        self.0.count.store(0, Ordering::Relaxed);
+        self.0.first_nanos.store(NO_CLIP, Ordering::Relaxed);
+    }
+}
+
+/// Passes a source through while counting the samples that clip.
+#[derive(Debug)]
+pub struct ClipDetect<I> {
+    input: I,
+    state: Arc<ClipState>,
+    // Samples at least this loud clip.
+    full_scale: f32,
+    clamp: bool,
+    // Time played up to the last sample rate change, plus frames played at `rate` since.
+    offset: Duration,
+    frames: u64,
+    rate: u32,
+    sample_in_frame: u16,
+}
+
+impl<I> ClipDetect<I> {
+    /// Also limits the samples beyond full scale to full scale, instead of passing them
+    /// through unchanged. This only affects floating point samples.
+    #[inline]
+    pub fn set_clamp(&mut self, clamp: bool) {
+        self.clamp = clamp;
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Returns the position of the current frame.
+    #[inline]
+    fn elapsed(&self) -> Duration {
+        if self.frames == 0 {
+            self.offset
+        } else {
+            self.offset + Duration::from_secs_f64(self.frames as f64 / self.rate as f64)
+        }
+    }
+}
+
+impl<I> Iterator for ClipDetect<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            let rate = self.input.sample_rate();
+            if rate != self.rate {
+                self.offset = self.elapsed();
+                self.frames = 0;
+                self.rate = rate;
+            }
+        }
+
+        let mut value = self.input.next()?;
+        let level = value.to_f32().abs();
+        if level >= self.full_scale {
+            if self.state.count.fetch_add(1, Ordering::Relaxed) == 0 {
+                let nanos = self.elapsed().as_nanos() as u64;
+                self.state.first_nanos.store(nanos, Ordering::Relaxed);
+            }
+            if self.clamp && level > 1.0 {
+                value = value.amplify(1.0 / level);
+            }
+        }
+
+        self.sample_in_frame += 1;
+        if self.sample_in_frame >= self.input.channels().max(1) {
+            self.sample_in_frame = 0;
+            self.frames += 1;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for ClipDetect<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.offset = pos;
+        self.frames = 0;
+        self.sample_in_frame = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn counts_clipped_samples() {
+        // Stereo at 1 kHz, the first clip is in the third frame.
+        let input = vec![0.5f32, -0.5, 0.9, 0.2, 1.5, 0.0, -1.0, 0.3, 0.1, -2.0];
+        let (source, handle) = SamplesBuffer::new(2, 1000, input.clone()).clip_detect();
+        assert_eq!(handle.first_clip(), None);
+
+        let output: Vec<f32> = source.collect();
+        assert_eq!(output, input);
+        assert_eq!(handle.clipped_samples(), 3);
+        assert_eq!(handle.first_clip(), Some(Duration::from_millis(2)));
+
+        handle.reset();
+        assert_eq!(handle.clipped_samples(), 0);
+        assert_eq!(handle.first_clip(), None);
+    }
+
+    #[test]
+    fn clamps_when_asked() {
+        let (mut source, handle) =
+            SamplesBuffer::new(1, 1000, vec![1.5f32, -0.5, -3.0]).clip_detect();
+        source.set_clamp(true);
+        let output: Vec<f32> = source.collect();
+        assert_eq!(output, vec![1.0, -0.5, -1.0]);
+        assert_eq!(handle.clipped_samples(), 2);
+    }
+
+    #[test]
+    fn full_scale_boundary() {
+        let below = 1.0 - f32::EPSILON;
+        let input = vec![below, -below, 1.0, -1.0];
+        let (source, handle) = SamplesBuffer::new(1, 1000, input).clip_detect();
+        source.for_each(drop);
+        assert_eq!(handle.clipped_samples(), 2);
+
+        // As loud as the largest positive integer sample is full scale.
+        let input = vec![i16::MAX - 1, i16::MIN + 2, i16::MAX, i16::MIN + 1, i16::MIN];
+        let (source, handle) = SamplesBuffer::new(1, 1000, input).clip_detect();
+        source.for_each(drop);
+        assert_eq!(handle.clipped_samples(), 3);
+    }
+}
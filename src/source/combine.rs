@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::{SeekError, UniformSourceIterator};
+
+/// Builds a stereo source from two mono sources, one per channel.
+///
+/// If the two sources have different sample rates both are resampled to the higher one. When
+/// one source ends before the other its channel is filled with silence until both have ended.
+/// Sources with more than one channel only contribute their first channel.
+pub fn combine_stereo<L, R>(left: L, right: R) -> CombineStereo<L, R>
+where
+    L: Source,
+    L::Item: Sample,
+    R: Source<Item = L::Item>,
+{
+    let sample_rate = left.sample_rate().max(right.sample_rate());
+    CombineStereo {
+        left: UniformSourceIterator::new(left, 1, sample_rate),
+        right: UniformSourceIterator::new(right, 1, sample_rate),
+        sample_rate,
+        pending_right: None,
+    }
+}
+
+/// A stereo source built from two mono sources with [`combine_stereo`].
+#[derive(Clone)]
+pub struct CombineStereo<L, R>
+where
+    L: Source,
+    L::Item: Sample,
+    R: Source<Item = L::Item>,
+{
+    left: UniformSourceIterator<L, L::Item>,
+    right: UniformSourceIterator<R, L::Item>,
+    sample_rate: u32,
+    // Right sample of the frame whose left sample was just returned.
+    pending_right: Option<L::Item>,
+}
+
+impl<L, R> Iterator for CombineStereo<L, R>
+where
+    L: Source,
+    L::Item: Sample,
+    R: Source<Item = L::Item>,
+{
+    type Item = L::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<L::Item> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+
+        let (left, right) = match (self.left.next(), self.right.next()) {
+            (None, None) => return None,
+            (left, right) => (left, right),
+        };
+        self.pending_right = Some(right.unwrap_or_else(L::Item::zero_value));
+        Some(left.unwrap_or_else(L::Item::zero_value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending_right.is_some() as usize;
+        let frames = self.left.size_hint().0.max(self.right.size_hint().0);
+        (pending + frames * 2, None)
+    }
+}
+
+impl<L, R> Source for CombineStereo<L, R>
+where
+    L: Source,
+    L::Item: Sample,
+    R: Source<Item = L::Item>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let left = self.left.total_duration()?;
+        let right = self.right.total_duration()?;
+        Some(left.max(right))
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.left.is_infinite() || self.right.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.left.try_seek(pos)?;
+        self.right.try_seek(pos)?;
+        self.pending_right = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{combine_stereo, Source};
+
+    #[test]
+    fn interleaves_both_channels() {
+        let left = SamplesBuffer::new(1, 48000, vec![1i16, 2, 3]);
+        let right = SamplesBuffer::new(1, 48000, vec![-1i16, -2]);
+        let stereo = combine_stereo(left, right);
+        assert_eq!(stereo.channels(), 2);
+        assert_eq!(stereo.sample_rate(), 48000);
+
+        let output: Vec<i16> = stereo.collect();
+        assert_eq!(output, vec![1, -1, 2, -2, 3, 0]);
+    }
+
+    #[test]
+    fn resamples_to_common_rate() {
+        let left = SamplesBuffer::new(1, 2000, vec![1.0f32; 4]);
+        let right = SamplesBuffer::new(1, 1000, vec![0.5f32; 2]);
+        let stereo = combine_stereo(left, right);
+        assert_eq!(stereo.sample_rate(), 2000);
+
+        let output: Vec<f32> = stereo.collect();
+        assert_eq!(output.len(), 8);
+        assert!(output.chunks(2).all(|frame| frame[0] == 1.0));
+        assert!(output.chunks(2).take(3).all(|frame| frame[1] == 0.5));
+    }
+}
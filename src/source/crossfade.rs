@@ -1,7 +1,9 @@
 use std::time::Duration;
+use std::{error, fmt};
 
 use cpal::FromSample;
 
+use super::fadeable::{self, FadeCurve, FadeDirection, Fadeable};
 use crate::source::{FadeIn, Mix, TakeDuration};
 use crate::{Sample, Source};
 
@@ -34,6 +36,161 @@ where
 /// covered.
 pub type Crossfade<I1, I2> = Mix<TakeDuration<I1>, FadeIn<TakeDuration<I2>>>;
 
+/// Internal function that builds a `CrossfadeInto` object.
+pub fn crossfade_into<A, B>(
+    from: A,
+    into: B,
+    duration: Duration,
+) -> Result<CrossfadeInto<A, B>, FormatMismatch>
+where
+    A: Source,
+    B: Source<Item = A::Item>,
+    A::Item: Sample,
+{
+    let format = (from.channels(), from.sample_rate());
+    if format != (into.channels(), into.sample_rate()) {
+        return Err(FormatMismatch {
+            from: format,
+            into: (into.channels(), into.sample_rate()),
+        });
+    }
+
+    let (from, fade_out) = fadeable::fadeable(from, duration, FadeCurve::Linear);
+    fade_out.change_direction(FadeDirection::Out);
+    let (into, fade_in) = fadeable::fadeable(into, duration, FadeCurve::Linear);
+    fade_in.change_direction(FadeDirection::In);
+    let (channels, sample_rate) = format;
+    let frames = (duration.as_secs_f64() * sample_rate as f64).ceil() as u64;
+    Ok(CrossfadeInto {
+        from: Some(from),
+        into,
+        overlap: frames * channels as u64,
+    })
+}
+
+/// The sources given to [`Source::crossfade_into`] differ in channel count or sample rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatMismatch {
+    /// Channel count and sample rate of the source fading out.
+    pub from: (u16, u32),
+    /// Channel count and sample rate of the source fading in.
+    pub into: (u16, u32),
+}
+
+impl fmt::Display for FormatMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can not crossfade from {} channels at {} Hz into {} channels at {} Hz",
+            self.from.0, self.from.1, self.into.0, self.into.1
+        )
+    }
+}
+
+impl error::Error for FormatMismatch {}
+
+/// Fades one sound out while fading the next one in, then plays on with the next one.
+#[derive(Clone, Debug)]
+pub struct CrossfadeInto<A, B> {
+    // Dropped once it faded out.
+    from: Option<Fadeable<A>>,
+    into: Fadeable<B>,
+    // Samples left until `from` is silent.
+    overlap: u64,
+}
+
+impl<A, B> CrossfadeInto<A, B>
+where
+    A: Source,
+    A::Item: Sample,
+    B: Source<Item = A::Item>,
+{
+    /// Returns true while both sounds are playing.
+    #[inline]
+    pub fn is_crossfading(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// Returns a reference to the sound fading in.
+    #[inline]
+    pub fn inner(&self) -> &B {
+        self.into.inner()
+    }
+
+    /// Returns a mutable reference to the sound fading in.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut B {
+        self.into.inner_mut()
+    }
+}
+
+impl<A, B> Iterator for CrossfadeInto<A, B>
+where
+    A: Source,
+    A::Item: Sample,
+    B: Source<Item = A::Item>,
+{
+    type Item = A::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<A::Item> {
+        let Some(from) = &mut self.from else {
+            return self.into.next();
+        };
+        if self.overlap == 0 {
+            self.from = None;
+            return self.into.next();
+        }
+        self.overlap -= 1;
+
+        match (from.next(), self.into.next()) {
+            (Some(fading_out), Some(fading_in)) => Some(fading_out.saturating_add(fading_in)),
+            (fading_out, None) => fading_out,
+            (None, fading_in) => {
+                self.from = None;
+                fading_in
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.into.size_hint()
+    }
+}
+
+impl<A, B> Source for CrossfadeInto<A, B>
+where
+    A: Source,
+    A::Item: Sample,
+    B: Source<Item = A::Item>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.into.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.into.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.into.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.into.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.into.is_infinite()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,6 +217,23 @@ mod tests {
         assert_eq!(mixed.next(), None);
     }
 
+    #[test]
+    fn test_crossfade_into() {
+        let from = SamplesBuffer::new(1, 4, vec![1.0f32; 8]);
+        let into = SamplesBuffer::new(1, 4, vec![2.0f32; 6]);
+        let mut mixed = from.crossfade_into(into, Duration::from_secs(1)).unwrap();
+        assert!(mixed.is_crossfading());
+        let output: Vec<f32> = mixed.by_ref().collect();
+        assert_eq!(output, vec![1.0, 1.25, 1.5, 1.75, 2.0, 2.0]);
+        assert!(!mixed.is_crossfading());
+
+        let stereo = SamplesBuffer::new(2, 4, vec![2.0f32; 4]);
+        let error = dummysource(4)
+            .crossfade_into(stereo, Duration::from_secs(1))
+            .unwrap_err();
+        assert_eq!(error.into, (2, 4));
+    }
+
     #[test]
     fn test_crossfade() {
         let source1 = dummysource(10);
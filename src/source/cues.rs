@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver};
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `CuedSource` object.
+pub fn with_cues<I, C>(input: I, cues: Vec<(Duration, C)>) -> (CuedSource<I, C>, Receiver<C>)
+where
+    I: Source,
+    I::Item: Sample,
+    C: Clone + Send + 'static,
+{
+    let (sender, receiver) = channel();
+    let source = cues_to(input, cues, move |cue| {
+        // Nobody listening is not a reason to stop playing.
+        let _ = sender.send(cue);
+    });
+    (source, receiver)
+}
+
+/// Builds a `CuedSource` that hands its cues to `send` instead of a channel.
+pub(crate) fn cues_to<I, C, F>(input: I, mut cues: Vec<(Duration, C)>, send: F) -> CuedSource<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    F: Fn(C) + Send + 'static,
+{
+    // Stable, so cues at the same time fire in the order they were given.
+    cues.sort_by_key(|(at, _)| *at);
+    CuedSource {
+        input,
+        cues,
+        next_cue: 0,
+        send: Box::new(send),
+        offset: 0.0,
+        frames: 0,
+        rate: 0,
+        sample_in_frame: 0,
+    }
+}
+
+/// Sends cues through a channel when playback reaches their timestamps.
+pub struct CuedSource<I, C> {
+    input: I,
+    // Sorted by timestamp.
+    cues: Vec<(Duration, C)>,
+    next_cue: usize,
+    send: Box<dyn Fn(C) + Send>,
+    // Seconds played up to the last sample rate change, plus frames played at `rate` since.
+    offset: f64,
+    frames: u64,
+    rate: u32,
+    sample_in_frame: u16,
+}
+
+impl<I, C> CuedSource<I, C> {
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, C> CuedSource<I, C>
+where
+    C: Clone,
+{
+    /// Returns the seconds played up to the start of the current frame.
+    #[inline]
+    fn elapsed(&self) -> f64 {
+        if self.frames == 0 {
+            self.offset
+        } else {
+            self.offset + self.frames as f64 / self.rate as f64
+        }
+    }
+
+    /// Sends all cues up to the current position that were not sent yet.
+    #[inline]
+    fn fire_due_cues(&mut self) {
+        let elapsed = self.elapsed();
+        while let Some((at, cue)) = self.cues.get(self.next_cue) {
+            if at.as_secs_f64() > elapsed {
+                break;
+            }
+            (self.send)(cue.clone());
+            self.next_cue += 1;
+        }
+    }
+}
+
+impl<I, C> Iterator for CuedSource<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    C: Clone,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        // Pulled first, so the cues of a frame follow whatever the input does when it starts.
+        let value = self.input.next();
+        if self.sample_in_frame == 0 {
+            self.fire_due_cues();
+        }
+        let value = value?;
+        self.sample_in_frame += 1;
+        if self.sample_in_frame >= self.input.channels().max(1) {
+            self.sample_in_frame = 0;
+            let rate = self.input.sample_rate();
+            if rate != self.rate {
+                self.offset = self.elapsed();
+                self.frames = 0;
+                self.rate = rate;
+            }
+            self.frames += 1;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I, C> Source for CuedSource<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    C: Clone,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    /// Cues at or after `pos` fire again when playback reaches them, cues before it are
+    /// skipped.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.offset = pos.as_secs_f64();
+        self.frames = 0;
+        self.sample_in_frame = 0;
+        self.next_cue = self.cues.partition_point(|(at, _)| *at < pos);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn cues_fire_once_in_order() {
+        // One second of stereo audio at 1 kHz.
+        let source = SamplesBuffer::new(2, 1000, vec![0i16; 2000]);
+        let cues = vec![
+            (Duration::from_millis(500), "chorus"),
+            (Duration::ZERO, "start"),
+            (Duration::from_millis(250), "verse"),
+            (Duration::from_secs(5), "never"),
+        ];
+        let (mut source, cues) = source.with_cues(cues);
+
+        assert_eq!(cues.try_recv().ok(), None);
+        source.next();
+        assert_eq!(cues.try_recv().ok(), Some("start"));
+
+        // 250 frames are 500 samples, the next frame starts at 250 ms.
+        source.by_ref().take(499).for_each(drop);
+        assert_eq!(cues.try_recv().ok(), None);
+        source.next();
+        assert_eq!(cues.try_recv().ok(), Some("verse"));
+
+        source.by_ref().for_each(drop);
+        assert_eq!(cues.try_iter().collect::<Vec<_>>(), vec!["chorus"]);
+    }
+}
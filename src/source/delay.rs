@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use cpal::Sample as CpalSample;
+
 use crate::{Sample, Source};
 
 use super::SeekError;
@@ -19,6 +21,26 @@ where
     Delay {
         remaining_samples: remaining_samples(duration, input.sample_rate(), input.channels()),
         requested_duration: duration,
+        first: None,
+        checked: false,
+        input,
+    }
+}
+
+/// Internal function that builds a `Delay` object delayed by a number of frames.
+pub fn delay_samples<I>(input: I, frames: usize) -> Delay<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let rate = input.sample_rate().max(1) as u64;
+    let nanos = frames as u64 * 1_000_000_000 / rate;
+    Delay {
+        remaining_samples: frames * input.channels() as usize,
+        // Only used to seek and to report the duration, the silence itself is exact.
+        requested_duration: Duration::from_nanos(nanos),
+        first: None,
+        checked: false,
         input,
     }
 }
@@ -29,6 +51,10 @@ pub struct Delay<I> {
     input: I,
     remaining_samples: usize,
     requested_duration: Duration,
+    // First sample of the input, read ahead so an empty input is not delayed. Kept as a
+    // float, which holds every `i16`, `u16` and `f32` sample exactly.
+    first: Option<f32>,
+    checked: bool,
 }
 
 impl<I> Delay<I>
@@ -64,9 +90,21 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if !self.checked {
+            self.checked = true;
+            self.first = self.input.next().map(Sample::to_f32);
+            if self.first.is_none() {
+                self.remaining_samples = 0;
+                return None;
+            }
+        }
+
         if self.remaining_samples >= 1 {
             self.remaining_samples -= 1;
             Some(Sample::zero_value())
+        } else if let Some(first) = self.first.take() {
+            let first: <I::Item as CpalSample>::Float = CpalSample::from_sample(first);
+            Some(first.to_sample())
         } else {
             self.input.next()
         }
@@ -75,23 +113,21 @@ where
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         let (min, max) = self.input.size_hint();
-        (
-            min + self.remaining_samples,
-            max.map(|v| v + self.remaining_samples),
-        )
+        let ahead = self.remaining_samples + self.first.is_some() as usize;
+        (min + ahead, max.map(|v| v + ahead))
     }
 }
 
 impl<I> Source for Delay<I>
 where
-    I: Iterator + Source,
+    I: Source,
     I::Item: Sample,
 {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
         self.input
             .current_frame_len()
-            .map(|val| val + self.remaining_samples)
+            .map(|val| val + self.remaining_samples + self.first.is_some() as usize)
     }
 
     #[inline]
@@ -111,6 +147,11 @@ where
             .map(|val| val + self.requested_duration)
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
     /// Pos is seen from the perspective of the api user.
     ///
     /// # Example
@@ -126,6 +167,8 @@ where
     ///
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.first = None;
+        self.checked = false;
         if pos < self.requested_duration {
             self.input.try_seek(Duration::ZERO)?;
             let until_playback = self.requested_duration - pos;
@@ -136,3 +179,33 @@ where
         self.input.try_seek(compensated_for_delay)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn delay_samples_is_exact() {
+        let output: Vec<i16> = SamplesBuffer::new(2, 44100, vec![1i16, -1, 2, -2])
+            .delay_samples(3)
+            .collect();
+        assert_eq!(output, vec![0, 0, 0, 0, 0, 0, 1, -1, 2, -2]);
+
+        // The first sample is read ahead and must come out unchanged.
+        let output: Vec<i16> = SamplesBuffer::new(1, 44100, vec![i16::MIN, i16::MAX])
+            .delay_samples(1)
+            .collect();
+        assert_eq!(output, vec![0, i16::MIN, i16::MAX]);
+        let output: Vec<u16> = SamplesBuffer::new(1, 44100, vec![u16::MAX, 1])
+            .delay_samples(1)
+            .collect();
+        assert_eq!(output, vec![32768, u16::MAX, 1]);
+
+        // Aligning a dry signal with a wet one that lags behind by a known latency.
+        let dry = SamplesBuffer::new(1, 44100, vec![0.5f32; 100]);
+        let wet = SamplesBuffer::new(1, 44100, [vec![0.0f32; 37], vec![0.5f32; 100]].concat());
+        let aligned: Vec<f32> = dry.delay_samples(37).collect();
+        assert_eq!(aligned, wet.collect::<Vec<f32>>());
+    }
+}
@@ -0,0 +1,337 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Feedback is kept below 1.0, where every echo would be as loud as the previous one.
+const MAX_FEEDBACK: f32 = 0.95;
+/// After the input ends the echoes ring out until they are 60 dB down.
+const TAIL_LEVEL: f32 = 0.001;
+
+/// Internal function that builds an `Echo` object.
+pub fn echo<I>(input: I, delay: Duration, feedback: f32, mix: f32) -> Echo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let handle = EchoHandle(Arc::new(EchoParams {
+        delay_nanos: AtomicU64::new(0),
+        feedback: AtomicU32::new(0),
+        mix: AtomicU32::new(0),
+    }));
+    handle.set_delay(delay);
+    handle.set_feedback(feedback);
+    handle.set_mix(mix);
+    Echo {
+        input,
+        params: handle,
+        channels: 0,
+        sample_rate: 0,
+        delay: Duration::ZERO,
+        feedback: 0.0,
+        mix: 0.0,
+        buffer: Vec::new(),
+        position: 0,
+        channel: 0,
+        started: false,
+        tail: None,
+    }
+}
+
+#[derive(Debug)]
+struct EchoParams {
+    delay_nanos: AtomicU64,
+    feedback: AtomicU32,
+    mix: AtomicU32,
+}
+
+/// Changes the parameters of an [`Echo`] while it plays, see [`Echo::handle`].
+///
+/// Changes are picked up at the start of the next frame.
+#[derive(Clone, Debug)]
+pub struct EchoHandle(Arc<EchoParams>);
+
+impl EchoHandle {
+    /// Changes the time between two echoes. The echoes that are still ringing are dropped.
+    pub fn set_delay(&self, delay: Duration) {
+        let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+        self.0.delay_nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    /// Returns the time between two echoes.
+    pub fn delay(&self) -> Duration {
+        Duration::from_nanos(self.0.delay_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Changes how much of each echo is fed back into the next one, clamped to 0.0 up to
+    /// 0.95.
+    pub fn set_feedback(&self, feedback: f32) {
+        let feedback = if feedback.is_nan() {
+            0.0
+        } else {
+            feedback.clamp(0.0, MAX_FEEDBACK)
+        };
+        self.0.feedback.store(feedback.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns how much of each echo is fed back into the next one.
+    pub fn feedback(&self) -> f32 {
+        f32::from_bits(self.0.feedback.load(Ordering::Relaxed))
+    }
+
+    /// Changes the level of the echoes relative to the original sound.
+    pub fn set_mix(&self, mix: f32) {
+        self.0.mix.store(mix.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the level of the echoes relative to the original sound.
+    pub fn mix(&self) -> f32 {
+        f32::from_bits(self.0.mix.load(Ordering::Relaxed))
+    }
+}
+
+/// Number of times the delay line is played after the input ended, until the echoes have
+/// died down.
+fn tail_passes(feedback: f32) -> u32 {
+    if feedback <= TAIL_LEVEL {
+        1
+    } else {
+        1 + (TAIL_LEVEL.ln() / feedback.ln()).ceil() as u32
+    }
+}
+
+/// Filter that mixes repeating, fading echoes into a source.
+///
+/// The source is fed through a delay line whose output is fed back into it, attenuated by
+/// the feedback, so every echo is quieter than the previous one. When the source ends the
+/// echoes ring out until they are 60 dB below the last sound.
+#[derive(Clone, Debug)]
+pub struct Echo<I> {
+    input: I,
+    params: EchoHandle,
+    // Format and delay the delay line was set up for, checked at every frame boundary.
+    channels: u16,
+    sample_rate: u32,
+    delay: Duration,
+    feedback: f32,
+    mix: f32,
+    // Interleaved delay line, one delay long.
+    buffer: Vec<f32>,
+    position: usize,
+    channel: u16,
+    // Whether the input played anything, an empty input stays empty.
+    started: bool,
+    // Samples left to ring out once the input ended.
+    tail: Option<usize>,
+}
+
+impl<I> Echo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the parameters from another thread.
+    pub fn handle(&self) -> EchoHandle {
+        self.params.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads the parameters and sets the delay line up again if the delay or the format of
+    /// the input changed.
+    fn update(&mut self) {
+        self.feedback = self.params.feedback();
+        self.mix = self.params.mix();
+
+        let delay = self.params.delay();
+        let (channels, sample_rate) = if self.tail.is_some() {
+            (self.channels, self.sample_rate)
+        } else {
+            (self.input.channels().max(1), self.input.sample_rate())
+        };
+        if (delay, channels, sample_rate) == (self.delay, self.channels, self.sample_rate) {
+            return;
+        }
+        self.delay = delay;
+        self.channels = channels;
+        self.sample_rate = sample_rate;
+        let frames = (delay.as_secs_f64() * sample_rate as f64).round() as usize;
+        self.buffer = vec![0.0; frames.max(1) * channels as usize];
+        self.position = 0;
+    }
+}
+
+impl<I> Iterator for Echo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let value = match self.tail {
+            None => match self.input.next() {
+                Some(value) => {
+                    self.started = true;
+                    value.to_f32()
+                }
+                None if self.started && self.channel == 0 => {
+                    let passes = tail_passes(self.feedback) as usize;
+                    self.tail = Some(passes * self.buffer.len());
+                    return self.next();
+                }
+                None => return None,
+            },
+            Some(0) => return None,
+            Some(ref mut left) => {
+                *left -= 1;
+                0.0
+            }
+        };
+
+        let delayed = self.buffer[self.position];
+        self.buffer[self.position] = value + delayed * self.feedback;
+        self.position = (self.position + 1) % self.buffer.len();
+        self.channel = (self.channel + 1) % self.channels;
+
+        let output: <I::Item as CpalSample>::Float =
+            CpalSample::from_sample(value + delayed * self.mix);
+        Some(output.to_sample())
+    }
+}
+
+impl<I> Source for Echo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.tail {
+            Some(left) => Some(left),
+            None => self.input.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        if self.tail.is_some() {
+            self.channels
+        } else {
+            self.input.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        if self.tail.is_some() {
+            self.sample_rate
+        } else {
+            self.input.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let passes = tail_passes(self.params.feedback());
+        self.input
+            .total_duration()
+            .map(|duration| duration + self.params.delay() * passes)
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.buffer.iter_mut().for_each(|sample| *sample = 0.0);
+        self.channel = 0;
+        self.tail = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    /// A stereo click at the start of half a second of silence.
+    fn click() -> SamplesBuffer<f32> {
+        let mut samples = vec![0.0; 48000];
+        samples[0] = 1.0;
+        samples[1] = 0.5;
+        SamplesBuffer::new(2, 48000, samples)
+    }
+
+    #[test]
+    fn first_echo_is_one_delay_late() {
+        let output: Vec<f32> = click()
+            .echo(Duration::from_millis(10), 0.5, 0.8)
+            .take(48000)
+            .collect();
+        // 10 ms at 48 kHz are 480 frames of two samples.
+        assert_eq!(&output[..2], &[1.0, 0.5]);
+        assert_eq!(&output[960..962], &[0.8, 0.4]);
+        assert_eq!(&output[1920..1922], &[0.4, 0.2]);
+        let nonzero: Vec<usize> = (0..output.len()).filter(|&i| output[i] != 0.0).collect();
+        assert_eq!(&nonzero[..6], &[0, 1, 960, 961, 1920, 1921]);
+    }
+
+    #[test]
+    fn echoes_ring_out_after_the_input() {
+        let source = click().echo(Duration::from_millis(100), 0.5, 1.0);
+        // 0.5 to the 10th power is below 60 dB, so the delay line plays 11 more times.
+        let expected = Duration::from_millis(500) + Duration::from_millis(100) * 11;
+        assert_eq!(source.total_duration(), Some(expected));
+        assert_eq!(source.count(), 48000 + 11 * 9600);
+    }
+
+    #[test]
+    fn feedback_is_clamped() {
+        let source = click().echo(Duration::from_millis(10), 2.0, 1.0);
+        assert_eq!(source.handle().feedback(), 0.95);
+    }
+
+    #[test]
+    fn parameters_change_while_playing() {
+        let mut source = click().echo(Duration::from_millis(10), 0.0, 1.0);
+        let handle = source.handle();
+        handle.set_mix(0.25);
+        handle.set_delay(Duration::from_millis(5));
+        let output: Vec<f32> = source.by_ref().take(1000).collect();
+        assert_eq!(&output[480..482], &[0.25, 0.125]);
+        assert!(output[960..].iter().all(|&s| s == 0.0));
+    }
+}
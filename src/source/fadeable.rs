@@ -1,50 +1,342 @@
-use std::sync::atomic::AtomicU8;
-use std::time::Duration;
-use std::sync::mpsc::{Receiver};
 use crate::{Sample, Source};
-use std::sync::Arc;
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use super::SeekError;
 
+/// Direction of the fade performed by a [`Fadeable`].
 #[derive(Clone, Debug, PartialEq)]
 pub enum FadeDirection {
+    /// Fade from silence to full volume.
     In,
+    /// Fade from full volume to silence.
     Out,
+    /// No direction has been requested yet. A new [`Fadeable`] starts like this and fades in
+    /// from silence over its fade-in duration, as with [`FadeDirection::In`].
     Nothing,
 }
 
-
-
 unsafe impl Send for FadeDirection {}
 unsafe impl Sync for FadeDirection {}
 
+impl FadeDirection {
+    fn from_u8(value: u8) -> FadeDirection {
+        match value {
+            0 => FadeDirection::In,
+            1 => FadeDirection::Out,
+            _ => FadeDirection::Nothing,
+        }
+    }
+}
+
+/// Shape of the gain of a [`Fadeable`] over the course of a fade.
+///
+/// Every curve starts at exactly 0.0 and ends at exactly 1.0, a fade-out runs the curve
+/// backwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FadeCurve {
+    /// The gain changes at a constant rate. Sounds abrupt on long fades, as loudness is
+    /// perceived roughly logarithmically.
+    #[default]
+    Linear,
+    /// The gain rises slowly first and quickly at the end, which is heard as an even change
+    /// in loudness.
+    Exponential,
+    /// The gain rises quickly first and slowly at the end.
+    Logarithmic,
+    /// The gain changes slowly at both ends and quickly in the middle.
+    SCurve,
+    /// The gain changes at a constant rate in decibels, from `floor_db` up to 0 dB. The
+    /// floor, for example -60.0, counts as silence, so the curve still starts at exactly 0.0.
+    Decibels {
+        /// Level at the start of the curve, in decibels below full scale.
+        floor_db: f32,
+    },
+}
+
+impl FadeCurve {
+    /// Maps the linear progress `x` between 0.0 and 1.0 to the gain at that point.
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => x,
+            FadeCurve::Exponential => x * x * x,
+            FadeCurve::Logarithmic => 1.0 - (1.0 - x) * (1.0 - x) * (1.0 - x),
+            FadeCurve::SCurve => x * x * (3.0 - 2.0 * x),
+            FadeCurve::Decibels { .. } if x == 0.0 => 0.0,
+            FadeCurve::Decibels { floor_db } => 10f32.powf(floor_db * (1.0 - x) / 20.0),
+        }
+    }
+}
+
+/// Stored instead of the remaining time while no fade is in progress.
+const NO_FADE: u64 = u64::MAX;
+
+/// State shared with an [`AtomicFadeDirection`], besides the direction and the factor.
+#[derive(Debug)]
+struct Shared {
+    // Latest `fade_to`: bits of the target `f32` and the duration.
+    target: AtomicU32,
+    duration_ns: AtomicU64,
+    // Incremented by every `fade_to`, after storing the values above.
+    generation: AtomicU32,
+    // Incremented by every `change_direction`, also to the current direction.
+    direction_changes: AtomicU32,
+    reset: AtomicBool,
+    // Nanoseconds left of the current fade, `NO_FADE` if there is none.
+    remaining_ns: AtomicU64,
+}
+
+impl Default for Shared {
+    fn default() -> Shared {
+        Shared {
+            target: AtomicU32::new(0),
+            duration_ns: AtomicU64::new(0),
+            generation: AtomicU32::new(0),
+            direction_changes: AtomicU32::new(0),
+            reset: AtomicBool::new(false),
+            remaining_ns: AtomicU64::new(NO_FADE),
+        }
+    }
+}
+
+/// Handle to control the direction of a [`Fadeable`] from another thread.
 #[derive(Clone, Debug)]
-pub struct AtomicFadeDirection(Arc<AtomicU8>);
+pub struct AtomicFadeDirection {
+    direction: Arc<AtomicU8>,
+    factor: Arc<AtomicU32>,
+    shared: Arc<Shared>,
+}
 impl AtomicFadeDirection {
+    /// Starts fading in the given direction. The fade restarts from the beginning
+    /// every time the direction changes.
     pub fn change_direction(&self, direction: FadeDirection) {
-        self.0.store(direction as u8, std::sync::atomic::Ordering::Relaxed);
+        self.direction.store(direction as u8, Ordering::Release);
+        self.shared
+            .direction_changes
+            .fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns the time left of the fade in progress, or `None` if the gain is not changing.
+    ///
+    /// This counts the samples the [`Fadeable`] has yet to play, so it ends exactly when the
+    /// fade does as long as the source is played without pauses.
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.shared.remaining_ns.load(Ordering::Relaxed) {
+            NO_FADE => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    /// Re-arms the fade, see [`Fadeable::reset_fade`].
+    pub fn reset(&self) {
+        self.shared.reset.store(true, Ordering::Release);
+    }
+
+    /// Changes the gain from where it currently is to `target` over `duration`, for example
+    /// to 0.3 to duck music under speech and back to 1.0 afterwards.
+    ///
+    /// The target is clamped between 0.0 and 1.0. A new request takes over from the gain
+    /// reached so far, so retargeting does not jump, while a direction change starts its own
+    /// fade as usual. Fades to a target are not reported by
+    /// [`Source::fadeable_with_completion`].
+    pub fn fade_to(&self, target: f32, duration: Duration) {
+        let target = target.clamp(0.0, 1.0);
+        self.shared
+            .target
+            .store(target.to_bits(), Ordering::Relaxed);
+        self.shared
+            .duration_ns
+            .store(duration.as_nanos() as u64, Ordering::Relaxed);
+        self.shared.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns the gain the [`Fadeable`] applied to its latest sample, see
+    /// [`Fadeable::current_factor`].
+    pub fn current_factor(&self) -> f32 {
+        f32::from_bits(self.factor.load(Ordering::Relaxed))
+    }
+
+    fn downgrade(&self) -> WeakFadeDirection {
+        WeakFadeDirection {
+            direction: Arc::downgrade(&self.direction),
+            factor: Arc::downgrade(&self.factor),
+            shared: Arc::downgrade(&self.shared),
+        }
     }
 }
 
+/// Member of a [`FadeGroup`], which does not keep the [`Fadeable`]'s state alive.
+#[derive(Debug)]
+struct WeakFadeDirection {
+    direction: Weak<AtomicU8>,
+    factor: Weak<AtomicU32>,
+    shared: Weak<Shared>,
+}
+
+impl WeakFadeDirection {
+    fn upgrade(&self) -> Option<AtomicFadeDirection> {
+        Some(AtomicFadeDirection {
+            direction: self.direction.upgrade()?,
+            factor: self.factor.upgrade()?,
+            shared: self.shared.upgrade()?,
+        })
+    }
+}
+
+/// Fades several [`Fadeable`]s together, for example all ambience sounds of a scene.
+///
+/// Changes are applied to all members at once, with the group locked, so no member starts
+/// its fade long after another; each picks the change up at its next sample. A member leaves
+/// the group when its [`Fadeable`] and all other handles to it are dropped. Clones share
+/// the same members.
+#[derive(Clone, Debug, Default)]
+pub struct FadeGroup {
+    members: Arc<Mutex<Vec<WeakFadeDirection>>>,
+}
 
+impl FadeGroup {
+    /// Creates a group without members.
+    pub fn new() -> FadeGroup {
+        FadeGroup::default()
+    }
 
+    /// Adds the [`Fadeable`] controlled by `handle` to the group.
+    pub fn register(&self, handle: AtomicFadeDirection) {
+        self.members.lock().unwrap().push(handle.downgrade());
+    }
+
+    /// Returns the number of members that are still alive.
+    pub fn len(&self) -> usize {
+        self.for_each(|_| ())
+    }
+
+    /// Returns true if no member is alive.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Starts fading all members in the given direction, see
+    /// [`AtomicFadeDirection::change_direction`].
+    pub fn change_direction(&self, direction: FadeDirection) {
+        self.for_each(|member| member.change_direction(direction.clone()));
+    }
+
+    /// Fades all members to `target` over `duration`, see [`AtomicFadeDirection::fade_to`].
+    pub fn fade_to(&self, target: f32, duration: Duration) {
+        self.for_each(|member| member.fade_to(target, duration));
+    }
+
+    /// Runs `f` on every live member and forgets the dropped ones, returns the live count.
+    fn for_each(&self, mut f: impl FnMut(&AtomicFadeDirection)) -> usize {
+        let mut members = self.members.lock().unwrap();
+        members.retain(|member| match member.upgrade() {
+            Some(member) => {
+                f(&member);
+                true
+            }
+            None => false,
+        });
+        members.len()
+    }
+}
 
 /// Internal function that builds a `Fadeable` object.
-pub fn fadeable<I>(input: I, duration: Duration, ) -> (Fadeable<I>, AtomicFadeDirection)
+pub fn fadeable<I>(
+    input: I,
+    duration: Duration,
+    curve: FadeCurve,
+) -> (Fadeable<I>, AtomicFadeDirection)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fadeable_asymmetric(input, duration, duration, curve)
+}
+
+/// Internal function that builds a `Fadeable` object with separate durations per direction.
+pub fn fadeable_asymmetric<I>(
+    input: I,
+    fade_in: Duration,
+    fade_out: Duration,
+    curve: FadeCurve,
+) -> (Fadeable<I>, AtomicFadeDirection)
 where
     I: Source,
     I::Item: Sample,
 {
-    let duration = duration.as_secs() * 1000000000 + duration.subsec_nanos() as u64;
+    let fade_in = fade_in.as_nanos() as f32;
+    let fade_out = fade_out.as_nanos() as f32;
 
     let direction = Arc::new(AtomicU8::new(FadeDirection::Nothing as u8));
+    let factor = Arc::new(AtomicU32::new(1.0f32.to_bits()));
+    let shared = Arc::new(Shared::default());
     let s = Fadeable {
         input,
-        remaining_ns: duration as f32,
-        total_ns: duration as f32,
+        remaining_ns: fade_in,
+        total_ns: fade_in,
+        total_ns_in: fade_in,
+        total_ns_out: fade_out,
         f: 1.0,
+        curve,
+        factor: factor.clone(),
         direction: direction.clone(),
         current_direction: FadeDirection::Nothing as u8,
+        completion: None,
+        completed: false,
+        shared: shared.clone(),
+        ramp_generation: 0,
+        direction_changes: 0,
+        rearmed: false,
+        ramp: None,
+    };
+    let handle = AtomicFadeDirection {
+        direction,
+        factor,
+        shared,
     };
-    (s, AtomicFadeDirection(direction.clone()))
+    (s, handle)
+}
+
+/// Internal function that builds a `Fadeable` object that fades at a rate in decibels.
+pub fn fadeable_db<I>(
+    input: I,
+    db_per_sec: f32,
+    floor_db: f32,
+) -> (Fadeable<I>, AtomicFadeDirection)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let floor_db = -floor_db.abs();
+    let secs = if db_per_sec > 0.0 {
+        -floor_db / db_per_sec
+    } else {
+        0.0
+    };
+    let duration = Duration::from_secs_f32(secs);
+    fadeable(input, duration, FadeCurve::Decibels { floor_db })
+}
+
+/// Internal function that builds a `Fadeable` object that reports finished fades.
+pub fn fadeable_with_completion<I>(
+    input: I,
+    duration: Duration,
+) -> (Fadeable<I>, AtomicFadeDirection, Receiver<FadeDirection>)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let (mut source, handle) = fadeable(input, duration, FadeCurve::Linear);
+    let (sender, receiver) = channel();
+    source.completion = Some(sender);
+    (source, handle, receiver)
 }
 
 /// Filter that modifies raises the volume from silence over a time period.
@@ -52,11 +344,26 @@ where
 pub struct Fadeable<I> {
     input: I,
     remaining_ns: f32,
+    // Duration of the current fade, one of the two below.
     total_ns: f32,
+    total_ns_in: f32,
+    total_ns_out: f32,
     f: f32,
+    curve: FadeCurve,
+    // `f` shared with the handle, as the bits of an `f32`.
+    factor: Arc<AtomicU32>,
     direction: Arc<AtomicU8>,
     current_direction: u8,
-    
+    completion: Option<Sender<FadeDirection>>,
+    // The end of the current fade was reported already.
+    completed: bool,
+    shared: Arc<Shared>,
+    ramp_generation: u32,
+    direction_changes: u32,
+    // The next direction change restarts the fade, even to the current direction.
+    rearmed: bool,
+    // Start and target gain while fading to a target.
+    ramp: Option<(f32, f32)>,
 }
 
 impl<I> Fadeable<I>
@@ -64,6 +371,23 @@ where
     I: Source,
     I::Item: Sample,
 {
+    /// Returns the gain applied to the latest sample, between 0.0 and 1.0.
+    #[inline]
+    pub fn current_factor(&self) -> f32 {
+        self.f
+    }
+
+    /// Re-arms the fade: the next direction change starts a fade of the full duration from
+    /// the start of that direction, even if the direction stays the same.
+    ///
+    /// Use it to fade a source in from silence again after a fade-in completed. The gain
+    /// currently heard does not change, a fade in progress runs on; only the next transition
+    /// is affected. [`AtomicFadeDirection::reset`] does the same from another thread.
+    #[inline]
+    pub fn reset_fade(&mut self) {
+        self.rearmed = true;
+    }
+
     /// Returns a reference to the inner source.
     #[inline]
     pub fn inner(&self) -> &I {
@@ -92,20 +416,65 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<I::Item> {
-
-        if self.direction.load(std::sync::atomic::Ordering::SeqCst) != self.current_direction {
+        if self.shared.reset.swap(false, Ordering::Acquire) {
+            self.reset_fade();
+        }
+        let changes = self.shared.direction_changes.load(Ordering::Acquire);
+        let direction = self.direction.load(Ordering::Acquire);
+        let restart = self.rearmed && changes != self.direction_changes;
+        self.direction_changes = changes;
+        if direction != self.current_direction || restart {
+            self.current_direction = direction;
+            self.rearmed = false;
+            // Restarts with the full duration of the new direction, also when interrupting a
+            // fade the other way.
+            self.total_ns = if self.current_direction == FadeDirection::Out as u8 {
+                self.total_ns_out
+            } else {
+                self.total_ns_in
+            };
             self.remaining_ns = self.total_ns;
-            self.current_direction = self.direction.load(std::sync::atomic::Ordering::SeqCst);
-        } 
+            self.completed = false;
+            self.ramp = None;
+        }
+        let generation = self.shared.generation.load(Ordering::Acquire);
+        if generation != self.ramp_generation {
+            self.ramp_generation = generation;
+            let target = f32::from_bits(self.shared.target.load(Ordering::Relaxed));
+            self.total_ns = self.shared.duration_ns.load(Ordering::Relaxed) as f32;
+            self.remaining_ns = self.total_ns;
+            // Starts from the current gain, also when interrupting another fade.
+            self.ramp = Some((self.f, target));
+            self.completed = true;
+        }
         // default is going lowwer
 
-        if self.remaining_ns < 0.0 {
-            self.f = if self.current_direction == FadeDirection::Out as u8 {
+        if self.remaining_ns <= 0.0 && !self.completed {
+            self.completed = true;
+            let direction = FadeDirection::from_u8(self.current_direction);
+            if let (Some(completion), false) =
+                (&self.completion, direction == FadeDirection::Nothing)
+            {
+                // Nobody might listen anymore, that is fine.
+                let _ = completion.send(direction);
+            }
+        }
+
+        let step = 1000000000.0 / (self.input.sample_rate() as f32 * self.channels() as f32);
+        let f = if let Some((start, target)) = self.ramp {
+            if self.remaining_ns <= 0.0 {
+                target
+            } else {
+                let progress = 1.0 - self.remaining_ns / self.total_ns;
+                self.remaining_ns -= step;
+                start + (target - start) * self.curve.apply(progress)
+            }
+        } else if self.remaining_ns <= 0.0 {
+            if self.current_direction == FadeDirection::Out as u8 {
                 0.0
             } else {
                 1.0
-            }; 
-            return self.input.next().map(|value| value.amplify(self.f)) 
+            }
         } else {
             let factor = if self.current_direction == FadeDirection::Out as u8 {
                 self.remaining_ns / self.total_ns
@@ -114,12 +483,21 @@ where
             };
 
             if self.remaining_ns > 0.0 {
-                self.remaining_ns -=
-                    1000000000.0 / (self.input.sample_rate() as f32 * self.channels() as f32);
+                self.remaining_ns -= step;
             }
-            self.input.next().map(|value| value.amplify(factor))
+            self.curve.apply(factor)
+        };
+        let remaining = if self.remaining_ns > 0.0 {
+            self.remaining_ns as u64
+        } else {
+            NO_FADE
+        };
+        self.shared.remaining_ns.store(remaining, Ordering::Relaxed);
+        if f != self.f {
+            self.f = f;
+            self.factor.store(f.to_bits(), Ordering::Relaxed);
         }
-
+        self.input.next().map(|value| value.amplify(self.f))
     }
 
     #[inline]
@@ -159,4 +537,221 @@ where
     fn total_duration(&self) -> Option<Duration> {
         self.input.total_duration()
     }
-}
\ No newline at end of file
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    /// Seeks the inner source, a fade in progress continues from where it is.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use approx::assert_abs_diff_eq;
+
+    use super::{FadeCurve, FadeGroup};
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{FadeDirection, Source};
+
+    #[test]
+    fn curves_end_exactly() {
+        for curve in [
+            FadeCurve::Linear,
+            FadeCurve::Exponential,
+            FadeCurve::Logarithmic,
+            FadeCurve::SCurve,
+        ] {
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(1.0), 1.0);
+            assert_eq!(curve.apply(-0.5), 0.0);
+            assert_eq!(curve.apply(1.5), 1.0);
+        }
+        assert!(FadeCurve::Exponential.apply(0.5) < 0.5);
+        assert!(FadeCurve::Logarithmic.apply(0.5) > 0.5);
+        assert_eq!(FadeCurve::SCurve.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn fade_out_follows_the_curve() {
+        let duration = Duration::from_millis(4);
+        let (source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 6])
+            .fadeable_with_curve(duration, FadeCurve::Exponential);
+        fader.change_direction(FadeDirection::Out);
+        let output: Vec<f32> = source.collect();
+        assert_eq!(output, vec![1.0, 0.421875, 0.125, 0.015625, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn completion_is_sent_once_per_fade() {
+        let duration = Duration::from_millis(2);
+        let (mut source, fader, completed) =
+            SamplesBuffer::new(1, 1000, vec![1.0f32; 12]).fadeable_with_completion(duration);
+        fader.change_direction(FadeDirection::Out);
+        source.nth(1);
+        assert!(completed.try_recv().is_err());
+        source.nth(2);
+        assert_eq!(completed.try_recv(), Ok(FadeDirection::Out));
+        source.nth(1);
+        assert!(completed.try_recv().is_err());
+
+        fader.change_direction(FadeDirection::In);
+        source.nth(3);
+        assert_eq!(completed.try_recv(), Ok(FadeDirection::In));
+        drop(completed);
+        fader.change_direction(FadeDirection::Out);
+        assert_eq!(source.count(), 1);
+    }
+
+    #[test]
+    fn directions_have_their_own_duration() {
+        let (mut source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 12])
+            .fadeable_asymmetric(Duration::from_millis(2), Duration::from_millis(4));
+        fader.change_direction(FadeDirection::Out);
+        let out: Vec<f32> = source.by_ref().take(6).collect();
+        assert_eq!(out, vec![1.0, 0.75, 0.5, 0.25, 0.0, 0.0]);
+
+        fader.change_direction(FadeDirection::In);
+        let fade_in: Vec<f32> = source.by_ref().take(4).collect();
+        assert_eq!(fade_in, vec![0.0, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn fades_to_a_target_and_retargets() {
+        let duration = Duration::from_millis(4);
+        let (mut source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 12]).fadeable(duration);
+        fader.fade_to(0.2, duration);
+        let ducked: Vec<f32> = source.by_ref().take(3).collect();
+        assert_eq!(ducked, vec![1.0, 0.8, 0.6]);
+
+        // Interrupted halfway, continues from 0.6 instead of jumping.
+        fader.fade_to(1.0, Duration::from_millis(2));
+        let restored: Vec<f32> = source.by_ref().take(4).collect();
+        assert_eq!(restored, vec![0.6, 0.8, 1.0, 1.0]);
+        assert_eq!(fader.current_factor(), 1.0);
+    }
+
+    #[test]
+    fn direction_flips_while_playing() {
+        let (source, fader) =
+            SamplesBuffer::new(2, 1000, vec![1.0f32; 200000]).fadeable(Duration::from_millis(10));
+        let stop = Arc::new(AtomicBool::new(false));
+        let flipper = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let mut out = false;
+                while !stop.load(Ordering::Relaxed) {
+                    out = !out;
+                    fader.change_direction(if out {
+                        FadeDirection::Out
+                    } else {
+                        FadeDirection::In
+                    });
+                }
+            })
+        };
+        let mut played = 0;
+        for value in source {
+            assert!((0.0..=1.0).contains(&value));
+            played += 1;
+        }
+        stop.store(true, Ordering::Relaxed);
+        flipper.join().unwrap();
+        assert_eq!(played, 200000);
+    }
+
+    #[test]
+    fn reset_restarts_the_same_direction() {
+        let duration = Duration::from_millis(2);
+        let (mut source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 12]).fadeable(duration);
+        fader.change_direction(FadeDirection::In);
+        let faded_in: Vec<f32> = source.by_ref().take(4).collect();
+        assert_eq!(faded_in, vec![0.0, 0.5, 1.0, 1.0]);
+
+        // Without a reset fading in again keeps the full volume.
+        fader.change_direction(FadeDirection::In);
+        assert_eq!(source.next(), Some(1.0));
+
+        fader.reset();
+        assert_eq!(source.next(), Some(1.0));
+        fader.change_direction(FadeDirection::In);
+        let again: Vec<f32> = source.by_ref().take(3).collect();
+        assert_eq!(again, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn remaining_counts_down() {
+        let duration = Duration::from_millis(4);
+        let (mut source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 12]).fadeable(duration);
+        source.by_ref().take(4).for_each(drop);
+        assert_eq!(fader.remaining(), None);
+
+        fader.change_direction(FadeDirection::Out);
+        source.next();
+        assert_eq!(fader.remaining(), Some(Duration::from_millis(3)));
+        source.nth(1);
+        assert_eq!(fader.remaining(), Some(Duration::from_millis(1)));
+        source.next();
+        assert_eq!(fader.remaining(), None);
+    }
+
+    #[test]
+    fn decibel_fades_are_linear_in_db() {
+        // 20 dB per second from -40 dB takes two seconds, at a sample every quarter second.
+        let (source, fader) = SamplesBuffer::new(1, 4, vec![1.0f32; 10]).fadeable_db(20.0, -40.0);
+        fader.change_direction(FadeDirection::In);
+        let output: Vec<f32> = source.collect();
+        assert_eq!(output[0], 0.0);
+        for (index, expected_db) in [(2, -30.0), (4, -20.0), (8, 0.0)] {
+            assert_abs_diff_eq!(20.0 * output[index].log10(), expected_db, epsilon = 1e-3);
+        }
+        assert_eq!(output[9], 1.0);
+    }
+
+    #[test]
+    fn group_fades_its_members() {
+        let duration = Duration::from_millis(2);
+        let (mut first, first_fader) =
+            SamplesBuffer::new(1, 1000, vec![1.0f32; 4]).fadeable(duration);
+        let (second, second_fader) =
+            SamplesBuffer::new(1, 1000, vec![1.0f32; 4]).fadeable(duration);
+        let group = FadeGroup::new();
+        group.register(first_fader);
+        group.register(second_fader);
+        assert_eq!(group.len(), 2);
+
+        group.change_direction(FadeDirection::Out);
+        let first_out: Vec<f32> = first.by_ref().take(3).collect();
+        let second_out: Vec<f32> = second.collect();
+        assert_eq!(first_out, vec![1.0, 0.5, 0.0]);
+        assert_eq!(second_out, vec![1.0, 0.5, 0.0, 0.0]);
+        assert_eq!(group.len(), 1);
+
+        group.fade_to(1.0, Duration::ZERO);
+        assert_eq!(first.next(), Some(1.0));
+        drop(first);
+        assert!(group.is_empty());
+    }
+
+    #[test]
+    fn current_factor_follows_the_fade() {
+        let duration = Duration::from_millis(4);
+        let (mut source, fader) = SamplesBuffer::new(1, 1000, vec![1.0f32; 6]).fadeable(duration);
+        fader.change_direction(FadeDirection::Out);
+        assert_eq!(fader.current_factor(), 1.0);
+        source.nth(1);
+        assert_eq!(source.current_factor(), 0.75);
+        assert_eq!(fader.current_factor(), 0.75);
+        source.by_ref().for_each(drop);
+        assert_eq!(fader.current_factor(), 0.0);
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Length of the fade applied when the gate opens or closes.
+const DECLICK: Duration = Duration::from_millis(5);
+
+/// Internal function that builds a `Gate` object.
+pub fn gated_by<I>(input: I, enabled: Arc<AtomicBool>, advance_when_closed: bool) -> Gate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let gain = if enabled.load(Ordering::Relaxed) {
+        1.0
+    } else {
+        0.0
+    };
+    Gate {
+        input,
+        enabled,
+        advance_when_closed,
+        gain,
+        sample_in_frame: 0,
+    }
+}
+
+/// Passes the source through only while a shared flag is set.
+#[derive(Clone, Debug)]
+pub struct Gate<I> {
+    input: I,
+    enabled: Arc<AtomicBool>,
+    advance_when_closed: bool,
+    // Follows the flag, ramping between 0.0 and 1.0 over `DECLICK`.
+    gain: f32,
+    sample_in_frame: u16,
+}
+
+impl<I> Gate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Gate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let channels = self.input.channels().max(1);
+        if self.sample_in_frame == 0 {
+            let target = if self.enabled.load(Ordering::Relaxed) {
+                1.0
+            } else {
+                0.0
+            };
+            if self.gain != target {
+                let step = 1.0 / (DECLICK.as_secs_f32() * self.input.sample_rate() as f32);
+                self.gain = if target > self.gain {
+                    (self.gain + step).min(target)
+                } else {
+                    (self.gain - step).max(target)
+                };
+            }
+        }
+        self.sample_in_frame = (self.sample_in_frame + 1) % channels;
+
+        if self.gain == 0.0 && !self.advance_when_closed {
+            return Some(I::Item::zero_value());
+        }
+        self.input.next().map(|value| value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.advance_when_closed {
+            self.input.size_hint()
+        } else {
+            (self.input.size_hint().0, None)
+        }
+    }
+}
+
+impl<I> Source for Gate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        if self.advance_when_closed {
+            self.input.total_duration()
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use approx::assert_abs_diff_eq;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    fn set_from_thread(flag: &Arc<AtomicBool>, value: bool) {
+        let flag = flag.clone();
+        thread::spawn(move || flag.store(value, Ordering::Relaxed))
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn closed_gate_is_silent() {
+        let enabled = Arc::new(AtomicBool::new(true));
+        // 5 ms declick at 1 kHz takes five frames.
+        let mut source =
+            SamplesBuffer::new(1, 1000, vec![1.0f32; 100]).gated_by(enabled.clone(), true);
+        assert_eq!(source.next(), Some(1.0));
+
+        set_from_thread(&enabled, false);
+        for expected in [0.8, 0.6, 0.4, 0.2, 0.0] {
+            assert_abs_diff_eq!(source.next().unwrap(), expected, epsilon = 1e-6);
+        }
+        assert!(source.by_ref().take(10).all(|value| value == 0.0));
+
+        set_from_thread(&enabled, true);
+        let opening: Vec<f32> = source.by_ref().take(5).collect();
+        assert!(opening.windows(2).all(|pair| pair[1] > pair[0]));
+        assert_abs_diff_eq!(opening[4], 1.0, epsilon = 1e-6);
+        // The source kept playing while the gate was closed.
+        assert_eq!(source.count(), 100 - 21);
+    }
+
+    #[test]
+    fn closed_gate_holds_position() {
+        let enabled = Arc::new(AtomicBool::new(false));
+        let input: Vec<i16> = (1..=4).collect();
+        let mut source = SamplesBuffer::new(1, 1000, input).gated_by(enabled.clone(), false);
+        assert!(source.by_ref().take(50).all(|value| value == 0));
+
+        set_from_thread(&enabled, true);
+        let output: Vec<i16> = source.collect();
+        // The first samples fade in, but none were skipped while closed.
+        assert_eq!(output.len(), 4);
+        assert_eq!(output[3], 4 * 4 / 5);
+    }
+}
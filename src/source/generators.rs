@@ -0,0 +1,250 @@
+//! Oscillators for beeps, test tones and simple synthesis.
+//!
+//! Every oscillator is an infinite mono source of `f32` samples between -1.0 and 1.0. Its
+//! frequency can be changed while it plays through a [`FrequencyHandle`], the waveform stays
+//! continuous so this can be used for vibrato or sweeps.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use rodio::source::SquareWave;
+//! use rodio::Source;
+//!
+//! let beep = SquareWave::with_sample_rate(880.0, 44100)
+//!     .take_duration(Duration::from_millis(150))
+//!     .amplify(0.2);
+//! ```
+use std::f32::consts::TAU;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Source;
+
+use super::SeekError;
+
+/// Rate of the oscillators created with `new`.
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// Changes the frequency of an oscillator while it plays.
+#[derive(Clone, Debug)]
+pub struct FrequencyHandle(Arc<AtomicU32>);
+
+impl FrequencyHandle {
+    /// Sets the frequency in Hz, picked up from the next sample on.
+    pub fn set_frequency(&self, frequency: f32) {
+        self.0.store(frequency.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the frequency in Hz.
+    pub fn frequency(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Position in the cycle of a waveform, advanced by the frequency of every sample so a change
+/// of the frequency does not make the waveform jump.
+#[derive(Debug)]
+struct Oscillator {
+    frequency: FrequencyHandle,
+    sample_rate: u32,
+    // Between 0.0 and 1.0, kept in double precision so long tones do not drift.
+    phase: f64,
+}
+
+impl Oscillator {
+    fn new(frequency: f32, sample_rate: u32) -> Oscillator {
+        Oscillator {
+            frequency: FrequencyHandle(Arc::new(AtomicU32::new(frequency.to_bits()))),
+            sample_rate,
+            phase: 0.0,
+        }
+    }
+
+    /// Returns the phase of the next sample and advances it.
+    #[inline]
+    fn advance(&mut self) -> f32 {
+        let phase = self.phase;
+        let step = self.frequency.frequency() as f64 / self.sample_rate.max(1) as f64;
+        self.phase = (self.phase + step).rem_euclid(1.0);
+        phase as f32
+    }
+
+    fn seek(&mut self, pos: Duration) {
+        self.phase = (pos.as_secs_f64() * self.frequency.frequency() as f64).rem_euclid(1.0);
+    }
+}
+
+impl Clone for Oscillator {
+    /// The clone gets a handle of its own, changing its frequency does not affect the
+    /// original.
+    fn clone(&self) -> Oscillator {
+        Oscillator {
+            phase: self.phase,
+            ..Oscillator::new(self.frequency.frequency(), self.sample_rate)
+        }
+    }
+}
+
+macro_rules! oscillator {
+    ($(#[$doc:meta])* $name:ident, |$phase:ident| $render:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            oscillator: Oscillator,
+        }
+
+        impl $name {
+            /// Creates an oscillator with the frequency in Hz and a rate of 48 kHz.
+            #[inline]
+            pub fn new(frequency: f32) -> $name {
+                $name::with_sample_rate(frequency, DEFAULT_SAMPLE_RATE)
+            }
+
+            /// Creates an oscillator with the frequency in Hz and the given sample rate.
+            #[inline]
+            pub fn with_sample_rate(frequency: f32, sample_rate: u32) -> $name {
+                $name {
+                    oscillator: Oscillator::new(frequency, sample_rate),
+                }
+            }
+
+            /// Returns a handle to change the frequency from another thread.
+            #[inline]
+            pub fn frequency_handle(&self) -> FrequencyHandle {
+                self.oscillator.frequency.clone()
+            }
+        }
+
+        impl Iterator for $name {
+            type Item = f32;
+
+            #[inline]
+            fn next(&mut self) -> Option<f32> {
+                let $phase = self.oscillator.advance();
+                Some($render)
+            }
+        }
+
+        impl Source for $name {
+            #[inline]
+            fn current_frame_len(&self) -> Option<usize> {
+                None
+            }
+
+            #[inline]
+            fn channels(&self) -> u16 {
+                1
+            }
+
+            #[inline]
+            fn sample_rate(&self) -> u32 {
+                self.oscillator.sample_rate
+            }
+
+            #[inline]
+            fn total_duration(&self) -> Option<Duration> {
+                None
+            }
+
+            #[inline]
+            fn is_infinite(&self) -> bool {
+                true
+            }
+
+            /// Moves to the point in the cycle the oscillator would be at after `pos` at its
+            /// current frequency.
+            #[inline]
+            fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+                self.oscillator.seek(pos);
+                Ok(())
+            }
+        }
+    };
+}
+
+oscillator!(
+    /// An infinite source that produces a sine.
+    SineWave,
+    |phase| (TAU * phase).sin()
+);
+
+oscillator!(
+    /// An infinite source that produces a square wave, high for the first half of each cycle.
+    SquareWave,
+    |phase| if phase < 0.5 { 1.0 } else { -1.0 }
+);
+
+oscillator!(
+    /// An infinite source that produces a rising sawtooth, starting at 0.0.
+    SawtoothWave,
+    |phase| 2.0 * (phase - (phase + 0.5).floor())
+);
+
+oscillator!(
+    /// An infinite source that produces a triangle wave, starting at -1.0.
+    TriangleWave,
+    |phase| 4.0 * (phase - (phase + 0.5).floor()).abs() - 1.0
+);
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use approx::assert_abs_diff_eq;
+
+    use super::{SawtoothWave, SineWave, SquareWave, TriangleWave};
+    use crate::Source;
+
+    #[test]
+    fn waveforms() {
+        let square: Vec<f32> = SquareWave::with_sample_rate(500.0, 2000).take(6).collect();
+        assert_eq!(square, [1.0, 1.0, -1.0, -1.0, 1.0, 1.0]);
+        let saw: Vec<f32> = SawtoothWave::with_sample_rate(50.0, 200).take(6).collect();
+        assert_eq!(saw, [0.0, 0.5, -1.0, -0.5, 0.0, 0.5]);
+        let triangle: Vec<f32> = TriangleWave::with_sample_rate(1000.0, 8000)
+            .take(9)
+            .collect();
+        assert_eq!(triangle, [-1.0, -0.5, 0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0]);
+        let mut sine = SineWave::with_sample_rate(100.0, 1000);
+        assert_abs_diff_eq!(sine.nth(1).unwrap(), 0.587_785_2);
+        assert_eq!(sine.sample_rate(), 1000);
+    }
+
+    #[test]
+    fn work_with_adapters() {
+        let beep = TriangleWave::new(440.0)
+            .take_duration(Duration::from_millis(100))
+            .amplify(0.5)
+            .fade_in(Duration::from_millis(10));
+        let samples: Vec<f32> = beep.collect();
+        assert_eq!(samples.len(), 4800);
+        assert!(samples.iter().all(|s| s.abs() <= 0.5));
+    }
+
+    #[test]
+    fn frequency_changes_keep_the_waveform_continuous() {
+        let mut sine = SineWave::new(1000.0);
+        let handle = sine.frequency_handle();
+        let mut previous = sine.next().unwrap();
+        for i in 0..4800 {
+            if i % 100 == 0 {
+                handle.set_frequency(if i % 200 == 0 { 1100.0 } else { 900.0 });
+            }
+            let sample = sine.next().unwrap();
+            // A 1100 Hz sine moves by at most 2 pi 1100 / 48000 per sample.
+            assert!((sample - previous).abs() < 0.15);
+            previous = sample;
+        }
+        assert_eq!(handle.frequency(), 900.0);
+    }
+
+    #[test]
+    fn clones_have_their_own_frequency() {
+        let sine = SineWave::new(440.0);
+        let clone = sine.clone();
+        clone.frequency_handle().set_frequency(880.0);
+        assert_eq!(sine.frequency_handle().frequency(), 440.0);
+    }
+}
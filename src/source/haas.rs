@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Channel of a stereo output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// The left channel.
+    Left,
+    /// The right channel.
+    Right,
+}
+
+/// Internal function that builds a `Haas` object.
+pub fn haas<I>(input: I, delay: Duration, side: Side) -> Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(input.channels(), 1, "only mono sources can be widened");
+    let delay_frames = (delay.as_secs_f64() * input.sample_rate() as f64).round() as usize;
+    Haas {
+        input,
+        delay,
+        side,
+        line: VecDeque::from(vec![I::Item::zero_value(); delay_frames]),
+        pending: None,
+        started: false,
+        ended: false,
+    }
+}
+
+/// Widens a mono source to stereo by delaying one of the channels.
+#[derive(Clone, Debug)]
+pub struct Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    delay: Duration,
+    side: Side,
+    // Samples of the delayed channel waiting to be played.
+    line: VecDeque<I::Item>,
+    // Second sample of the frame whose first sample was just returned.
+    pending: Option<I::Item>,
+    // An empty source stays empty instead of playing the delay line.
+    started: bool,
+    ended: bool,
+}
+
+impl<I> Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(value) = self.pending.take() {
+            return Some(value);
+        }
+
+        let direct = if self.ended { None } else { self.input.next() };
+        let (direct, delayed) = match direct {
+            Some(value) => {
+                self.started = true;
+                self.line.push_back(value);
+                (value, self.line.pop_front().unwrap())
+            }
+            None => {
+                // Let the delayed channel play out the end of the source.
+                self.ended = true;
+                if !self.started {
+                    self.line.clear();
+                }
+                (I::Item::zero_value(), self.line.pop_front()?)
+            }
+        };
+
+        let (left, right) = match self.side {
+            Side::Left => (delayed, direct),
+            Side::Right => (direct, delayed),
+        };
+        self.pending = Some(right);
+        Some(left)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = self.pending.is_some() as usize;
+        let tail = self.line.len() * 2;
+        let (lower, upper) = self.input.size_hint();
+        (
+            pending + tail + lower * 2,
+            upper.map(|upper| pending + tail + upper * 2),
+        )
+    }
+}
+
+impl<I> Source for Haas<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration().map(|d| d + self.delay)
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The line shrinks while the end of the source plays out, refill it completely.
+        let delay_frames = (self.delay.as_secs_f64() * self.input.sample_rate() as f64).round();
+        self.line.clear();
+        self.line
+            .resize(delay_frames as usize, I::Item::zero_value());
+        self.pending = None;
+        self.ended = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{Side, Source};
+
+    #[test]
+    fn one_channel_is_delayed_copy() {
+        let input: Vec<i16> = (1..=6).collect();
+        // 2 ms at 1 kHz is a delay of two frames.
+        let source = SamplesBuffer::new(1, 1000, input).haas(Duration::from_millis(2), Side::Right);
+        assert_eq!(source.channels(), 2);
+
+        let output: Vec<i16> = source.collect();
+        let left: Vec<i16> = output.iter().step_by(2).copied().collect();
+        let right: Vec<i16> = output.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(left, vec![1, 2, 3, 4, 5, 6, 0, 0]);
+        assert_eq!(right, vec![0, 0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn zero_delay_is_centered() {
+        let source = SamplesBuffer::new(1, 1000, vec![1i16, 2, 3]).haas(Duration::ZERO, Side::Left);
+        let output: Vec<i16> = source.collect();
+        assert_eq!(output, vec![1, 1, 2, 2, 3, 3]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::biquad::{Biquad, BiquadState};
+use super::{CutoffHandle, SeekError};
+
+/// Internal function that builds a `HighPass` object.
+pub fn high_pass<I>(input: I, cutoff: f32, q: f32) -> HighPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    HighPass {
+        input,
+        cutoff: CutoffHandle::new(cutoff),
+        q: q.max(0.01),
+        current_cutoff: f32::NAN,
+        sample_rate: 0,
+        biquad: None,
+        state: Vec::new(),
+        channel: 0,
+    }
+}
+
+/// Second order high-pass filter with a separate state for each channel.
+#[derive(Clone, Debug)]
+pub struct HighPass<I> {
+    input: I,
+    cutoff: CutoffHandle,
+    q: f32,
+    // Cutoff and rate the coefficients were computed for, checked at every frame boundary.
+    current_cutoff: f32,
+    sample_rate: u32,
+    biquad: Option<Biquad>,
+    state: Vec<BiquadState>,
+    channel: usize,
+}
+
+impl<I> HighPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the cutoff frequency from another thread.
+    pub fn cutoff_handle(&self) -> CutoffHandle {
+        self.cutoff.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Recomputes the coefficients if the cutoff or the format of the input changed.
+    fn update(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        if self.state.len() != channels {
+            self.state = vec![[0.0; 4]; channels];
+        }
+
+        let (cutoff, sample_rate) = (self.cutoff.cutoff(), self.input.sample_rate());
+        if cutoff == self.current_cutoff && sample_rate == self.sample_rate {
+            return;
+        }
+        self.current_cutoff = cutoff;
+        self.sample_rate = sample_rate;
+        // The filter is only stable below the Nyquist frequency.
+        let nyquist = sample_rate as f32 / 2.0;
+        self.biquad = (sample_rate > 0)
+            .then(|| Biquad::high_pass(cutoff.clamp(1.0, nyquist * 0.99), sample_rate, self.q));
+    }
+}
+
+impl<I> Iterator for HighPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let value = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.state.len();
+        let Some(biquad) = &self.biquad else {
+            return Some(value);
+        };
+
+        let filtered = biquad.process(value.to_f32(), &mut self.state[channel]);
+        let filtered: <I::Item as CpalSample>::Float = CpalSample::from_sample(filtered);
+        Some(filtered.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for HighPass<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for HighPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.state.iter_mut().for_each(|state| *state = [0.0; 4]);
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak(samples: impl Iterator<Item = f32>) -> f32 {
+        samples.fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn attenuates_low_frequencies() {
+        let rumble = SineWave::new(30.0).high_pass(1000.0).skip(9600).take(9600);
+        let voice = SineWave::new(4000.0)
+            .high_pass(1000.0)
+            .skip(9600)
+            .take(9600);
+        assert!(peak(rumble) < 0.005);
+        assert!(peak(voice) > 0.95);
+    }
+
+    #[test]
+    fn removes_dc_offset() {
+        let output: Vec<i16> = SamplesBuffer::new(2, 48000, vec![8000i16; 9600])
+            .high_pass(100.0)
+            .collect();
+        assert!(
+            output[9000..].iter().all(|s| s.abs() <= 1),
+            "{:?}",
+            &output[9000..]
+        );
+    }
+
+    #[test]
+    fn cutoff_changes_while_playing() {
+        let mut filter = SineWave::new(200.0).high_pass(20.0);
+        let handle = filter.cutoff_handle();
+        assert!(peak(filter.by_ref().skip(4800).take(4800)) > 0.95);
+
+        handle.set_cutoff(5000.0);
+        assert!(peak(filter.skip(4800).take(4800)) < 0.01);
+    }
+}
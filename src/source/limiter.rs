@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// How far ahead peaks are seen, and so how long the gain takes to come down for them.
+const LOOK_AHEAD: Duration = Duration::from_millis(5);
+/// Width of the soft knee around the ceiling.
+const KNEE_DB: f32 = 6.0;
+
+/// Internal function that builds a `Limiter` object.
+pub fn limit<I>(input: I, ceiling: f32, release: Duration) -> Limiter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Limiter {
+        input,
+        ceiling: ceiling.abs(),
+        release,
+        knee: Knee::Hard,
+        delay: VecDeque::new(),
+        required: VecDeque::new(),
+        input_done: false,
+        gain: 1.0,
+        channel: 0,
+        frame_len: 0,
+    }
+}
+
+/// How a [`Limiter`] treats levels close to its ceiling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Knee {
+    /// Levels below the ceiling pass unchanged, louder ones are brought down to it.
+    Hard,
+    /// The gain is lowered gradually from 3 dB below the ceiling on, which is less audible.
+    /// The output still never exceeds the ceiling.
+    Soft,
+}
+
+/// Filter that keeps the peaks of a source below a ceiling.
+///
+/// The output runs 5 ms behind the input so the gain can be lowered before a peak instead of
+/// after it, and a transient is caught without clipping. The gain is shared by all channels
+/// and recovers over the release time once the peaks are gone.
+#[derive(Clone, Debug)]
+pub struct Limiter<I> {
+    input: I,
+    ceiling: f32,
+    release: Duration,
+    knee: Knee,
+    // Interleaved frames that were read but not played yet.
+    delay: VecDeque<f32>,
+    // Gain each frame in the delay needs to stay below the ceiling.
+    required: VecDeque<f32>,
+    input_done: bool,
+    gain: f32,
+    // Position in the frame being played and its number of channels.
+    channel: u16,
+    frame_len: u16,
+}
+
+impl<I> Limiter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Selects a hard or a soft knee, the knee is hard by default.
+    #[inline]
+    pub fn set_knee(&mut self, knee: Knee) {
+        self.knee = knee;
+    }
+
+    /// Returns the knee of the limiter.
+    #[inline]
+    pub fn knee(&self) -> Knee {
+        self.knee
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Returns the gain that brings a frame with this peak to or below the ceiling.
+    fn required_gain(&self, peak: f32) -> f32 {
+        if self.knee == Knee::Hard {
+            return if peak > self.ceiling {
+                self.ceiling / peak
+            } else {
+                1.0
+            };
+        }
+
+        // Soft knee with an infinite ratio: above the knee the output stays at the ceiling.
+        let over_db = 20.0 * (peak / self.ceiling).log10();
+        let gain_db = if over_db <= -KNEE_DB / 2.0 {
+            0.0
+        } else if over_db >= KNEE_DB / 2.0 {
+            -over_db
+        } else {
+            let into_knee = over_db + KNEE_DB / 2.0;
+            -into_knee * into_knee / (2.0 * KNEE_DB)
+        };
+        10f32.powf(gain_db / 20.0)
+    }
+
+    /// Reads a frame into the delay. Returns false at the end of the input.
+    fn read_frame(&mut self) -> bool {
+        let (mut peak, mut read) = (0.0f32, 0);
+        for _ in 0..self.input.channels().max(1) {
+            let Some(sample) = self.input.next() else {
+                self.input_done = true;
+                break;
+            };
+            let sample = sample.to_f32();
+            peak = peak.max(sample.abs());
+            self.delay.push_back(sample);
+            read += 1;
+        }
+        if read == 0 {
+            return false;
+        }
+
+        self.required.push_back(self.required_gain(peak));
+        !self.input_done
+    }
+
+    /// Keeps the delay filled and sets the gain for the next frame to play.
+    fn start_frame(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        let sample_rate = self.input.sample_rate();
+        let look_ahead = (LOOK_AHEAD.as_secs_f32() * sample_rate as f32) as usize;
+        while !self.input_done && self.delay.len() < (look_ahead + 1) * channels {
+            self.read_frame();
+        }
+        self.frame_len = self.delay.len().min(channels) as u16;
+
+        let lowest = self.required.iter().copied().fold(1.0, f32::min);
+        if lowest >= self.gain {
+            let frames = self.release.as_secs_f32() * sample_rate as f32;
+            let step = if frames > 1.0 {
+                1.0 - (-1.0 / frames).exp()
+            } else {
+                1.0
+            };
+            self.gain += (lowest - self.gain) * step;
+        } else {
+            // Come down in a straight line that reaches each gain ahead by the time its frame
+            // plays.
+            let mut gain = self.gain;
+            for (frames_left, &required) in (1..).zip(&self.required) {
+                gain = gain.min(self.gain - (self.gain - required) / frames_left as f32);
+            }
+            self.gain = gain;
+        }
+        self.required.pop_front();
+    }
+}
+
+impl<I> Iterator for Limiter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.start_frame();
+        }
+
+        let sample = self.delay.pop_front()?;
+        self.channel += 1;
+        if self.channel >= self.frame_len {
+            self.channel = 0;
+        }
+        let output: <I::Item as CpalSample>::Float = CpalSample::from_sample(sample * self.gain);
+        Some(output.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.input.size_hint();
+        let buffered = self.delay.len();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> Source for Limiter<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| len + self.delay.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.delay.clear();
+        self.required.clear();
+        self.input_done = false;
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Knee;
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak<'a>(samples: impl IntoIterator<Item = &'a f32>) -> f32 {
+        samples.into_iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn quiet_sources_are_only_delayed() {
+        let input: Vec<f32> = SineWave::new(440.0).amplify(0.5).take(9600).collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, 48000, input.clone())
+            .limit(1.0, Duration::from_millis(50))
+            .collect();
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn transients_stay_below_the_ceiling() {
+        // Silence followed by a loud stereo burst that starts at full level.
+        let input: Vec<f32> = (0..9600)
+            .flat_map(|i| {
+                let value = if i < 4800 { 0.0 } else { 3.0 };
+                let sign = if i % 24 < 12 { 1.0 } else { -1.0 };
+                [value * sign, value * sign * 0.5]
+            })
+            .collect();
+        let output: Vec<f32> = SamplesBuffer::new(2, 48000, input)
+            .limit(0.8, Duration::from_millis(50))
+            .collect();
+        assert_eq!(output.len(), 19200);
+        assert!(peak(&output) <= 0.8 + 1e-6);
+        assert!((peak(&output[16000..]) - 0.8).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gain_recovers_after_the_peak() {
+        let loud = SineWave::new(440.0).take(4800);
+        let quiet = SineWave::new(440.0).amplify(0.5).take(48000);
+        let input: Vec<f32> = loud.chain(quiet).collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, 48000, input)
+            .limit(0.5, Duration::from_millis(20))
+            .collect();
+        assert!(peak(&output) <= 0.5 + 1e-6);
+        assert!((peak(&output[40000..]) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn soft_knee_starts_below_the_ceiling() {
+        let limited = |knee| {
+            let mut limiter = SineWave::new(440.0)
+                .amplify(0.9)
+                .limit(1.0, Duration::from_millis(50));
+            limiter.set_knee(knee);
+            peak(&limiter.skip(9600).take(4800).collect::<Vec<_>>())
+        };
+        assert!((limited(Knee::Hard) - 0.9).abs() < 1e-3);
+        // 0.9 is 0.9 dB below the ceiling, 2.1 dB into the knee, so it is lowered by 0.36 dB.
+        assert!((limited(Knee::Soft) - 0.863).abs() < 2e-3);
+    }
+}
@@ -90,7 +90,7 @@ where
             factor = self.start_gain * (1.0f32 - p) + self.end_gain * p;
         }
 
-        if self.sample_idx % (self.channels() as u64) == 0 {
+        if self.sample_idx.is_multiple_of(self.channels() as u64) {
             self.elapsed_ns += 1000000000.0 / (self.input.sample_rate() as f32);
         }
 
@@ -135,6 +135,11 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.elapsed_ns = pos.as_nanos() as f32;
@@ -0,0 +1,255 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `LoopRegion` object.
+pub fn loop_region<I>(input: I, frames: Range<u64>, crossfade: Duration) -> LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels().max(1) as u64;
+    let start = frames.start.saturating_mul(channels);
+    let end = frames.end.saturating_mul(channels);
+    let len = (frames.end != u64::MAX).then(|| end.saturating_sub(start) as usize);
+    let crossfade_frames = (crossfade.as_secs_f64() * input.sample_rate() as f64) as usize;
+    let mut source = LoopRegion {
+        input,
+        channels: channels as usize,
+        start,
+        read: 0,
+        region: Vec::new(),
+        len,
+        crossfade_frames,
+        overlap: 0,
+        replay: None,
+    };
+    source.overlap = source.overlap_for(len.unwrap_or(0));
+    source
+}
+
+/// Plays a source up to the end of a region and then repeats that region forever.
+#[derive(Clone, Debug)]
+pub struct LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    channels: usize,
+    // First sample of the region, in samples of the input.
+    start: u64,
+    // Number of samples read from the input so far.
+    read: u64,
+    region: Vec<I::Item>,
+    // Length of the region in samples, `None` until the input ended if it loops up to its end.
+    len: Option<usize>,
+    crossfade_frames: usize,
+    // Samples at the end of the region blended with its start, the same number of samples
+    // at its start are skipped when the region repeats.
+    overlap: usize,
+    // Position in `region` once the whole region was read.
+    replay: Option<usize>,
+}
+
+impl<I> LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Number of samples blended at the seam of a region of `len` samples.
+    fn overlap_for(&self, len: usize) -> usize {
+        self.crossfade_frames.min(len / self.channels / 2) * self.channels
+    }
+
+    /// Mixes the end of the region into its start at position `index`.
+    #[inline]
+    fn blend(&self, index: usize, value: I::Item) -> I::Item {
+        let Some(len) = self.len else {
+            return value;
+        };
+        let fade_start = len - self.overlap;
+        if index < fade_start {
+            return value;
+        }
+        let head = self.region[index - fade_start];
+        let frame = ((index - fade_start) / self.channels) as u32;
+        let frames = (self.overlap / self.channels) as u32;
+        // Reaches the start completely on the last frame, so it continues without a jump.
+        Sample::lerp(value, head, frame + 1, frames)
+    }
+
+    /// Starts repeating the region, returns false if it is empty.
+    fn start_replay(&mut self, seam_blended: bool) -> bool {
+        if self.region.is_empty() {
+            return false;
+        }
+        self.replay = Some(if seam_blended { self.overlap } else { 0 });
+        true
+    }
+}
+
+impl<I> Iterator for LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.replay.is_none() {
+            if self.read < self.start {
+                self.read += 1;
+                return self.input.next();
+            }
+
+            let index = self.region.len();
+            if Some(index) == self.len {
+                if !self.start_replay(self.overlap > 0) {
+                    return None;
+                }
+            } else {
+                match self.input.next() {
+                    Some(value) => {
+                        self.read += 1;
+                        let output = self.blend(index, value);
+                        self.region.push(value);
+                        return Some(output);
+                    }
+                    None => {
+                        // The region ends with the input, its seam could not be blended yet.
+                        self.len = Some(index);
+                        self.overlap = self.overlap_for(index);
+                        if !self.start_replay(false) {
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+
+        let index = self.replay?;
+        let value = self.blend(index, self.region[index]);
+        let next = index + 1;
+        self.replay = Some(if Some(next) == self.len {
+            self.overlap
+        } else {
+            next
+        });
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.replay.is_some() {
+            (usize::MAX, None)
+        } else {
+            (self.input.size_hint().0, None)
+        }
+    }
+}
+
+impl<I> Source for LoopRegion<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.replay {
+            Some(_) => None,
+            None => self.input.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        // An empty region ends the source.
+        self.len != Some(0)
+    }
+
+    /// Seeking is not supported, the region is only known once it has been played.
+    #[inline]
+    fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
+        Err(SeekError::NotSupported {
+            underlying_source: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn region_repeats_after_intro() {
+        let input: Vec<i16> = (0..10).collect();
+        let output: Vec<i16> = SamplesBuffer::new(1, 1000, input)
+            .loop_region(2..5, Duration::ZERO)
+            .take(11)
+            .collect();
+        assert_eq!(output, vec![0, 1, 2, 3, 4, 2, 3, 4, 2, 3, 4]);
+    }
+
+    #[test]
+    fn whole_source_loops() {
+        let output: Vec<i16> = SamplesBuffer::new(2, 1000, vec![1i16, -1, 2, -2])
+            .loop_region(0..u64::MAX, Duration::ZERO)
+            .take(8)
+            .collect();
+        assert_eq!(output, vec![1, -1, 2, -2, 1, -1, 2, -2]);
+    }
+
+    #[test]
+    fn seam_is_crossfaded() {
+        // 2 ms at 1 kHz blends the last two frames of the region into its first two.
+        let input = vec![100i16, 100, 0, 0, 0, 0, 200, 200];
+        let output: Vec<i16> = SamplesBuffer::new(1, 1000, input)
+            .loop_region(0..8, Duration::from_millis(2))
+            .take(14)
+            .collect();
+        // The region repeats without its first two frames, which were played blended.
+        assert_eq!(output[..8], [100, 100, 0, 0, 0, 0, 150, 100]);
+        assert_eq!(output[8..], [0, 0, 0, 0, 150, 100]);
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::biquad::{Biquad, BiquadState};
+use super::SeekError;
+
+/// Quality factor of a Butterworth response, flat up to the cutoff without a resonant peak.
+pub(crate) const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Internal function that builds a `LowPass` object.
+pub fn low_pass<I>(input: I, cutoff: f32, q: f32) -> LowPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    LowPass {
+        input,
+        cutoff: CutoffHandle::new(cutoff),
+        q: q.max(0.01),
+        current_cutoff: f32::NAN,
+        sample_rate: 0,
+        biquad: None,
+        state: Vec::new(),
+        channel: 0,
+    }
+}
+
+/// Changes the cutoff frequency of a filter while it plays, see [`LowPass::cutoff_handle`]
+/// and [`HighPass::cutoff_handle`](super::HighPass::cutoff_handle).
+#[derive(Clone, Debug)]
+pub struct CutoffHandle(Arc<AtomicU32>);
+
+impl CutoffHandle {
+    pub(crate) fn new(cutoff: f32) -> CutoffHandle {
+        CutoffHandle(Arc::new(AtomicU32::new(cutoff.to_bits())))
+    }
+
+    /// Sets the cutoff frequency in Hz. The filter picks it up at the start of its next frame.
+    pub fn set_cutoff(&self, cutoff: f32) {
+        self.0.store(cutoff.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the cutoff frequency in Hz.
+    pub fn cutoff(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Second order low-pass filter with a separate state for each channel.
+#[derive(Clone, Debug)]
+pub struct LowPass<I> {
+    input: I,
+    cutoff: CutoffHandle,
+    q: f32,
+    // Cutoff and rate the coefficients were computed for, checked at every frame boundary.
+    current_cutoff: f32,
+    sample_rate: u32,
+    biquad: Option<Biquad>,
+    state: Vec<BiquadState>,
+    channel: usize,
+}
+
+impl<I> LowPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the cutoff frequency from another thread, for example to
+    /// sweep it.
+    pub fn cutoff_handle(&self) -> CutoffHandle {
+        self.cutoff.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Recomputes the coefficients if the cutoff or the format of the input changed.
+    fn update(&mut self) {
+        let channels = self.input.channels().max(1) as usize;
+        if self.state.len() != channels {
+            self.state = vec![[0.0; 4]; channels];
+        }
+
+        let (cutoff, sample_rate) = (self.cutoff.cutoff(), self.input.sample_rate());
+        if cutoff == self.current_cutoff && sample_rate == self.sample_rate {
+            return;
+        }
+        self.current_cutoff = cutoff;
+        self.sample_rate = sample_rate;
+        // The filter is only stable below the Nyquist frequency.
+        let nyquist = sample_rate as f32 / 2.0;
+        self.biquad = (sample_rate > 0)
+            .then(|| Biquad::low_pass(cutoff.clamp(1.0, nyquist * 0.99), sample_rate, self.q));
+    }
+}
+
+impl<I> Iterator for LowPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let value = self.input.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % self.state.len();
+        let Some(biquad) = &self.biquad else {
+            return Some(value);
+        };
+
+        let filtered = biquad.process(value.to_f32(), &mut self.state[channel]);
+        let filtered: <I::Item as CpalSample>::Float = CpalSample::from_sample(filtered);
+        Some(filtered.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for LowPass<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for LowPass<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.state.iter_mut().for_each(|state| *state = [0.0; 4]);
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak(samples: impl Iterator<Item = f32>) -> f32 {
+        samples.fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn attenuates_above_the_cutoff() {
+        let low = SineWave::new(100.0).low_pass(1000.0).skip(4800).take(4800);
+        let high = SineWave::new(8000.0).low_pass(1000.0).skip(4800).take(4800);
+        assert!(peak(low) > 0.95);
+        assert!(peak(high) < 0.05);
+    }
+
+    #[test]
+    fn channels_are_filtered_separately() {
+        // A loud square on the left, silence on the right.
+        let input: Vec<f32> = (0..2000)
+            .flat_map(|i| [if i % 8 < 4 { 1.0 } else { -1.0 }, 0.0])
+            .collect();
+        let output: Vec<f32> = SamplesBuffer::new(2, 48000, input)
+            .low_pass(1000.0)
+            .collect();
+        assert!(output.iter().step_by(2).any(|s| s.abs() > 0.01));
+        assert!(output.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn cutoff_changes_while_playing() {
+        let mut filter = SineWave::new(4000.0).low_pass(20000.0);
+        let handle = filter.cutoff_handle();
+        assert!(peak(filter.by_ref().skip(4800).take(4800)) > 0.9);
+
+        handle.set_cutoff(200.0);
+        assert_eq!(handle.cutoff(), 200.0);
+        assert!(peak(filter.skip(4800).take(4800)) < 0.01);
+    }
+}
@@ -121,6 +121,11 @@ where
         }
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input1.is_infinite() || self.input2.is_infinite()
+    }
+
     /// Will only attempt a seek if both underlying sources support seek.
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
@@ -2,77 +2,142 @@
 
 use core::fmt;
 use core::time::Duration;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::Receiver;
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::Receiver;
 
 use cpal::FromSample;
 
-use crate::Sample;
+use crate::{ChannelLayout, Sample};
 
+pub use self::adsr::{Adsr, AdsrHandle};
 pub use self::agc::AutomaticGainControl;
 pub use self::amplify::Amplify;
-pub use self::blt::BltFilter;
+pub use self::anti_alias::AntiAlias;
+pub use self::auto_fade_out::AutoFadeOut;
+pub use self::biquad_eq::{BiquadEq, BiquadHandle, EqFilterType};
+pub use self::buffer_health::{BufferHealth, Prebuffered};
 pub use self::buffered::Buffered;
 pub use self::channel_volume::ChannelVolume;
 pub use self::chirp::{chirp, Chirp};
-pub use self::crossfade::Crossfade;
+pub use self::clip_detect::{ClipDetect, ClipHandle};
+pub use self::combine::{combine_stereo, CombineStereo};
+pub use self::crossfade::{Crossfade, CrossfadeInto, FormatMismatch};
+pub(crate) use self::cues::cues_to;
+pub use self::cues::CuedSource;
 pub use self::delay::Delay;
 pub use self::done::Done;
+pub use self::echo::{Echo, EchoHandle};
 pub use self::empty::Empty;
 pub use self::empty_callback::EmptyCallback;
+pub use self::fadeable::{AtomicFadeDirection, FadeCurve, FadeDirection, FadeGroup, Fadeable};
 pub use self::fadein::FadeIn;
 pub use self::fadeout::FadeOut;
-pub use self::fadeable::{FadeDirection, Fadeable, AtomicFadeDirection};
 pub use self::from_factory::{from_factory, FromFactoryIter};
 pub use self::from_iter::{from_iter, FromIter};
+pub use self::gate::Gate;
+pub use self::generators::{FrequencyHandle, SawtoothWave, SineWave, SquareWave, TriangleWave};
+pub use self::haas::{Haas, Side};
+pub use self::high_pass::HighPass;
+pub use self::limiter::{Knee, Limiter};
 pub use self::linear_ramp::LinearGainRamp;
+pub use self::loop_region::LoopRegion;
+pub use self::low_pass::{CutoffHandle, LowPass};
 pub use self::mix::Mix;
+pub use self::modulate::Modulate;
+pub use self::normalize::{LoudnessHandle, Normalize};
+pub use self::normalize_peak::NormalizePeak;
+pub use self::pan::{Pan, PanHandle};
 pub use self::pausable::Pausable;
 pub use self::periodic::PeriodicAccess;
+pub use self::pitch_shift::{PitchShift, PitchShiftHandle};
 pub use self::position::TrackPosition;
-pub use self::repeat::Repeat;
+pub use self::remap::RemapChannels;
+pub use self::repeat::{Repeat, RepeatCrossfaded};
 pub use self::samples_converter::SamplesConverter;
+pub use self::sanitize::Sanitize;
+pub use self::shutdown::{ShutdownToken, WithShutdown};
 pub use self::signal_generator::{Function, SignalGenerator};
-pub use self::sine::SineWave;
 pub use self::skip::SkipDuration;
 pub use self::skippable::Skippable;
+pub use self::slew::SlewLimit;
 pub use self::spatial::Spatial;
 pub use self::speed::Speed;
+pub use self::split::SplitChannel;
+pub use self::stabilize_rate::StabilizeRate;
 pub use self::stoppable::Stoppable;
 pub use self::take::TakeDuration;
+pub use self::time_stretch::{TimeStretch, TimeStretchHandle};
+pub use self::tracked::{PlaybackHandle, Tracked};
 pub use self::uniform::UniformSourceIterator;
+pub use self::waveshape::{Shape, Transfer, Waveshape};
 pub use self::zero::Zero;
 
+mod adsr;
 mod agc;
 mod amplify;
-mod blt;
+mod anti_alias;
+mod auto_fade_out;
+mod biquad;
+mod biquad_eq;
+mod buffer_health;
 mod buffered;
 mod channel_volume;
 mod chirp;
+mod clip_detect;
+mod combine;
 mod crossfade;
+mod cues;
 mod delay;
 mod done;
+mod echo;
 mod empty;
 mod empty_callback;
+mod fadeable;
 mod fadein;
 mod fadeout;
-mod fadeable;
 mod from_factory;
 mod from_iter;
+mod gate;
+mod generators;
+mod haas;
+mod high_pass;
+mod limiter;
 mod linear_ramp;
+mod loop_region;
+mod low_pass;
 mod mix;
+mod modulate;
+mod normalize;
+mod normalize_peak;
+mod pan;
 mod pausable;
 mod periodic;
+mod pitch_shift;
 mod position;
+mod remap;
 mod repeat;
 mod samples_converter;
+mod sanitize;
+mod shutdown;
 mod signal_generator;
-mod sine;
 mod skip;
 mod skippable;
+mod slew;
 mod spatial;
 mod speed;
+mod split;
+mod stabilize_rate;
 mod stoppable;
 mod take;
+mod time_stretch;
+mod tracked;
 mod uniform;
+mod waveshape;
 mod zero;
 
 #[cfg(feature = "noise")]
@@ -80,6 +145,11 @@ mod noise;
 #[cfg(feature = "noise")]
 pub use self::noise::{pink, white, PinkNoise, WhiteNoise};
 
+#[cfg(feature = "wav")]
+mod record;
+#[cfg(feature = "wav")]
+pub use self::record::RecordTo;
+
 /// A source of samples.
 ///
 /// # A quick lesson about sounds
@@ -148,6 +218,12 @@ pub use self::noise::{pink, white, PinkNoise, WhiteNoise};
 /// the number of samples that remain in the iterator before the samples rate and number of
 /// channels can potentially change.
 ///
+/// # Empty sources
+///
+/// A source may end before returning a single sample. The adapters of this trait pass such a
+/// source through as an empty source: they neither panic nor play silence of their own, and
+/// repeating adapters end instead of looping over nothing.
+///
 pub trait Source: Iterator
 where
     Self::Item: Sample,
@@ -171,8 +247,18 @@ where
     /// `None` indicates at the same time "infinite" or "unknown".
     fn total_duration(&self) -> Option<Duration>;
 
-    /// Stores the source in a buffer in addition to returning it. This iterator can be cloned.
+    /// Returns true if the source never ends on its own.
+    ///
+    /// Unlike a `None` [`total_duration`](Source::total_duration) this is not ambiguous: sources
+    /// of unknown length return false. Generators such as [`SineWave`] and
+    /// [`repeat_infinite`](Source::repeat_infinite) return true, adapters forward the value of
+    /// the source they wrap. The default implementation returns false.
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        false
+    }
 
+    /// Stores the source in a buffer in addition to returning it. This iterator can be cloned.
     #[inline]
     fn buffered(self) -> Buffered<Self>
     where
@@ -181,6 +267,23 @@ where
         buffered::buffered(self)
     }
 
+    /// Declares the speaker layout of this source and moves its channels to the speakers of
+    /// another layout.
+    ///
+    /// Sources are otherwise assumed to use the default layout of their channel count, see
+    /// [`ChannelLayout`] for the supported layouts and how channels are matched.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the source does not have as many channels as `from`.
+    #[inline]
+    fn remap_channels(self, from: ChannelLayout, to: ChannelLayout) -> RemapChannels<Self>
+    where
+        Self: Sized,
+    {
+        remap::remap_channels(self, from, to)
+    }
+
     /// Mixes this source with another one.
     #[inline]
     fn mix<S>(self, other: S) -> Mix<Self, S>
@@ -205,6 +308,43 @@ where
         repeat::repeat(self)
     }
 
+    /// Repeats this source forever, crossfading the end of each iteration into the start of
+    /// the next one over `overlap`.
+    ///
+    /// This hides the click that [`repeat_infinite`](Source::repeat_infinite) can produce when
+    /// the source does not start and end on a zero crossing. Each iteration after the first one
+    /// is shortened by `overlap`. An `overlap` of zero behaves like `repeat_infinite`.
+    ///
+    /// Like `repeat_infinite` this stores the data in a buffer.
+    #[inline]
+    fn repeat_infinite_crossfaded(self, overlap: Duration) -> RepeatCrossfaded<Self>
+    where
+        Self: Sized,
+    {
+        repeat::repeat_crossfaded(self, overlap)
+    }
+
+    /// Plays the sound up to the end of a region of frames, then repeats that region forever.
+    ///
+    /// This is how game music usually loops: an intro plays once, then the body of the track
+    /// repeats seamlessly. `frames` is a range of frames from the start of the sound, for
+    /// example the [loop points](crate::Decoder::loop_points) of a decoded file. A range ending
+    /// at `u64::MAX` loops up to the end of the sound.
+    ///
+    /// If the loop points do not line up perfectly, a `crossfade` other than zero blends the
+    /// end of the region into its start, shortening each repetition by that much. It is limited
+    /// to half of the region.
+    ///
+    /// The region is stored in memory while it first plays and repeated from there. Seeking is
+    /// not supported.
+    #[inline]
+    fn loop_region(self, frames: std::ops::Range<u64>, crossfade: Duration) -> LoopRegion<Self>
+    where
+        Self: Sized,
+    {
+        loop_region::loop_region(self, frames, crossfade)
+    }
+
     /// Takes a certain duration of this source and then stops.
     #[inline]
     fn take_duration(self, duration: Duration) -> TakeDuration<Self>
@@ -214,6 +354,40 @@ where
         take::take_duration(self, duration)
     }
 
+    /// Widens a mono source to stereo by delaying one channel (the Haas effect).
+    ///
+    /// Both channels play the source, but the channel on `side` lags behind by `delay`. Delays
+    /// of about 1 to 30 milliseconds are heard as a wider sound rather than an echo, with the
+    /// sound pulled towards the other side. A `delay` of zero plays the source centered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source does not have exactly one channel.
+    #[inline]
+    fn haas(self, delay: Duration, side: Side) -> Haas<Self>
+    where
+        Self: Sized,
+    {
+        haas::haas(self, delay, side)
+    }
+
+    /// Moves a sound between the left (-1.0) and the right (1.0) channel.
+    ///
+    /// Uses an equal-power law, so the perceived loudness does not dip in between: the channel
+    /// gains are the cosine and sine of the position mapped to a quarter turn, scaled to 1.0 in
+    /// the center. The sum of their squares stays constant, which means the remaining channel
+    /// gets up to 3 dB louder at the sides. Mono sources are upmixed to stereo first. Of sources
+    /// with more than two channels only the first pair is panned, the others pass through.
+    ///
+    /// The position can be changed while playing through [`Pan::handle`].
+    #[inline]
+    fn pan(self, position: f32) -> Pan<Self>
+    where
+        Self: Sized,
+    {
+        pan::pan(self, position)
+    }
+
     /// Delays the sound by a certain duration.
     ///
     /// The rate and channels of the silence will use the same format as the first frame of the
@@ -226,6 +400,19 @@ where
         delay::delay(self, duration)
     }
 
+    /// Delays the sound by exactly `frames` samples of silence on every channel.
+    ///
+    /// Unlike [`delay`](Source::delay) the delay is not rounded to the sample rate, which makes
+    /// it suited to compensate the latency of an effect: delaying the dry branch of a split
+    /// source by the latency of the wet branch keeps both aligned when they are mixed again.
+    #[inline]
+    fn delay_samples(self, frames: usize) -> Delay<Self>
+    where
+        Self: Sized,
+    {
+        delay::delay_samples(self, frames)
+    }
+
     /// Immediately skips a certain duration of this source.
     ///
     /// If the specified duration is longer than the source itself, `skip_duration` will skip to the end of the source.
@@ -237,6 +424,48 @@ where
         skip::skip_duration(self, duration)
     }
 
+    /// Splits a stereo source into its left and right channel, as two mono sources.
+    ///
+    /// Both halves read from the same input, a frame at a time, so they stay aligned with each
+    /// other. When one half is pulled further than the other, the samples of the lagging half
+    /// are buffered until it catches up; the buffer grows without limit if a half is never
+    /// pulled. Seeking either half seeks both.
+    ///
+    /// Use [`combine_stereo`] to join the halves again after processing them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the source does not have exactly two channels.
+    fn split_stereo(self) -> (SplitChannel<Self>, SplitChannel<Self>)
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        split::split_stereo(self)
+    }
+
+    /// Resamples the sound to a fixed sample rate, even if the rate of the sound changes midway.
+    ///
+    /// Sources normally only change their sample rate at the end of a frame (see
+    /// [`current_frame_len`](Source::current_frame_len)). Some streams, for example adaptive
+    /// internet radio, change it at any sample. This adapter queries the input rate on every
+    /// frame and adjusts its resampling ratio right away, so the output always runs at
+    /// `target`. The channel count must not change.
+    ///
+    /// Uses linear interpolation between frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` is 0.
+    #[inline]
+    fn stabilize_rate(self, target: u32) -> StabilizeRate<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        stabilize_rate::stabilize_rate(self, target)
+    }
+
     /// Amplifies the sound by the given value.
     #[inline]
     fn amplify(self, value: f32) -> Amplify<Self>
@@ -304,6 +533,11 @@ where
     /// # Example (Quick start)
     ///
     /// ```rust
+    /// # use rodio::Source;
+    /// # let source = rodio::source::SineWave::new(440.0);
+    /// # let (sink, _queue_output) = rodio::Sink::new_idle();
+    /// # #[cfg(feature = "experimental")]
+    /// # {
     /// // Apply Automatic Gain Control to the source (AGC is on by default)
     /// let agc_source = source.automatic_gain_control(1.0, 4.0, 0.005, 5.0);
     ///
@@ -318,6 +552,7 @@ where
     ///
     /// // Note: Using agc_control is optional. If you don't need to toggle AGC,
     /// // you can simply use the agc_source directly without getting agc_control.
+    /// # }
     /// ```
     #[inline]
     fn automatic_gain_control(
@@ -345,6 +580,50 @@ where
         )
     }
 
+    /// Steers the loudness of the source towards `target_lufs`, for example -16 for music or
+    /// -23 for broadcast.
+    ///
+    /// The short-term loudness is measured as described in ITU-R BS.1770 and the gain follows
+    /// it, a limiter keeps the raised peaks below -1 dBFS. The loudness measured so far can be
+    /// read through [`Normalize::loudness`]. See [`normalize_peak`](Source::normalize_peak)
+    /// for scaling by the peaks instead.
+    #[inline]
+    fn normalize(self, target_lufs: f32) -> Normalize<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        normalize::normalize(self, target_lufs)
+    }
+
+    /// Scales the source so its loudest sample reaches `target_peak`, 1.0 being full scale.
+    ///
+    /// The output is delayed by a short look-ahead, which lets the gain be lowered in time for
+    /// louder samples later on. See [`NormalizePeak`] for details.
+    #[inline]
+    fn normalize_peak(self, target_peak: f32) -> NormalizePeak<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        normalize_peak::normalize_peak(self, target_peak)
+    }
+
+    /// Keeps the peaks of the source below `ceiling`, an amplitude where 1.0 is full scale.
+    ///
+    /// The limiter looks 5 ms ahead, so transients are caught instead of clipped, and the gain
+    /// recovers over `release` once the peaks are gone. Unlike the limiter of the mixer this
+    /// protects a single source at its own stage. The knee is hard by default, see
+    /// [`Limiter::set_knee`].
+    #[inline]
+    fn limit(self, ceiling: f32, release: Duration) -> Limiter<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        limiter::limit(self, ceiling, release)
+    }
+
     /// Mixes this sound fading out with another sound fading in for the given duration.
     ///
     /// Only the crossfaded portion (beginning of self, beginning of other) is returned.
@@ -358,6 +637,27 @@ where
         crossfade::crossfade(self, other, duration)
     }
 
+    /// Fades this sound out while fading `next` in over `duration`, then plays on with `next`.
+    ///
+    /// During the overlap the samples of both sounds are summed, afterwards only `next` is
+    /// played. Unlike [`take_crossfade_with`](Source::take_crossfade_with) the result is the
+    /// whole transition, which makes it a building block for gapless playlists. Both sounds
+    /// must have the same channel count and sample rate, otherwise [`FormatMismatch`] is
+    /// returned.
+    #[inline]
+    fn crossfade_into<B>(
+        self,
+        next: B,
+        duration: Duration,
+    ) -> Result<CrossfadeInto<Self, B>, FormatMismatch>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+        B: Source<Item = Self::Item>,
+    {
+        crossfade::crossfade_into(self, next, duration)
+    }
+
     /// Fades in the sound.
     #[inline]
     fn fade_in(self, duration: Duration) -> FadeIn<Self>
@@ -376,12 +676,111 @@ where
         fadeout::fadeout(self, duration)
     }
 
+    /// Fades out the last `duration` of the sound to silence.
+    ///
+    /// The end is found with [`total_duration`](Source::total_duration), so no handle has to be
+    /// triggered at the right time. Sounds of unknown length are left unchanged.
+    #[inline]
+    fn auto_fade_out(self, duration: Duration) -> AutoFadeOut<Self>
+    where
+        Self: Sized,
+    {
+        auto_fade_out::auto_fade_out(self, duration)
+    }
+
+    /// Makes the sound fadeable. The returned handle can be used to fade the sound in or out
+    /// over the given duration at any time.
     #[inline]
     fn fadeable(self, duration: Duration) -> (Fadeable<Self>, AtomicFadeDirection)
     where
         Self: Sized,
     {
-        fadeable::fadeable(self, duration)
+        fadeable::fadeable(self, duration, FadeCurve::Linear)
+    }
+
+    /// Makes the sound fadeable like [`fadeable`](Source::fadeable), with separate durations
+    /// for fading in and fading out.
+    ///
+    /// A fade that interrupts a fade the other way starts over with the full duration of its
+    /// own direction.
+    #[inline]
+    fn fadeable_asymmetric(
+        self,
+        fade_in: Duration,
+        fade_out: Duration,
+    ) -> (Fadeable<Self>, AtomicFadeDirection)
+    where
+        Self: Sized,
+    {
+        fadeable::fadeable_asymmetric(self, fade_in, fade_out, FadeCurve::Linear)
+    }
+
+    /// Makes the sound fadeable like [`fadeable`](Source::fadeable), with fades that change the
+    /// level by `db_per_sec` decibels per second.
+    ///
+    /// A fade runs between `floor_db`, for example -60.0, and 0 dB, the floor is treated as
+    /// silence. A fade therefore takes `floor_db / db_per_sec` seconds, see
+    /// [`FadeCurve::Decibels`].
+    #[inline]
+    fn fadeable_db(self, db_per_sec: f32, floor_db: f32) -> (Fadeable<Self>, AtomicFadeDirection)
+    where
+        Self: Sized,
+    {
+        fadeable::fadeable_db(self, db_per_sec, floor_db)
+    }
+
+    /// Makes the sound fadeable like [`fadeable`](Source::fadeable), with the gain following
+    /// `curve` during a fade instead of changing linearly.
+    #[inline]
+    fn fadeable_with_curve(
+        self,
+        duration: Duration,
+        curve: FadeCurve,
+    ) -> (Fadeable<Self>, AtomicFadeDirection)
+    where
+        Self: Sized,
+    {
+        fadeable::fadeable(self, duration, curve)
+    }
+
+    /// Makes the sound fadeable like [`fadeable`](Source::fadeable), and also returns a
+    /// receiver that gets the direction of every fade once it finished.
+    ///
+    /// A fade-out finished when the gain reached 0.0, a fade-in when it reached 1.0. Use it
+    /// for example to swap tracks only once the music faded out. Sending never blocks the
+    /// audio thread, and dropping the receiver is fine.
+    ///
+    /// Enable the feature flag `crossbeam-channel` in rodio to use a `crossbeam_channel::Receiver`
+    /// instead.
+    #[inline]
+    fn fadeable_with_completion(
+        self,
+        duration: Duration,
+    ) -> (Fadeable<Self>, AtomicFadeDirection, Receiver<FadeDirection>)
+    where
+        Self: Sized,
+    {
+        fadeable::fadeable_with_completion(self, duration)
+    }
+
+    /// Applies an attack-decay-sustain-release amplitude envelope to the sound.
+    ///
+    /// The volume rises from silence to full over `attack`, falls to the `sustain` level over
+    /// `decay` and then stays there. Calling [`AdsrHandle::note_off`] on the returned handle
+    /// ramps the volume from its current level to silence over `release`, after which the
+    /// source ends. Timings are computed from the sample rate of the source.
+    #[inline]
+    fn adsr(
+        self,
+        attack: Duration,
+        decay: Duration,
+        sustain: f32,
+        release: Duration,
+    ) -> (Adsr<Self>, AdsrHandle)
+    where
+        Self: Sized,
+    {
+        adsr::adsr(self, attack, decay, sustain, release)
     }
 
     /// Applies a linear gain ramp to the sound.
@@ -444,6 +843,49 @@ where
         speed::speed(self, ratio)
     }
 
+    /// Changes the pitch of the source by `semitones` without changing its speed or duration.
+    ///
+    /// Negative values lower the pitch, 12 semitones are an octave. The shift can be changed
+    /// while playing through [`PitchShift::handle`]. Shifts by more than an octave or so
+    /// become audibly grainy. See [`pitch_shift_with_window`](Source::pitch_shift_with_window)
+    /// to trade smoothness for timing precision.
+    #[inline]
+    fn pitch_shift(self, semitones: f32) -> PitchShift<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        pitch_shift::pitch_shift(self, semitones, pitch_shift::DEFAULT_WINDOW)
+    }
+
+    /// Changes the pitch of the source while allowing the length of the grains to be chosen.
+    ///
+    /// The default is 50 ms. Longer windows sound smoother on sustained and low sounds, shorter
+    /// ones smear transients less. See [`pitch_shift`](Source::pitch_shift).
+    #[inline]
+    fn pitch_shift_with_window(self, semitones: f32, window: Duration) -> PitchShift<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        pitch_shift::pitch_shift(self, semitones, window)
+    }
+
+    /// Changes the tempo of the source without changing its pitch.
+    ///
+    /// `ratio` is how much faster the source plays, 0.8 slows a podcast down and 1.5 speeds it
+    /// up, the total duration is divided by it. The ratio can be changed while playing through
+    /// [`TimeStretch::handle`]. Unlike [`speed`](Source::speed) the pitch stays the same, at
+    /// the cost of some smearing of transients.
+    #[inline]
+    fn stretch(self, ratio: f32) -> TimeStretch<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        time_stretch::stretch(self, ratio)
+    }
+
     /// Adds a basic reverb effect.
     ///
     /// This function requires the source to implement `Clone`. This can be done by using
@@ -465,6 +907,21 @@ where
         self.mix(echo)
     }
 
+    /// Adds repeating echoes that fade out, for example for a cave ambience.
+    ///
+    /// Each echo follows the previous one after `delay` and is `feedback` times as loud, the
+    /// feedback is clamped to 0.95. `mix` is the level of the echoes relative to the original
+    /// sound. Unlike [`reverb`](Source::reverb) the source does not need to be `Clone`, and
+    /// the parameters can be changed while playing through [`Echo::handle`].
+    #[inline]
+    fn echo(self, delay: Duration, feedback: f32, mix: f32) -> Echo<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        echo::echo(self, delay, feedback, mix)
+    }
+
     /// Converts the samples of this source to another type.
     #[inline]
     fn convert_samples<D>(self) -> SamplesConverter<Self, D>
@@ -475,6 +932,150 @@ where
         SamplesConverter::new(self)
     }
 
+    /// Replaces NaN and infinite samples with silence.
+    ///
+    /// A buggy source or an unstable filter can produce such samples, which the device turns
+    /// into loud pops. Wrapping a source with this adapter keeps them from reaching the output.
+    /// With the `tracing` feature a warning is logged the first time a sample is replaced.
+    #[inline]
+    fn sanitize(self) -> Sanitize<Self>
+    where
+        Self: Sized,
+    {
+        sanitize::sanitize(self)
+    }
+
+    /// Limits how much each sample may differ from the previous sample of the same channel.
+    ///
+    /// `max_delta_per_sample` is relative to full scale, where `1.0` is the distance from
+    /// silence to the loudest `f32` sample. Sudden jumps, such as clicks or the edges of a
+    /// square wave, are turned into ramps with at most that slope, while signals changing
+    /// slowly enough pass through untouched. Unlike a low-pass filter this acts on the slope
+    /// alone, making it a cheap safety net that protects tweeters and takes the edge off harsh
+    /// transients. Small limits also dull loud high frequencies, which cannot rise fast enough.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_delta_per_sample` is not positive.
+    #[inline]
+    fn slew_limit(self, max_delta_per_sample: f32) -> SlewLimit<Self>
+    where
+        Self: Sized,
+    {
+        slew::slew_limit(self, max_delta_per_sample)
+    }
+
+    /// Writes the samples of this source to a WAV file at `path` as they are played.
+    ///
+    /// The samples pass through unchanged, so applying this after some effects records exactly
+    /// what this source contributes to the mix. The file is a 32 bit float WAV with the channel
+    /// count and sample rate the source has when this is called. It is written on a separate
+    /// thread and finished when the source ends or is dropped. Seeking does not rewrite the
+    /// file, it simply continues with the samples played after the seek.
+    ///
+    /// Returns an error if the file can not be created.
+    #[cfg(feature = "wav")]
+    #[inline]
+    fn record_to<P>(self, path: P) -> std::io::Result<RecordTo<Self>>
+    where
+        Self: Sized,
+        P: AsRef<std::path::Path>,
+    {
+        record::record_to(self, path.as_ref())
+    }
+
+    /// Sends each cue through the returned channel when playback reaches its timestamp.
+    ///
+    /// This turns a source into a timeline driver, for example for lyrics, subtitles or game
+    /// events synchronized with music. Timestamps are relative to the start of this source and
+    /// counted in played samples, so they stay exact regardless of pauses or speed changes
+    /// applied before this adapter. Every cue fires once, in the order of the timestamps; cues
+    /// with equal timestamps fire in the order they were given. After a seek the cues at or
+    /// after the new position fire again, the ones before it are skipped.
+    ///
+    /// A cue is sent when the sample at its timestamp is pulled from the source. The audio
+    /// thread pulls whole device buffers at once, ahead of them being heard, so a cue can arrive
+    /// up to about one buffer length (typically 5 to 20 milliseconds) before the sound it
+    /// belongs to plays. Sending never blocks the audio thread.
+    ///
+    /// Enable the feature flag `crossbeam-channel` in rodio to use a `crossbeam_channel::Receiver`
+    /// instead.
+    #[inline]
+    fn with_cues<C>(self, cues: Vec<(Duration, C)>) -> (CuedSource<Self, C>, Receiver<C>)
+    where
+        Self: Sized,
+        C: Clone + Send + 'static,
+    {
+        cues::with_cues(self, cues)
+    }
+
+    /// Multiplies each sample by the current sample of a control source.
+    ///
+    /// The control is read in lockstep with this source and its samples are used as gain, so
+    /// it is usually between 0 and 1: a slow sine shifted to that range gives a tremolo, a
+    /// recorded envelope shapes the loudness of this source, and an audible sine between -1
+    /// and 1 gives ring modulation. The control is converted to mono and resampled to the
+    /// sample rate this source has when this is called; all channels of a frame get the same
+    /// gain. The output ends as soon as either source ends.
+    #[inline]
+    fn modulate_by<C>(self, control: C) -> Modulate<Self, C>
+    where
+        Self: Sized,
+        C: Source,
+        C::Item: Sample,
+    {
+        modulate::modulate_by(self, control)
+    }
+
+    /// Counts the samples that clip, for finding loudness and mixing problems.
+    ///
+    /// A sample clips when it reaches or exceeds full scale: an absolute value of at least 1.0
+    /// for floating point samples, or of the largest positive value for integer samples.
+    /// The returned [`ClipHandle`] reports the number of clipped samples and the position of
+    /// the first one. The samples pass through unchanged unless
+    /// [`ClipDetect::set_clamp`] is enabled.
+    #[inline]
+    fn clip_detect(self) -> (ClipDetect<Self>, ClipHandle)
+    where
+        Self: Sized,
+    {
+        clip_detect::clip_detect(self)
+    }
+
+    /// Reads the sound ahead of playback on a worker thread, and reports how much is buffered.
+    ///
+    /// The worker keeps up to `target` of audio buffered; the returned [`BufferHealth`] tells
+    /// how much currently is, see there for the low-water mark. Meant for streams whose reader
+    /// may stall, such as network downloads: while the buffer is empty silence is played
+    /// instead of blocking the audio thread. The first sample is read right away on the
+    /// calling thread. The channel count and sample rate of the sound must not change, and
+    /// the result can not seek.
+    #[inline]
+    fn with_buffer_health(self, target: Duration) -> (Prebuffered<Self::Item>, BufferHealth)
+    where
+        Self: Sized + Send + 'static,
+        Self::Item: Sample + Send,
+    {
+        buffer_health::with_buffer_health(self, target)
+    }
+
+    /// Maps every sample through a transfer function, to distort or saturate the sound.
+    ///
+    /// `shape` is one of the built-in [`Shape`]s or a closure such as `f32::tanh` for soft
+    /// saturation or a polynomial for fuzz. Samples are passed in the range -1.0 to 1.0,
+    /// amplify the sound beforehand to drive the shaper harder.
+    ///
+    /// A nonlinear transfer function creates harmonics that can exceed the Nyquist frequency
+    /// and alias; see [`Waveshape::set_oversampling`] to reduce that.
+    #[inline]
+    fn waveshape<F>(self, shape: F) -> Waveshape<Self, F>
+    where
+        Self: Sized,
+        F: Transfer,
+    {
+        waveshape::waveshape(self, shape)
+    }
+
     /// Makes the sound pausable.
     // TODO: add example
     #[inline]
@@ -485,6 +1086,21 @@ where
         pausable::pausable(self, initially_paused)
     }
 
+    /// Passes the sound through only while `enabled` is true, for example for push-to-talk.
+    ///
+    /// The flag is read once per frame, so it can be flipped from any thread and takes effect
+    /// almost immediately. Opening and closing the gate fades over 5 milliseconds to avoid
+    /// clicks. While the gate is closed silence is played; if `advance_when_closed` is true the
+    /// source keeps playing unheard in the meantime, otherwise it is held where it was closed
+    /// and resumes from there.
+    #[inline]
+    fn gated_by(self, enabled: Arc<AtomicBool>, advance_when_closed: bool) -> Gate<Self>
+    where
+        Self: Sized,
+    {
+        gate::gated_by(self, enabled, advance_when_closed)
+    }
+
     /// Makes the sound stoppable.
     // TODO: add example
     #[inline]
@@ -505,6 +1121,18 @@ where
         skippable::skippable(self)
     }
 
+    /// Ends this source when the given [`ShutdownToken`] is signalled.
+    ///
+    /// Once the token is signalled the source fades out over the token's fade duration and
+    /// then ends. A single token can be shared by many sources to stop all of them together,
+    /// for example when the application shuts down.
+    fn with_shutdown(self, token: ShutdownToken) -> WithShutdown<Self>
+    where
+        Self: Sized,
+    {
+        shutdown::with_shutdown(self, token)
+    }
+
     /// Start tracking the elapsed duration since the start of the underlying
     /// source.
     ///
@@ -522,45 +1150,73 @@ where
         position::track_position(self)
     }
 
-    /// Applies a low-pass filter to the source.
-    /// **Warning**: Probably buggy.
+    /// Applies a low-pass filter to the source, which removes the frequencies above
+    /// `cutoff_hz`, for example to muffle a sound heard through a wall or under water.
+    ///
+    /// The cutoff can be swept while playing through [`LowPass::cutoff_handle`]. Every channel
+    /// is filtered separately and the coefficients follow the sample rate of the source.
     #[inline]
-    fn low_pass(self, freq: u32) -> BltFilter<Self>
+    fn low_pass(self, cutoff_hz: f32) -> LowPass<Self>
     where
         Self: Sized,
-        Self: Source<Item = f32>,
+        Self::Item: Sample,
     {
-        blt::low_pass(self, freq)
+        low_pass::low_pass(self, cutoff_hz, low_pass::BUTTERWORTH_Q)
     }
 
-    /// Applies a high-pass filter to the source.
+    /// Applies a high-pass filter to the source, which removes the frequencies below
+    /// `cutoff_hz`, for example rumble and DC offset in voice chat.
+    ///
+    /// The cutoff can be changed while playing through [`HighPass::cutoff_handle`]. Every
+    /// channel is filtered separately and the coefficients follow the sample rate of the source.
     #[inline]
-    fn high_pass(self, freq: u32) -> BltFilter<Self>
+    fn high_pass(self, cutoff_hz: f32) -> HighPass<Self>
     where
         Self: Sized,
-        Self: Source<Item = f32>,
+        Self::Item: Sample,
     {
-        blt::high_pass(self, freq)
+        high_pass::high_pass(self, cutoff_hz, low_pass::BUTTERWORTH_Q)
     }
 
-    /// Applies a low-pass filter to the source while allowing the q (bandwidth) to be changed.
+    /// Applies a low-pass filter to the source while allowing the q to be changed.
+    ///
+    /// A q above 0.7 boosts the frequencies around the cutoff, the higher the more resonant
+    /// the filter sounds. See [`low_pass`](Source::low_pass).
     #[inline]
-    fn low_pass_with_q(self, freq: u32, q: f32) -> BltFilter<Self>
+    fn low_pass_with_q(self, cutoff_hz: f32, q: f32) -> LowPass<Self>
     where
         Self: Sized,
-        Self: Source<Item = f32>,
+        Self::Item: Sample,
     {
-        blt::low_pass_with_q(self, freq, q)
+        low_pass::low_pass(self, cutoff_hz, q)
     }
 
-    /// Applies a high-pass filter to the source while allowing the q (bandwidth) to be changed.
+    /// Applies a high-pass filter to the source while allowing the q to be changed.
+    ///
+    /// A q above 0.7 boosts the frequencies around the cutoff. See
+    /// [`high_pass`](Source::high_pass).
     #[inline]
-    fn high_pass_with_q(self, freq: u32, q: f32) -> BltFilter<Self>
+    fn high_pass_with_q(self, cutoff_hz: f32, q: f32) -> HighPass<Self>
     where
         Self: Sized,
-        Self: Source<Item = f32>,
+        Self::Item: Sample,
     {
-        blt::high_pass_with_q(self, freq, q)
+        high_pass::high_pass(self, cutoff_hz, q)
+    }
+
+    /// Applies one band of a parametric equalizer to the source.
+    ///
+    /// `freq` is the center of a peaking band or the corner of a shelf in Hz, `q` sets the
+    /// width of the band or the steepness of the shelf, and `gain_db` boosts or cuts. Chain
+    /// several calls for a multi-band equalizer. The parameters can be changed while playing
+    /// through [`BiquadEq::handle`], the coefficients are only recomputed when they change.
+    #[inline]
+    fn biquad(self, filter_type: EqFilterType, freq: f32, q: f32, gain_db: f32) -> BiquadEq<Self>
+    where
+        Self: Sized,
+        Self::Item: Sample,
+    {
+        biquad_eq::biquad(self, filter_type, freq, q, gain_db)
     }
 
     // There is no `can_seek()` method as it is impossible to use correctly. Between
@@ -609,6 +1265,15 @@ pub enum SeekError {
     #[cfg(feature = "wav")]
     /// The hound (wav) decoder ran into an issue
     HoundDecoder(std::io::Error),
+    #[cfg(feature = "flac")]
+    /// The claxon (flac) decoder ran into an issue while restarting the file
+    ClaxonDecoder(claxon::Error),
+    #[cfg(feature = "vorbis")]
+    /// The lewton (vorbis) decoder ran into an issue while restarting the file
+    LewtonDecoder(lewton::VorbisError),
+    #[cfg(feature = "opus")]
+    /// The opus decoder ran into an issue while restarting the file
+    OpusDecoder(ogg::OggReadError),
     // Prefer adding an enum variant to using this. Its meant for end users their
     // own try_seek implementations
     /// Any other error probably in a custom Source
@@ -628,6 +1293,12 @@ impl fmt::Display for SeekError {
             SeekError::SymphoniaDecoder(err) => write!(f, "Error seeking: {}", err),
             #[cfg(feature = "wav")]
             SeekError::HoundDecoder(err) => write!(f, "Error seeking in wav source: {}", err),
+            #[cfg(feature = "flac")]
+            SeekError::ClaxonDecoder(err) => write!(f, "Error seeking in flac source: {}", err),
+            #[cfg(feature = "vorbis")]
+            SeekError::LewtonDecoder(err) => write!(f, "Error seeking in vorbis source: {}", err),
+            #[cfg(feature = "opus")]
+            SeekError::OpusDecoder(err) => write!(f, "Error seeking in opus source: {}", err),
             SeekError::Other(_) => write!(f, "An error occurred"),
         }
     }
@@ -640,6 +1311,12 @@ impl std::error::Error for SeekError {
             SeekError::SymphoniaDecoder(err) => Some(err),
             #[cfg(feature = "wav")]
             SeekError::HoundDecoder(err) => Some(err),
+            #[cfg(feature = "flac")]
+            SeekError::ClaxonDecoder(err) => Some(err),
+            #[cfg(feature = "vorbis")]
+            SeekError::LewtonDecoder(err) => Some(err),
+            #[cfg(feature = "opus")]
+            SeekError::OpusDecoder(err) => Some(err),
             SeekError::Other(err) => Some(err.as_ref()),
         }
     }
@@ -660,6 +1337,12 @@ impl SeekError {
             SeekError::SymphoniaDecoder(_) => false,
             #[cfg(feature = "wav")]
             SeekError::HoundDecoder(_) => false,
+            #[cfg(feature = "flac")]
+            SeekError::ClaxonDecoder(_) => false,
+            #[cfg(feature = "vorbis")]
+            SeekError::LewtonDecoder(_) => false,
+            #[cfg(feature = "opus")]
+            SeekError::OpusDecoder(_) => false,
             SeekError::Other(_) => false,
         }
     }
@@ -688,6 +1371,11 @@ macro_rules! source_pointer_impl {
                 (**self).total_duration()
             }
 
+            #[inline]
+            fn is_infinite(&self) -> bool {
+                (**self).is_infinite()
+            }
+
             #[inline]
             fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
                 (**self).try_seek(pos)
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::{SeekError, UniformSourceIterator};
+
+/// Internal function that builds a `Modulate` object.
+pub fn modulate_by<I, C>(input: I, control: C) -> Modulate<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    C: Source,
+    C::Item: Sample,
+{
+    let sample_rate = input.sample_rate();
+    Modulate {
+        input,
+        control: UniformSourceIterator::new(control, 1, sample_rate),
+        gain: 0.0,
+        sample_in_frame: 0,
+    }
+}
+
+/// Filter that multiplies a source by a control source, sample by sample.
+#[derive(Clone)]
+pub struct Modulate<I, C>
+where
+    C: Source,
+    C::Item: Sample,
+{
+    input: I,
+    control: UniformSourceIterator<C, C::Item>,
+    // Control value of the current frame.
+    gain: f32,
+    sample_in_frame: u16,
+}
+
+impl<I, C> Modulate<I, C>
+where
+    C: Source,
+    C::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I, C> Iterator for Modulate<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    C: Source,
+    C::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            self.gain = self.control.next()?.to_f32();
+        }
+        let value = self.input.next()?;
+        self.sample_in_frame = (self.sample_in_frame + 1) % self.input.channels().max(1);
+        Some(value.amplify(self.gain))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I, C> Source for Modulate<I, C>
+where
+    I: Source,
+    I::Item: Sample,
+    C: Source,
+    C::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        match (self.input.total_duration(), self.control.total_duration()) {
+            (Some(input), Some(control)) => Some(input.min(control)),
+            (input, None) if self.control.is_infinite() => input,
+            (None, control) if self.input.is_infinite() => control,
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite() && self.control.is_infinite()
+    }
+
+    /// Seeks both the source and the control source, which keeps them in lockstep.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.control.try_seek(pos)?;
+        self.sample_in_frame = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn tremolo_follows_control() {
+        // A 5 Hz sine shifted to 0..1 as the gain of a constant signal.
+        let control = SineWave::new(5.0).amplify(0.5).map(|value| value + 0.5);
+        let control = SamplesBuffer::new(1, 48000, control.take(48000).collect::<Vec<f32>>());
+        let output: Vec<f32> = SamplesBuffer::new(2, 1000, vec![1.0f32; 2000])
+            .modulate_by(control)
+            .collect();
+        assert_eq!(output.len(), 2000);
+
+        // Both channels get the same gain.
+        assert!(output.chunks(2).all(|frame| frame[0] == frame[1]));
+        let envelope: Vec<f32> = output.iter().step_by(2).copied().collect();
+        assert!(envelope.iter().all(|&gain| (0.0..=1.0).contains(&gain)));
+        // Five periods in one second: the envelope falls through 0.5 five times.
+        let falling = envelope
+            .windows(2)
+            .filter(|pair| pair[0] >= 0.5 && pair[1] < 0.5)
+            .count();
+        assert_eq!(falling, 5);
+    }
+}
@@ -8,6 +8,10 @@ use super::SeekError;
 
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
+/// Brings the output of the pink noise filters into [-1.0, 1.0]. For white noise in that range
+/// the filters sum to at most 69.14, the sum of their coefficients over one minus their poles.
+const PINK_GAIN: f32 = 1.0 / 69.2;
+
 /// Convenience function to create a new `WhiteNoise` noise source.
 #[inline]
 pub fn white(sample_rate: cpal::SampleRate) -> WhiteNoise {
@@ -38,11 +42,14 @@ impl WhiteNoise {
     }
 
     /// Create a new white noise generator, seeding the RNG with system entropy.
+    ///
+    /// In [deterministic mode](crate::deterministic) the seed comes from a fixed sequence.
     pub fn new(sample_rate: cpal::SampleRate) -> Self {
-        Self {
-            sample_rate,
-            rng: SmallRng::from_entropy(),
-        }
+        let rng = match crate::deterministic::next_seed() {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+        Self { sample_rate, rng }
     }
 }
 
@@ -78,6 +85,11 @@ impl Source for WhiteNoise {
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, _: std::time::Duration) -> Result<(), SeekError> {
         // Does nothing, should do nothing
@@ -92,16 +104,28 @@ impl Source for WhiteNoise {
 /// method][pk_method] from *musicdsp.org*.
 ///
 /// [pk_method]: https://www.musicdsp.org/en/latest/Filters/76-pink-noise-filter.html
+#[derive(Clone, Debug)]
 pub struct PinkNoise {
     white_noise: WhiteNoise,
     b: [f32; 7],
 }
 
 impl PinkNoise {
+    /// Create a new pink noise generator, seeding the RNG with system entropy.
+    ///
+    /// In [deterministic mode](crate::deterministic) the seed comes from a fixed sequence.
     pub fn new(sample_rate: cpal::SampleRate) -> Self {
         Self {
             white_noise: WhiteNoise::new(sample_rate),
-            b: [0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32, 0.0f32],
+            b: [0.0f32; 7],
+        }
+    }
+
+    /// Create a new pink noise generator, seeding the RNG with `seed`.
+    pub fn new_with_seed(sample_rate: cpal::SampleRate, seed: u64) -> Self {
+        Self {
+            white_noise: WhiteNoise::new_with_seed(sample_rate, seed),
+            b: [0.0f32; 7],
         }
     }
 }
@@ -129,7 +153,7 @@ impl Iterator for PinkNoise {
 
         self.b[6] = white * 0.115926;
 
-        Some(pink)
+        Some(pink * PINK_GAIN)
     }
 }
 
@@ -150,9 +174,62 @@ impl Source for PinkNoise {
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, _: std::time::Duration) -> Result<(), SeekError> {
         // Does nothing, should do nothing
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PinkNoise, WhiteNoise};
+    use crate::Source;
+
+    const RATE: cpal::SampleRate = cpal::SampleRate(48000);
+
+    /// Ratio of the energy below 500 Hz to the energy above 5 kHz.
+    fn tilt(noise: impl Source<Item = f32> + Clone) -> f32 {
+        let energy = |samples: &mut dyn Iterator<Item = f32>| samples.map(|s| s * s).sum::<f32>();
+        let low = energy(&mut noise.clone().low_pass(500.0).take(48000));
+        let high = energy(&mut noise.high_pass(5000.0).take(48000));
+        low / high
+    }
+
+    #[test]
+    fn seeded_noise_is_reproducible() {
+        let white = |seed| {
+            WhiteNoise::new_with_seed(RATE, seed)
+                .take(100)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(white(1), white(1));
+        assert_ne!(white(1), white(2));
+        let pink = |seed| {
+            PinkNoise::new_with_seed(RATE, seed)
+                .take(100)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pink(1), pink(1));
+        assert_ne!(pink(1), pink(2));
+    }
+
+    #[test]
+    fn pink_noise_falls_with_frequency() {
+        assert!(tilt(WhiteNoise::new_with_seed(RATE, 7)) < 0.1);
+        assert!(tilt(PinkNoise::new_with_seed(RATE, 7)) > 1.0);
+    }
+
+    #[test]
+    fn pink_noise_stays_in_range() {
+        let samples: Vec<f32> = PinkNoise::new_with_seed(RATE, 3).take(480000).collect();
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        assert!(rms > 0.01 && rms < 0.05, "{rms}");
+    }
+}
@@ -0,0 +1,411 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::biquad::{Biquad, BiquadState};
+use super::SeekError;
+
+/// Loudness is measured in blocks of 100 ms, following ITU-R BS.1770.
+const SUB_BLOCK: Duration = Duration::from_millis(100);
+/// The gated blocks of the integrated loudness are 400 ms long.
+const BLOCK_SUB_BLOCKS: usize = 4;
+/// The short-term loudness is measured over the last 3 s.
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+/// Blocks quieter than this are silence and ignored.
+const ABSOLUTE_GATE: f32 = -70.0;
+/// Blocks this much below the ungated loudness are ignored for the integrated loudness.
+const RELATIVE_GATE: f32 = -10.0;
+/// Resolution and upper end of the histogram the integrated loudness is computed from.
+const HISTOGRAM_STEP: f32 = 0.1;
+const HISTOGRAM_MAX: f32 = 5.0;
+/// The gain stays within this many dB of unity.
+const MAX_GAIN_DB: f32 = 20.0;
+/// The limiter keeps the output below -1 dBFS.
+const CEILING: f32 = 0.891;
+const LIMITER_RELEASE: Duration = Duration::from_millis(100);
+
+/// Internal function that builds a `Normalize` object.
+pub fn normalize<I>(input: I, target_lufs: f32) -> Normalize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Normalize {
+        input,
+        target_lufs,
+        measured: LoudnessHandle(Arc::new(Measured {
+            integrated: AtomicU32::new(f32::NAN.to_bits()),
+            short_term: AtomicU32::new(f32::NAN.to_bits()),
+        })),
+        channels: 0,
+        sample_rate: 0,
+        weighting: None,
+        state: Vec::new(),
+        meter: LoudnessMeter::new(0),
+        frame_energy: 0.0,
+        gain: 1.0,
+        gain_step: 0.0,
+        limit: 1.0,
+        release: 0.0,
+        channel: 0,
+    }
+}
+
+#[derive(Debug)]
+struct Measured {
+    integrated: AtomicU32,
+    short_term: AtomicU32,
+}
+
+/// Reads the loudness measured by a [`Normalize`], see [`Normalize::loudness`].
+#[derive(Clone, Debug)]
+pub struct LoudnessHandle(Arc<Measured>);
+
+impl LoudnessHandle {
+    /// Returns the gated loudness of everything played so far in LUFS, `None` before the
+    /// first 400 ms that are not silent.
+    pub fn integrated(&self) -> Option<f32> {
+        let lufs = f32::from_bits(self.0.integrated.load(Ordering::Relaxed));
+        (!lufs.is_nan()).then_some(lufs)
+    }
+
+    /// Returns the loudness of the last 3 s in LUFS, `None` before the first 100 ms.
+    pub fn short_term(&self) -> Option<f32> {
+        let lufs = f32::from_bits(self.0.short_term.load(Ordering::Relaxed));
+        (!lufs.is_nan()).then_some(lufs)
+    }
+}
+
+/// Loudness of a mean square, in LUFS.
+fn lufs(mean_square: f64) -> f32 {
+    -0.691 + 10.0 * mean_square.log10() as f32
+}
+
+/// Measures the loudness of K-weighted frames as described in ITU-R BS.1770.
+#[derive(Clone, Debug)]
+struct LoudnessMeter {
+    sub_block_frames: usize,
+    frames: usize,
+    energy: f64,
+    // Mean squares of the last sub-blocks and the number of sub-blocks measured.
+    recent: [f64; SHORT_TERM_SUB_BLOCKS],
+    sub_blocks: usize,
+    // Count and summed mean square of the blocks of each loudness, so the integrated loudness
+    // can be gated without keeping every block.
+    histogram: Vec<(u32, f64)>,
+}
+
+impl LoudnessMeter {
+    fn new(sample_rate: u32) -> LoudnessMeter {
+        let bins = ((HISTOGRAM_MAX - ABSOLUTE_GATE) / HISTOGRAM_STEP) as usize + 1;
+        let mut meter = LoudnessMeter {
+            sub_block_frames: 1,
+            frames: 0,
+            energy: 0.0,
+            recent: [0.0; SHORT_TERM_SUB_BLOCKS],
+            sub_blocks: 0,
+            histogram: vec![(0, 0.0); bins],
+        };
+        meter.set_sample_rate(sample_rate);
+        meter
+    }
+
+    /// Starts a new sub-block at another rate, keeping the measured blocks.
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sub_block_frames = ((SUB_BLOCK.as_secs_f32() * sample_rate as f32) as usize).max(1);
+        self.frames = 0;
+        self.energy = 0.0;
+    }
+
+    /// Adds the summed squares of the channels of a frame. Returns true when that completed a
+    /// sub-block.
+    fn add_frame(&mut self, energy: f64) -> bool {
+        self.energy += energy;
+        self.frames += 1;
+        if self.frames < self.sub_block_frames {
+            return false;
+        }
+
+        self.recent[self.sub_blocks % SHORT_TERM_SUB_BLOCKS] = self.energy / self.frames as f64;
+        self.sub_blocks += 1;
+        self.energy = 0.0;
+        self.frames = 0;
+        if self.sub_blocks >= BLOCK_SUB_BLOCKS {
+            let block = self.mean_of_last(BLOCK_SUB_BLOCKS);
+            let loudness = lufs(block);
+            if loudness >= ABSOLUTE_GATE {
+                let last = self.histogram.len() - 1;
+                let bin = ((loudness - ABSOLUTE_GATE) / HISTOGRAM_STEP) as usize;
+                let bin = &mut self.histogram[bin.min(last)];
+                bin.0 += 1;
+                bin.1 += block;
+            }
+        }
+        true
+    }
+
+    fn mean_of_last(&self, sub_blocks: usize) -> f64 {
+        let sub_blocks = sub_blocks.min(self.sub_blocks);
+        let sum: f64 = (1..=sub_blocks)
+            .map(|back| self.recent[(self.sub_blocks - back) % SHORT_TERM_SUB_BLOCKS])
+            .sum();
+        sum / sub_blocks as f64
+    }
+
+    fn short_term(&self) -> Option<f32> {
+        (self.sub_blocks > 0).then(|| lufs(self.mean_of_last(SHORT_TERM_SUB_BLOCKS)))
+    }
+
+    fn integrated(&self) -> Option<f32> {
+        let mean_above = |first_bin: usize| {
+            let (count, sum) = self.histogram[first_bin..]
+                .iter()
+                .fold((0, 0.0), |(count, sum), bin| (count + bin.0, sum + bin.1));
+            (count > 0).then(|| sum / count as f64)
+        };
+        let ungated = lufs(mean_above(0)?);
+        let gate = ((ungated + RELATIVE_GATE - ABSOLUTE_GATE) / HISTOGRAM_STEP).max(0.0);
+        mean_above(gate as usize).map(lufs)
+    }
+}
+
+/// Filter that steers the loudness of a source towards a target.
+///
+/// The short-term loudness of the input is measured as described in ITU-R BS.1770 and the
+/// gain follows it every 100 ms, within 20 dB of unity. Silence is not amplified. A limiter
+/// catches the peaks that a raised gain would push above -1 dBFS.
+#[derive(Clone, Debug)]
+pub struct Normalize<I> {
+    input: I,
+    target_lufs: f32,
+    measured: LoudnessHandle,
+    // Format the filters and the meter were set up for, checked at every frame boundary.
+    channels: u16,
+    sample_rate: u32,
+    // The K-weighting of BS.1770, a high shelf followed by a high-pass.
+    weighting: Option<[Biquad; 2]>,
+    state: Vec<[BiquadState; 2]>,
+    meter: LoudnessMeter,
+    frame_energy: f64,
+    // Gain that is ramped from one sub-block to the next.
+    gain: f32,
+    gain_step: f32,
+    // Gain of the limiter, recovering towards 1.0 by `release` each frame.
+    limit: f32,
+    release: f32,
+    channel: u16,
+}
+
+impl<I> Normalize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to read the measured loudness of the input from another thread, for
+    /// example to display it.
+    pub fn loudness(&self) -> LoudnessHandle {
+        self.measured.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Sets the filters and the meter up again if the format of the input changed.
+    fn update_format(&mut self) {
+        let (channels, sample_rate) = (self.input.channels().max(1), self.input.sample_rate());
+        if (channels, sample_rate) == (self.channels, self.sample_rate) {
+            return;
+        }
+        self.channels = channels;
+        self.sample_rate = sample_rate;
+        self.state = vec![[[0.0; 4]; 2]; channels as usize];
+        self.weighting = (sample_rate > 0).then(|| {
+            [
+                Biquad::k_weighting_shelf(sample_rate),
+                Biquad::k_weighting_high_pass(sample_rate),
+            ]
+        });
+        self.meter.set_sample_rate(sample_rate);
+        self.release = 1.0 - (-1.0 / (LIMITER_RELEASE.as_secs_f32() * sample_rate as f32)).exp();
+    }
+
+    /// Publishes the loudness and sets the gain ramp towards the target for the next
+    /// sub-block.
+    fn sub_block_done(&mut self) {
+        let short_term = self.meter.short_term();
+        let integrated = self.meter.integrated();
+        let store = |atomic: &AtomicU32, lufs: Option<f32>| {
+            atomic.store(lufs.unwrap_or(f32::NAN).to_bits(), Ordering::Relaxed)
+        };
+        store(&self.measured.0.short_term, short_term);
+        store(&self.measured.0.integrated, integrated);
+
+        self.gain_step = match short_term {
+            Some(lufs) if lufs >= ABSOLUTE_GATE => {
+                let gain_db = (self.target_lufs - lufs).clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+                let gain = 10f32.powf(gain_db / 20.0);
+                (gain - self.gain) / self.meter.sub_block_frames as f32
+            }
+            _ => 0.0,
+        };
+    }
+}
+
+impl<I> Iterator for Normalize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update_format();
+            self.gain += self.gain_step;
+            self.limit += (1.0 - self.limit) * self.release;
+        }
+
+        let value = self.input.next()?.to_f32();
+        let channel = self.channel as usize;
+        if let Some([shelf, high_pass]) = &self.weighting {
+            let state = &mut self.state[channel];
+            let weighted = high_pass.process(shelf.process(value, &mut state[0]), &mut state[1]);
+            self.frame_energy += (weighted * weighted) as f64;
+        }
+
+        self.channel += 1;
+        if self.channel == self.channels {
+            self.channel = 0;
+            let energy = std::mem::take(&mut self.frame_energy);
+            if self.meter.add_frame(energy) {
+                self.sub_block_done();
+            }
+        }
+
+        let amplified = value * self.gain;
+        if amplified.abs() * self.limit > CEILING {
+            self.limit = CEILING / amplified.abs();
+        }
+        let output: <I::Item as CpalSample>::Float =
+            CpalSample::from_sample(amplified * self.limit);
+        Some(output.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Normalize<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Normalize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The gain and the loudness measured so far still describe the source.
+        self.state
+            .iter_mut()
+            .for_each(|state| *state = [[0.0; 4]; 2]);
+        self.frame_energy = 0.0;
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak(samples: impl Iterator<Item = f32>) -> f32 {
+        samples.fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn measures_a_full_scale_sine_at_minus_three() {
+        let mut source = SineWave::new(1000.0).normalize(-23.0);
+        let loudness = source.loudness();
+        assert_eq!(loudness.integrated(), None);
+        source.by_ref().take(48000 * 4).for_each(drop);
+        // BS.1770 defines a 1 kHz sine at full scale as -3.01 LUFS.
+        assert!((loudness.integrated().unwrap() + 3.01).abs() < 0.1);
+        assert!((loudness.short_term().unwrap() + 3.01).abs() < 0.1);
+    }
+
+    #[test]
+    fn quiet_sources_are_raised_to_the_target() {
+        // -29 LUFS raised to -23 LUFS is a gain of 6 dB.
+        let source = SineWave::new(1000.0).amplify(0.05).normalize(-23.0);
+        let level = peak(source.skip(48000 * 4).take(48000));
+        assert!((level - 0.0998).abs() < 0.002, "{level}");
+    }
+
+    #[test]
+    fn limiter_prevents_overshoot() {
+        let source = SineWave::new(1000.0).amplify(0.8).normalize(0.0);
+        let level = peak(source.skip(9600).take(48000 * 4));
+        assert!(level <= 0.891 + 1e-6, "{level}");
+        assert!(level > 0.85, "{level}");
+    }
+
+    #[test]
+    fn silence_is_not_amplified() {
+        let source = SineWave::new(1000.0).amplify(0.0).normalize(-23.0);
+        assert_eq!(peak(source.take(48000 * 2)), 0.0);
+    }
+}
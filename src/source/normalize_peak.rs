@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// How far ahead peaks are seen, and how long the gain takes to make room for them.
+const LOOK_AHEAD: Duration = Duration::from_millis(200);
+/// The gain never exceeds 24 dB, so a quiet or silent start is not raised without end.
+const MAX_GAIN: f32 = 16.0;
+
+/// Internal function that builds a `NormalizePeak` object.
+pub fn normalize_peak<I>(input: I, target_peak: f32) -> NormalizePeak<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    NormalizePeak {
+        input,
+        target_peak: target_peak.abs(),
+        look_ahead: VecDeque::new(),
+        filled: false,
+        peak: 0.0,
+        gain: MAX_GAIN,
+        wanted: MAX_GAIN,
+        gain_step: 0.0,
+    }
+}
+
+/// Filter that scales a source so its peaks reach a target.
+///
+/// The output runs 200 ms behind the input. The gain is set from the loudest sample seen so
+/// far, and lowered gradually over that delay whenever a louder sample comes in, so no sample
+/// is ever played above the target. The gain only goes down, up to a maximum of 24 dB.
+#[derive(Clone, Debug)]
+pub struct NormalizePeak<I> {
+    input: I,
+    target_peak: f32,
+    // Input samples that were read but not played yet.
+    look_ahead: VecDeque<f32>,
+    filled: bool,
+    peak: f32,
+    // Current gain, moving by `gain_step` each sample until it reaches the `wanted` gain.
+    gain: f32,
+    wanted: f32,
+    gain_step: f32,
+}
+
+impl<I> NormalizePeak<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the current gain.
+    #[inline]
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads a sample into the look-ahead. Returns false at the end of the input.
+    fn read(&mut self) -> bool {
+        let Some(sample) = self.input.next() else {
+            return false;
+        };
+        let sample = sample.to_f32();
+        self.look_ahead.push_back(sample);
+        if sample.abs() > self.peak {
+            self.peak = sample.abs();
+            self.wanted = (self.target_peak / self.peak).min(MAX_GAIN);
+            // Reach the new gain just as the new peak is played, without slowing down a ramp
+            // that still has to make room for an earlier peak.
+            let step = (self.gain - self.wanted) / self.look_ahead.len() as f32;
+            self.gain_step = self.gain_step.max(step);
+        }
+        true
+    }
+
+    /// Fills the look-ahead at the start or after a seek, nothing is playing yet so the gain
+    /// can jump.
+    fn fill(&mut self) {
+        let frames = LOOK_AHEAD.as_secs_f32() * self.input.sample_rate() as f32;
+        let samples = frames as usize * self.input.channels().max(1) as usize;
+        self.look_ahead.reserve(samples + 1);
+        while self.look_ahead.len() < samples && self.read() {}
+        self.gain = self.gain.min(self.wanted);
+        self.gain_step = 0.0;
+        self.filled = true;
+    }
+}
+
+impl<I> Iterator for NormalizePeak<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if !self.filled {
+            self.fill();
+        } else {
+            self.read();
+        }
+
+        let sample = self.look_ahead.pop_front()?;
+        self.gain = (self.gain - self.gain_step).max(self.wanted);
+        if self.gain == self.wanted {
+            self.gain_step = 0.0;
+        }
+        let output: <I::Item as CpalSample>::Float = CpalSample::from_sample(sample * self.gain);
+        Some(output.to_sample())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.input.size_hint();
+        let buffered = self.look_ahead.len();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl<I> Source for NormalizePeak<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input
+            .current_frame_len()
+            .map(|len| len + self.look_ahead.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        // The peak seen so far still describes the source.
+        self.look_ahead.clear();
+        self.filled = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    fn peak(samples: impl Iterator<Item = f32>) -> f32 {
+        samples.fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    #[test]
+    fn scales_to_the_target_peak() {
+        let source = SineWave::new(440.0).amplify(0.25).normalize_peak(0.9);
+        assert!((peak(source.take(48000)) - 0.9).abs() < 1e-3);
+    }
+
+    #[test]
+    fn later_peaks_are_not_played_above_the_target() {
+        let quiet = SineWave::new(440.0).amplify(0.25).take(48000);
+        let loud = SineWave::new(440.0).amplify(0.5).take(48000);
+        let input: Vec<f32> = quiet.chain(loud).collect();
+        let output: Vec<f32> = SamplesBuffer::new(1, 48000, input)
+            .normalize_peak(1.0)
+            .collect();
+        assert_eq!(output.len(), 96000);
+        assert!(peak(output.iter().copied()) <= 1.0 + 1e-6);
+        assert!((peak(output[72000..].iter().copied()) - 1.0).abs() < 1e-3);
+    }
+}
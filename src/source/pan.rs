@@ -0,0 +1,307 @@
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Pan` object.
+pub fn pan<I>(input: I, position: f32) -> Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let position = position.clamp(-1.0, 1.0);
+    Pan {
+        input,
+        target: PanHandle(Arc::new(AtomicU32::new(position.to_bits()))),
+        position,
+        gains: gains(position),
+        channel: 0,
+        upmixed: None,
+        stereo_only: false,
+    }
+}
+
+/// Moves a [`Pan`] while it plays, see [`Pan::handle`].
+#[derive(Clone, Debug)]
+pub struct PanHandle(Arc<AtomicU32>);
+
+impl PanHandle {
+    /// Moves the sound, see [`Pan::set_position`]. The pan picks it up at the start of its
+    /// next frame.
+    pub fn set_position(&self, position: f32) {
+        self.0
+            .store(position.clamp(-1.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the position the sound is moved to.
+    pub fn position(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Returns the left and right gains of a pan position between -1.0 (left) and 1.0 (right).
+///
+/// Equal-power law: the gains are the cosine and sine of the position mapped to a quarter
+/// turn, scaled so that both are 1.0 in the center. The sum of their squares stays 2.0.
+#[inline]
+fn gains(position: f32) -> [f32; 2] {
+    let angle = (position + 1.0) * FRAC_PI_4;
+    [angle.cos() * SQRT_2, angle.sin() * SQRT_2]
+}
+
+/// Filter that moves a source between the left and the right channel.
+///
+/// Mono sources are played on two channels. Of sources with more than two channels only the
+/// first two, the front left and right, are panned; the others pass through unchanged.
+#[derive(Clone, Debug)]
+pub struct Pan<I>
+where
+    I: Iterator,
+{
+    input: I,
+    target: PanHandle,
+    // Position of the current frame.
+    position: f32,
+    gains: [f32; 2],
+    channel: u16,
+    // The right channel of a mono sample that is played on both.
+    upmixed: Option<I::Item>,
+    // Leaves sources that are not stereo unchanged, for the balance of a sink.
+    stereo_only: bool,
+}
+
+impl<I> Pan<I>
+where
+    I: Iterator,
+{
+    /// Moves the sound, from -1.0 for fully left over 0.0 for centered to 1.0 for fully
+    /// right. Values out of that range are clamped. Takes effect at the start of the next
+    /// frame.
+    #[inline]
+    pub fn set_position(&mut self, position: f32) {
+        self.target.set_position(position);
+    }
+
+    /// Returns the position the sound is moved to, see [`set_position`](Pan::set_position).
+    #[inline]
+    pub fn position(&self) -> f32 {
+        self.target.position()
+    }
+
+    /// Returns a handle to move the sound from another thread, for example to sweep it
+    /// across the stereo field.
+    pub fn handle(&self) -> PanHandle {
+        self.target.clone()
+    }
+
+    /// Only pans stereo sources, others pass through unchanged.
+    pub(crate) fn stereo_only(mut self) -> Self {
+        self.stereo_only = true;
+        self
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns true if the source is mono and played on two channels.
+    #[inline]
+    fn upmixes(&self) -> bool {
+        !self.stereo_only && self.input.channels() == 1
+    }
+}
+
+impl<I> Iterator for Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if let Some(right) = self.upmixed.take() {
+            return Some(right);
+        }
+
+        if self.channel == 0 {
+            let position = self.target.position();
+            if position != self.position {
+                self.position = position;
+                self.gains = gains(position);
+            }
+        }
+
+        let channels = self.input.channels();
+        let value = self.input.next()?;
+        if channels == 1 && !self.stereo_only {
+            self.upmixed = Some(value.amplify(self.gains[1]));
+            return Some(value.amplify(self.gains[0]));
+        }
+
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % channels.max(1);
+        if channel >= 2 || self.position == 0.0 || (self.stereo_only && channels != 2) {
+            return Some(value);
+        }
+        Some(value.amplify(self.gains[channel as usize]))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.input.size_hint();
+        let extra = self.upmixed.is_some() as usize;
+        if self.upmixes() {
+            (
+                lower.saturating_mul(2) + extra,
+                upper.and_then(|upper| upper.checked_mul(2)?.checked_add(extra)),
+            )
+        } else {
+            (lower, upper)
+        }
+    }
+}
+
+impl<I> Source for Pan<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let extra = self.upmixed.is_some() as usize;
+        match self.upmixes() {
+            true => Some(self.input.current_frame_len()? * 2 + extra),
+            false => self.input.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self.upmixes() {
+            true => 2,
+            false => self.input.channels(),
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.channel = 0;
+        self.upmixed = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::gains;
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn power_is_constant() {
+        for step in -10..=10 {
+            let [left, right] = gains(step as f32 / 10.0);
+            assert_abs_diff_eq!(left * left + right * right, 2.0, epsilon = 1e-5);
+        }
+        assert!(gains(-1.0)[1].abs() < 1e-6);
+        assert_abs_diff_eq!(gains(0.0)[0], 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn stereo_is_panned() {
+        let stereo: Vec<f32> = SamplesBuffer::new(2, 48000, vec![0.5f32; 4])
+            .pan(1.0)
+            .collect();
+        assert_abs_diff_eq!(stereo[0], 0.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(stereo[1], 0.5 * std::f32::consts::SQRT_2, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn mono_is_upmixed() {
+        let pan = SamplesBuffer::new(1, 48000, vec![0.5f32, 0.25]).pan(-1.0);
+        assert_eq!((pan.channels(), pan.size_hint()), (2, (4, Some(4))));
+        let output: Vec<f32> = pan.collect();
+        assert_abs_diff_eq!(output[0], 0.5 * std::f32::consts::SQRT_2, epsilon = 1e-6);
+        assert_abs_diff_eq!(output[1], 0.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(output[2], 0.25 * std::f32::consts::SQRT_2, epsilon = 1e-6);
+        assert_abs_diff_eq!(output[3], 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn only_the_front_pair_of_surround_is_panned() {
+        let output: Vec<f32> = SamplesBuffer::new(4, 48000, vec![0.5f32; 4])
+            .pan(1.0)
+            .collect();
+        assert_abs_diff_eq!(output[0], 0.0, epsilon = 1e-6);
+        assert_eq!(&output[2..], &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn stereo_only_leaves_other_layouts() {
+        let mono = SamplesBuffer::new(1, 48000, vec![0.5f32; 2])
+            .pan(1.0)
+            .stereo_only();
+        assert_eq!(mono.channels(), 1);
+        assert_eq!(mono.collect::<Vec<_>>(), vec![0.5, 0.5]);
+
+        let surround: Vec<f32> = SamplesBuffer::new(4, 48000, vec![0.5f32; 4])
+            .pan(1.0)
+            .stereo_only()
+            .collect();
+        assert_eq!(surround, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn position_changes_at_frame_start() {
+        let mut pan = SamplesBuffer::new(2, 48000, vec![0.5f32; 6]).pan(0.0);
+        let handle = pan.handle();
+        assert_eq!(pan.next(), Some(0.5));
+        handle.set_position(1.0);
+        assert_eq!(pan.position(), 1.0);
+        assert_eq!(pan.next(), Some(0.5));
+        assert_abs_diff_eq!(pan.next().unwrap(), 0.0, epsilon = 1e-6);
+    }
+}
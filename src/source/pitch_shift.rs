@@ -0,0 +1,283 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Length of the grains if none is given.
+pub(crate) const DEFAULT_WINDOW: Duration = Duration::from_millis(50);
+
+/// Internal function that builds a `PitchShift` object.
+pub fn pitch_shift<I>(input: I, semitones: f32, window: Duration) -> PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    PitchShift {
+        input,
+        semitones: PitchShiftHandle(Arc::new(AtomicU32::new(semitones.to_bits()))),
+        window,
+        ratio: 1.0,
+        channels: 0,
+        sample_rate: 0,
+        window_frames: 0,
+        buffer: Vec::new(),
+        write_frame: 0,
+        delay: 0.0,
+        channel: 0,
+    }
+}
+
+/// Changes the shift of a [`PitchShift`] while it plays, see [`PitchShift::handle`].
+#[derive(Clone, Debug)]
+pub struct PitchShiftHandle(Arc<AtomicU32>);
+
+impl PitchShiftHandle {
+    /// Sets the shift in semitones, negative values lower the pitch. The shift is picked up at
+    /// the start of the next frame.
+    pub fn set_semitones(&self, semitones: f32) {
+        self.0.store(semitones.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the shift in semitones.
+    pub fn semitones(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Filter that changes the pitch of a source without changing its speed.
+///
+/// The source runs through a delay line that is read at another speed than it is written.
+/// Two read positions half a window apart take turns, each fading in and out over a window,
+/// so the jumps back to keep up with the input are not heard. Longer windows suit low and
+/// sustained sounds, shorter ones keep transients such as speech crisper but sound rougher.
+#[derive(Clone, Debug)]
+pub struct PitchShift<I> {
+    input: I,
+    semitones: PitchShiftHandle,
+    window: Duration,
+    // Playback rate of the delay line for the current frame, 1.0 passes the input through.
+    ratio: f32,
+    // Format the delay line was set up for, checked at every frame boundary.
+    channels: u16,
+    sample_rate: u32,
+    window_frames: usize,
+    // Interleaved delay line, a window and two frames for the interpolation long.
+    buffer: Vec<f32>,
+    write_frame: usize,
+    // Delay of the first read position in frames, between zero and a window.
+    delay: f32,
+    channel: u16,
+}
+
+impl<I> PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the shift from another thread.
+    pub fn handle(&self) -> PitchShiftHandle {
+        self.semitones.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Reads the shift and sets the delay line up again if the format of the input changed.
+    fn update(&mut self) {
+        self.ratio = 2f32.powf(self.semitones.semitones() / 12.0);
+
+        let (channels, sample_rate) = (self.input.channels().max(1), self.input.sample_rate());
+        if (channels, sample_rate) == (self.channels, self.sample_rate) {
+            return;
+        }
+        self.channels = channels;
+        self.sample_rate = sample_rate;
+        self.window_frames = ((self.window.as_secs_f32() * sample_rate as f32) as usize).max(16);
+        self.buffer = vec![0.0; (self.window_frames + 2) * channels as usize];
+        self.write_frame = 0;
+        self.delay = 0.0;
+    }
+
+    /// Reads the delay line `delay` frames behind the last written frame.
+    #[inline]
+    fn tap(&self, delay: f32, channel: usize) -> f32 {
+        let frames = self.window_frames + 2;
+        let whole = delay as usize;
+        let fraction = delay - whole as f32;
+        let at = |back: usize| {
+            let frame = (self.write_frame + frames - back) % frames;
+            self.buffer[frame * self.channels as usize + channel]
+        };
+        at(whole) * (1.0 - fraction) + at(whole + 1) * fraction
+    }
+
+    /// Mixes the two read positions, each faded with a Hann window so their gains add to one.
+    #[inline]
+    fn shifted(&self, channel: usize) -> f32 {
+        let window = self.window_frames as f32;
+        let first = self.delay;
+        let second = (self.delay + window / 2.0) % window;
+        let gain = |delay: f32| (PI * delay / window).sin().powi(2);
+        self.tap(first, channel) * gain(first) + self.tap(second, channel) * gain(second)
+    }
+}
+
+impl<I> Iterator for PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            self.update();
+        }
+
+        let value = self.input.next()?;
+        let channel = self.channel as usize;
+        self.buffer[self.write_frame * self.channels as usize + channel] = value.to_f32();
+        let output = if self.ratio == 1.0 {
+            value
+        } else {
+            let shifted: <I::Item as CpalSample>::Float =
+                CpalSample::from_sample(self.shifted(channel));
+            shifted.to_sample()
+        };
+
+        self.channel += 1;
+        if self.channel == self.channels {
+            self.channel = 0;
+            self.write_frame = (self.write_frame + 1) % (self.window_frames + 2);
+            let window = self.window_frames as f32;
+            self.delay = (self.delay + 1.0 - self.ratio).rem_euclid(window);
+        }
+        Some(output)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for PitchShift<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for PitchShift<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.buffer.iter_mut().for_each(|sample| *sample = 0.0);
+        self.channel = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    /// Estimates the frequency of a tone from its zero crossings.
+    fn frequency(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (samples.len() as f32 / sample_rate as f32)
+    }
+
+    #[test]
+    fn octave_up_doubles_the_frequency() {
+        let shifted: Vec<f32> = SineWave::new(220.0)
+            .take_duration(Duration::from_secs(1))
+            .pitch_shift(12.0)
+            .collect();
+        assert_eq!(shifted.len(), 48000);
+        let measured = frequency(&shifted[4800..], 48000);
+        assert!((measured - 440.0).abs() < 440.0 * 0.03, "{measured}");
+    }
+
+    #[test]
+    fn duration_is_kept() {
+        let samples: Vec<f32> = SineWave::new(440.0).take(48000).collect();
+        let source = SamplesBuffer::new(2, 48000, samples).pitch_shift(-5.0);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(500)));
+        assert_eq!(source.count(), 48000);
+    }
+
+    #[test]
+    fn shift_changes_while_playing() {
+        let mut source = SineWave::new(440.0).pitch_shift(0.0);
+        let handle = source.handle();
+        let unshifted: Vec<f32> = source.by_ref().take(9600).collect();
+        assert_eq!(
+            unshifted,
+            SineWave::new(440.0).take(9600).collect::<Vec<_>>()
+        );
+
+        handle.set_semitones(-12.0);
+        let shifted: Vec<f32> = source.skip(4800).take(24000).collect();
+        let measured = frequency(&shifted, 48000);
+        assert!((measured - 220.0).abs() < 220.0 * 0.03, "{measured}");
+    }
+}
@@ -144,6 +144,11 @@ where
         self.input.total_duration()
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         let result = self.input.try_seek(pos);
@@ -177,7 +182,7 @@ mod tests {
         source.next();
         assert_eq!(source.get_pos().as_secs_f32(), 2.0);
 
-        assert_eq!(source.try_seek(Duration::new(1, 0)).is_ok(), true);
+        assert!(source.try_seek(Duration::new(1, 0)).is_ok());
         assert_eq!(source.get_pos().as_secs_f32(), 1.0);
     }
 
@@ -193,7 +198,7 @@ mod tests {
         source.next();
         assert_eq!(source.get_pos().as_secs_f32(), 1.0);
 
-        assert_eq!(source.try_seek(Duration::new(1, 0)).is_ok(), true);
+        assert!(source.try_seek(Duration::new(1, 0)).is_ok());
         assert_eq!(source.get_pos().as_secs_f32(), 1.0);
     }
 }
@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::recording::wav_to_io_error;
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Number of samples collected before they are handed to the writer thread.
+const CHUNK_LEN: usize = 4096;
+
+/// Internal function that builds a `RecordTo` object.
+pub fn record_to<I>(input: I, path: &Path) -> io::Result<RecordTo<I>>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let spec = hound::WavSpec {
+        channels: input.channels(),
+        sample_rate: input.sample_rate(),
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let writer = hound::WavWriter::create(path, spec).map_err(wav_to_io_error)?;
+    let (sender, receiver) = channel();
+    let writer = thread::Builder::new()
+        .name("rodio record_to".to_owned())
+        .spawn(move || write_wav(receiver, writer))?;
+    Ok(RecordTo {
+        input,
+        chunk: Vec::with_capacity(CHUNK_LEN),
+        sender: Some(sender),
+        writer,
+    })
+}
+
+/// Passes a source through unchanged while writing its samples to a WAV file.
+pub struct RecordTo<I> {
+    input: I,
+    chunk: Vec<f32>,
+    // Dropped once the source ended, which lets the writer thread finish the file.
+    sender: Option<Sender<Vec<f32>>>,
+    writer: JoinHandle<()>,
+}
+
+impl<I> RecordTo<I> {
+    /// Returns true once the file is complete, some time after the source ended.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.writer.is_finished()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Sends the collected samples to the writer thread. With `last` the file is finished.
+    fn flush(&mut self, last: bool) {
+        if let Some(sender) = &self.sender {
+            if !self.chunk.is_empty() {
+                let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(CHUNK_LEN));
+                // Only fails if the writer thread gave up after an error, it reported that.
+                let _ = sender.send(chunk);
+            }
+        }
+        if last {
+            self.sender = None;
+        }
+    }
+}
+
+impl<I> Drop for RecordTo<I> {
+    fn drop(&mut self) {
+        self.flush(true);
+    }
+}
+
+impl<I> Iterator for RecordTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self.input.next() {
+            Some(value) => {
+                self.chunk.push(value.to_f32());
+                if self.chunk.len() == CHUNK_LEN {
+                    self.flush(false);
+                }
+                Some(value)
+            }
+            None => {
+                self.flush(true);
+                None
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for RecordTo<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+fn write_wav(receiver: Receiver<Vec<f32>>, mut writer: hound::WavWriter<BufWriter<File>>) {
+    let result = receiver
+        .iter()
+        .try_for_each(|chunk| {
+            chunk
+                .into_iter()
+                .try_for_each(|sample| writer.write_sample(sample))
+        })
+        .and_then(|()| writer.finalize());
+    if let Err(err) = result {
+        #[cfg(feature = "tracing")]
+        tracing::error!("could not record source to file: {err}");
+        #[cfg(not(feature = "tracing"))]
+        eprintln!("could not record source to file: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn file_matches_played_samples() {
+        let path = std::env::temp_dir().join("rodio-record-to-test.wav");
+        let mut source = SineWave::new(440.0)
+            .take_duration(Duration::from_millis(250))
+            .record_to(&path)
+            .unwrap();
+        let played: Vec<f32> = source.by_ref().collect();
+        while !source.is_finished() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.spec().sample_rate, 48000);
+        let recorded: Vec<f32> = reader.samples().map(Result::unwrap).collect();
+        assert_eq!(recorded, played);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use crate::conversions::{ChannelCountConverter, ChannelLayout};
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `RemapChannels` object.
+///
+/// # Panic
+///
+/// Panics if the source does not have as many channels as the `from` layout.
+pub fn remap_channels<I>(input: I, from: ChannelLayout, to: ChannelLayout) -> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(
+        input.channels(),
+        from.channels(),
+        "the source does not match the given channel layout"
+    );
+    RemapChannels {
+        input: ChannelCountConverter::with_layouts(input, from, to),
+        from,
+        to,
+    }
+}
+
+/// Filter that moves the channels of a source to the speakers of another layout.
+#[derive(Clone)]
+pub struct RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: ChannelCountConverter<I>,
+    from: ChannelLayout,
+    to: ChannelLayout,
+}
+
+impl<I> RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the layout of the inner source.
+    #[inline]
+    pub fn from_layout(&self) -> ChannelLayout {
+        self.from
+    }
+
+    /// Returns the layout this source outputs.
+    #[inline]
+    pub fn to_layout(&self) -> ChannelLayout {
+        self.to
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        self.input.inner()
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        self.input.inner_mut()
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input.into_inner()
+    }
+}
+
+impl<I> Iterator for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        self.input.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for RemapChannels<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for RemapChannels<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let from = self.from.channels() as usize;
+        let to = self.to.channels() as usize;
+        self.input
+            .inner()
+            .current_frame_len()
+            .map(|len| len / from * to + self.input.left_in_frame())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.to.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.inner().sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.inner().total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.inner().is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.inner_mut().try_seek(pos)
+    }
+}
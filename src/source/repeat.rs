@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::time::Duration;
 
 use crate::source::buffered::Buffered;
@@ -87,6 +88,13 @@ where
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        // Repeating nothing ends right away. The buffered start knows whether the input was
+        // empty even if it could not tell its duration.
+        self.next.current_frame_len() != Some(0)
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner.try_seek(pos)
@@ -106,3 +114,188 @@ where
         }
     }
 }
+
+/// Internal function that builds a `RepeatCrossfaded` object.
+pub fn repeat_crossfaded<I>(input: I, overlap: Duration) -> RepeatCrossfaded<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let input = input.buffered();
+    let channels = input.channels().max(1) as usize;
+    let overlap_frames = (overlap.as_secs_f64() * input.sample_rate() as f64) as usize;
+    RepeatCrossfaded {
+        inner: input.clone(),
+        next: input,
+        tail: VecDeque::with_capacity(overlap_frames * channels + 1),
+        overlap_len: overlap_frames * channels,
+        channels,
+        crossfade_len: 0,
+        crossfade_pos: 0,
+    }
+}
+
+/// A source that repeats the given source, crossfading the end of each iteration into the
+/// start of the next one.
+pub struct RepeatCrossfaded<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    inner: Buffered<I>,
+    next: Buffered<I>,
+    // The last `overlap_len` samples read from `inner`, played with a delay so they can be
+    // mixed with the start of the next iteration.
+    tail: VecDeque<I::Item>,
+    overlap_len: usize,
+    channels: usize,
+    // Length in samples of the crossfade in progress, zero when not crossfading.
+    crossfade_len: usize,
+    crossfade_pos: usize,
+}
+
+impl<I> Iterator for RepeatCrossfaded<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = <I as Iterator>::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<<I as Iterator>::Item> {
+        if self.crossfade_len > 0 {
+            let head = self.inner.next();
+            let tail = self.tail.pop_front();
+            let frames = (self.crossfade_len / self.channels) as u32;
+            let frame = (self.crossfade_pos / self.channels) as u32;
+            self.crossfade_pos += 1;
+            if self.crossfade_pos == self.crossfade_len {
+                self.crossfade_len = 0;
+            }
+            return match (tail, head) {
+                (Some(tail), Some(head)) => Some(Sample::lerp(tail, head, frame, frames)),
+                (tail, head) => tail.or(head),
+            };
+        }
+
+        while self.tail.len() <= self.overlap_len {
+            match self.inner.next() {
+                Some(value) => self.tail.push_back(value),
+                None => break,
+            }
+        }
+        if self.tail.len() > self.overlap_len {
+            return self.tail.pop_front();
+        }
+
+        // The current iteration ended, start the next one while fading out the tail.
+        self.inner = self.next.clone();
+        if self.tail.is_empty() {
+            return self.inner.next();
+        }
+        self.crossfade_len = self.tail.len();
+        self.crossfade_pos = 0;
+        self.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // infinite
+        (0, None)
+    }
+}
+
+impl<I> Source for RepeatCrossfaded<I>
+where
+    I: Iterator + Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.next.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.next.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.next.current_frame_len() != Some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn infinite_sources() {
+        let buffer = SamplesBuffer::new(1, 48000, vec![0.0f32; 4]);
+        assert!(!buffer.is_infinite());
+        assert!(buffer.clone().repeat_infinite().is_infinite());
+        assert!(buffer
+            .buffered()
+            .repeat_infinite()
+            .amplify(0.5)
+            .is_infinite());
+        assert!(SineWave::new(440.0).amplify(0.5).is_infinite());
+        assert!(!SineWave::new(440.0)
+            .take_duration(Duration::from_secs(1))
+            .is_infinite());
+        // Ends right away, without telling its duration beforehand.
+        let empty = crate::source::from_iter(Vec::<SamplesBuffer<f32>>::new());
+        assert_eq!(empty.total_duration(), None);
+        let mut repeated = empty.repeat_infinite();
+        assert!(!repeated.is_infinite());
+        assert_eq!(repeated.next(), None);
+    }
+
+    #[test]
+    fn crossfaded_seam_has_no_jump() {
+        // 12.5 ms of a 100 Hz tone ends on a peak.
+        let tone = SineWave::new(100.0).take_duration(Duration::from_micros(12_500));
+        let len = tone.clone().count();
+
+        let plain: Vec<f32> = tone.clone().repeat_infinite().take(len * 3).collect();
+        let crossfaded: Vec<f32> = tone
+            .repeat_infinite_crossfaded(Duration::from_millis(2))
+            .take(len * 3)
+            .collect();
+
+        let max_jump = |samples: &[f32]| {
+            samples
+                .windows(2)
+                .map(|w| (w[1] - w[0]).abs())
+                .fold(0.0f32, f32::max)
+        };
+        // One sample step of a 100 Hz sine at 48 kHz is about 0.013.
+        assert!(max_jump(&plain) > 0.5);
+        assert!(max_jump(&crossfaded) < 0.05);
+    }
+
+    #[test]
+    fn zero_overlap_matches_repeat_infinite() {
+        let tone = SineWave::new(440.0).take_duration(Duration::from_millis(5));
+        let plain: Vec<f32> = tone.clone().repeat_infinite().take(1000).collect();
+        let crossfaded: Vec<f32> = tone
+            .repeat_infinite_crossfaded(Duration::ZERO)
+            .take(1000)
+            .collect();
+        assert_eq!(plain, crossfaded);
+    }
+}
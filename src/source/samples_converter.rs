@@ -97,6 +97,11 @@ where
         self.inner.total_duration()
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.inner.is_infinite()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         self.inner.try_seek(pos)
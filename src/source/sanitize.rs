@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds a `Sanitize` object.
+pub fn sanitize<I>(input: I) -> Sanitize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Sanitize {
+        input,
+        replaced: false,
+    }
+}
+
+/// Filter that replaces NaN and infinite samples with silence.
+#[derive(Clone, Debug)]
+pub struct Sanitize<I> {
+    input: I,
+    replaced: bool,
+}
+
+impl<I> Sanitize<I> {
+    /// Returns true if at least one sample was replaced so far.
+    #[inline]
+    pub fn replaced_any(&self) -> bool {
+        self.replaced
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for Sanitize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let value = self.input.next()?;
+        if value.to_f32().is_finite() {
+            return Some(value);
+        }
+
+        if !self.replaced {
+            self.replaced = true;
+            #[cfg(feature = "tracing")]
+            tracing::warn!("source emitted a NaN or infinite sample, replacing it with silence");
+        }
+        Some(I::Item::zero_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> ExactSizeIterator for Sanitize<I>
+where
+    I: Source + ExactSizeIterator,
+    I::Item: Sample,
+{
+}
+
+impl<I> Source for Sanitize<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn replaces_non_finite_samples() {
+        let samples = vec![
+            0.5f32,
+            f32::NAN,
+            -0.25,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            1.0,
+        ];
+        let mut source = SamplesBuffer::new(1, 48000, samples).sanitize();
+        assert!(!source.replaced_any());
+
+        let output: Vec<f32> = source.by_ref().collect();
+        assert!(output.iter().all(|s| s.is_finite()));
+        assert_eq!(output, vec![0.5, 0.0, -0.25, 0.0, 0.0, 1.0]);
+        assert!(source.replaced_any());
+    }
+}
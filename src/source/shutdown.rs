@@ -0,0 +1,202 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Length of the fade applied when a [`ShutdownToken`] is signalled, unless configured otherwise.
+const DEFAULT_FADE: Duration = Duration::from_millis(10);
+
+/// Token shared between sources to end all of them at once.
+///
+/// Cloning the token is cheap, every clone controls the same sources.
+#[derive(Clone, Debug)]
+pub struct ShutdownToken {
+    signalled: Arc<AtomicBool>,
+    fade: Duration,
+}
+
+impl ShutdownToken {
+    /// Builds a new token. Sources fade out over 10 milliseconds when it is signalled.
+    pub fn new() -> ShutdownToken {
+        ShutdownToken::with_fade(DEFAULT_FADE)
+    }
+
+    /// Builds a new token. Sources fade out over `fade` when it is signalled.
+    pub fn with_fade(fade: Duration) -> ShutdownToken {
+        ShutdownToken {
+            signalled: Arc::new(AtomicBool::new(false)),
+            fade,
+        }
+    }
+
+    /// Fades out and ends every source wrapped with this token.
+    pub fn shutdown(&self) {
+        self.signalled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if [`shutdown`](ShutdownToken::shutdown) was called.
+    pub fn is_shutdown(&self) -> bool {
+        self.signalled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the duration of the fade applied on shutdown.
+    pub fn fade(&self) -> Duration {
+        self.fade
+    }
+}
+
+impl Default for ShutdownToken {
+    fn default() -> Self {
+        ShutdownToken::new()
+    }
+}
+
+/// Internal function that builds a `WithShutdown` object.
+pub fn with_shutdown<I>(input: I, token: ShutdownToken) -> WithShutdown<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    WithShutdown {
+        input,
+        token,
+        fade_frames: None,
+        frames_faded: 0,
+        sample_in_frame: 0,
+    }
+}
+
+/// Filter that fades out and ends the source once its [`ShutdownToken`] is signalled.
+#[derive(Clone, Debug)]
+pub struct WithShutdown<I> {
+    input: I,
+    token: ShutdownToken,
+    // Length of the fade in frames, set once the shutdown was noticed.
+    fade_frames: Option<u64>,
+    frames_faded: u64,
+    sample_in_frame: u16,
+}
+
+impl<I> WithShutdown<I> {
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for WithShutdown<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            if self.fade_frames.is_none() && self.token.is_shutdown() {
+                let frames = self.token.fade.as_secs_f32() * self.input.sample_rate() as f32;
+                self.fade_frames = Some(frames as u64);
+            }
+            if let Some(fade_frames) = self.fade_frames {
+                if self.frames_faded >= fade_frames {
+                    return None;
+                }
+                self.frames_faded += 1;
+            }
+        }
+
+        let value = self.input.next()?;
+        self.sample_in_frame = (self.sample_in_frame + 1) % self.input.channels().max(1);
+        match self.fade_frames {
+            Some(fade_frames) => {
+                let factor = 1.0 - (self.frames_faded - 1) as f32 / fade_frames as f32;
+                Some(value.amplify(factor))
+            }
+            None => Some(value),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.input.size_hint().1)
+    }
+}
+
+impl<I> Source for WithShutdown<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::{ShutdownToken, SineWave, Source};
+
+    #[test]
+    fn shared_token_ends_all_sources_within_fade() {
+        let token = ShutdownToken::with_fade(Duration::from_millis(4));
+        let mut sine = SineWave::new(440.0).with_shutdown(token.clone());
+        let mut stereo =
+            SamplesBuffer::new(2, 1000, vec![1.0f32; 1000]).with_shutdown(token.clone());
+
+        assert_eq!(sine.by_ref().take(100).count(), 100);
+        assert_eq!(stereo.by_ref().take(10).count(), 10);
+
+        token.shutdown();
+        // 4 ms at 48 kHz mono.
+        assert_eq!(sine.count(), 192);
+        // 4 ms at 1 kHz stereo, fading towards silence.
+        let tail: Vec<f32> = stereo.collect();
+        assert_eq!(tail, vec![1.0, 1.0, 0.75, 0.75, 0.5, 0.5, 0.25, 0.25]);
+    }
+}
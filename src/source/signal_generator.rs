@@ -112,6 +112,11 @@ impl Source for SignalGenerator {
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        true
+    }
+
     #[inline]
     fn try_seek(&mut self, duration: Duration) -> Result<(), SeekError> {
         self.i = (self.sample_rate.0 as f32 * duration.as_secs_f32()) as u64;
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Resolution of the fraction of a step that is let through.
+const LERP_STEPS: u32 = 4096;
+
+/// Number of steps of the integer sample formats per unit of amplitude.
+const SMALLEST_STEPS: f32 = 32768.0;
+
+/// Internal function that builds a `SlewLimit` object.
+pub fn slew_limit<I>(input: I, max_delta_per_sample: f32) -> SlewLimit<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(
+        max_delta_per_sample > 0.0,
+        "the maximum slew must be positive"
+    );
+    let channels = input.channels().max(1) as usize;
+    SlewLimit {
+        input,
+        max_delta: max_delta_per_sample,
+        previous: vec![I::Item::zero_value(); channels],
+        channel: 0,
+    }
+}
+
+/// Filter that limits how fast the samples of each channel can change.
+#[derive(Clone, Debug)]
+pub struct SlewLimit<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    max_delta: f32,
+    // Last output sample of each channel.
+    previous: Vec<I::Item>,
+    channel: usize,
+}
+
+impl<I> SlewLimit<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl<I> Iterator for SlewLimit<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            let channels = self.input.channels().max(1) as usize;
+            self.previous.resize(channels, I::Item::zero_value());
+        }
+
+        let value = self.input.next()?;
+        let previous = self.previous[self.channel];
+        let delta = (value.to_f32() - previous.to_f32()).abs();
+        let limited = if delta > self.max_delta * LERP_STEPS as f32 {
+            // Less than one lerp step: move by one step of a finer subdivision
+            // instead. Integer samples can round that down to no movement at
+            // all, so they move by their smallest nonzero step.
+            let denominator = (delta / self.max_delta).ceil().min(u32::MAX as f32);
+            let stepped = Sample::lerp(previous, value, 1, denominator as u32);
+            if stepped.to_f32() == previous.to_f32() {
+                let denominator = (delta * SMALLEST_STEPS).max(1.0);
+                Sample::lerp(previous, value, 1, denominator as u32)
+            } else {
+                stepped
+            }
+        } else if delta > self.max_delta {
+            // Rounded down so the step never exceeds the limit.
+            let numerator = (self.max_delta / delta * LERP_STEPS as f32) as u32;
+            Sample::lerp(previous, value, numerator, LERP_STEPS)
+        } else {
+            value
+        };
+
+        self.previous[self.channel] = limited;
+        self.channel = (self.channel + 1) % self.previous.len();
+        Some(limited)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for SlewLimit<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn step_is_turned_into_a_ramp() {
+        let step = [vec![0.0f32; 4], vec![1.0f32; 16]].concat();
+        let output: Vec<f32> = SamplesBuffer::new(1, 48000, step).slew_limit(0.1).collect();
+
+        let mut previous = 0.0;
+        for &value in &output {
+            assert!(value - previous <= 0.1 + 1e-6, "{previous} -> {value}");
+            previous = value;
+        }
+        assert!(output[4] > 0.09);
+        assert!((output[19] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn channels_are_limited_separately() {
+        let input = vec![1000i16, -1000, 1000, -1000, 1000, -1000];
+        let output: Vec<i16> = SamplesBuffer::new(2, 48000, input)
+            .slew_limit(400.0 / 32768.0)
+            .collect();
+        assert_eq!(output, vec![399, -399, 798, -798, 1000, -1000]);
+    }
+
+    #[test]
+    fn very_slow_slew_still_moves() {
+        let output: Vec<f32> = SamplesBuffer::new(1, 48000, vec![1.0f32; 1000])
+            .slew_limit(1e-5)
+            .collect();
+
+        let mut previous = 0.0;
+        for &value in &output {
+            assert!(value > previous, "stalled at {previous}");
+            assert!(value - previous <= 1e-5 + 1e-9, "{previous} -> {value}");
+            previous = value;
+        }
+
+        let output: Vec<i16> = SamplesBuffer::new(1, 48000, vec![1000i16; 4])
+            .slew_limit(0.1 / 32768.0)
+            .collect();
+        assert_eq!(output, vec![1, 2, 3, 4]);
+    }
+}
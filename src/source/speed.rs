@@ -14,7 +14,7 @@
 //! ```no_run
 //!# use std::fs::File;
 //!# use std::io::BufReader;
-//!# use rodio::{Decoder, Sink, OutputStream, source::{Source, SineWave}};
+//!# use rodio::{Decoder, Sink, OutputStream, OutputStreamTrait, source::{Source, SineWave}};
 //!
 //! // Get an output stream handle to the default physical sound device.
 //! // Note that no sound will be played if _stream is dropped
@@ -25,16 +25,19 @@
 //! let source = Decoder::new(file).unwrap();
 //! // Play the sound directly on the device 2x faster
 //! stream_handle.play_raw(source.convert_samples().speed(2.0));
-
+//!
 //! std::thread::sleep(std::time::Duration::from_secs(5));
 //! ```
 //! here is how you would do it using the sink
-//! ```
+//! ```no_run
+//!# use std::time::Duration;
+//!# use rodio::{Sink, OutputStream, OutputStreamTrait, source::{Source, SineWave}};
+//!# let (_stream, stream_handle) = OutputStream::try_default().unwrap();
 //! let source = SineWave::new(440.0)
 //! .take_duration(Duration::from_secs_f32(20.25))
 //! .amplify(0.20);
 //!
-//! let sink = Sink::try_new(&stream_handle)?;
+//! let sink = Sink::try_new(&stream_handle).unwrap();
 //! sink.set_speed(2.0);
 //! sink.append(source);
 //! std::thread::sleep(std::time::Duration::from_secs(5));
@@ -141,6 +144,11 @@ where
         self.input.total_duration().map(|d| d.div_f32(self.factor))
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         let pos_accounting_for_speedup = pos.mul_f32(self.factor);
@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Internal function that builds the two halves of a split stereo source.
+pub fn split_stereo<I>(input: I) -> (SplitChannel<I>, SplitChannel<I>)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert_eq!(input.channels(), 2, "only stereo sources can be split");
+    let shared = Arc::new(Mutex::new(SplitState {
+        input,
+        pending: [VecDeque::new(), VecDeque::new()],
+        alive: [true; 2],
+    }));
+    let left = SplitChannel {
+        shared: shared.clone(),
+        channel: 0,
+    };
+    let right = SplitChannel { shared, channel: 1 };
+    (left, right)
+}
+
+struct SplitState<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    // Samples read from the input but not yet pulled, per channel.
+    pending: [VecDeque<I::Item>; 2],
+    // Whether each channel is still around to pull its samples.
+    alive: [bool; 2],
+}
+
+/// One channel of a stereo source split with [`Source::split_stereo`], as a mono source.
+pub struct SplitChannel<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    shared: Arc<Mutex<SplitState<I>>>,
+    channel: usize,
+}
+
+impl<I> SplitChannel<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns the number of samples read from the input for this channel but not yet pulled.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.shared.lock().unwrap().pending[self.channel].len()
+    }
+}
+
+impl<I> Drop for SplitChannel<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn drop(&mut self) {
+        // The other channel no longer keeps samples around for this one.
+        if let Ok(mut state) = self.shared.lock() {
+            state.alive[self.channel] = false;
+            state.pending[self.channel] = VecDeque::new();
+        }
+    }
+}
+
+impl<I> Iterator for SplitChannel<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let mut state = self.shared.lock().unwrap();
+        if let Some(value) = state.pending[self.channel].pop_front() {
+            return Some(value);
+        }
+
+        // Read a whole frame so both channels stay aligned.
+        let left = state.input.next()?;
+        let right = state.input.next()?;
+        let (own, other) = if self.channel == 0 {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        if state.alive[1 - self.channel] {
+            state.pending[1 - self.channel].push_back(other);
+        }
+        Some(own)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let state = self.shared.lock().unwrap();
+        let pending = state.pending[self.channel].len();
+        let (lower, upper) = state.input.size_hint();
+        (
+            pending + lower / 2,
+            upper.map(|upper| pending + upper.div_ceil(2)),
+        )
+    }
+}
+
+impl<I> Source for SplitChannel<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.shared.lock().unwrap().input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.shared.lock().unwrap().input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.shared.lock().unwrap().input.is_infinite()
+    }
+
+    /// Seeks the shared input, which moves both channels.
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let mut state = self.shared.lock().unwrap();
+        state.input.try_seek(pos)?;
+        state.pending.iter_mut().for_each(VecDeque::clear);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::SamplesBuffer;
+    use crate::Source;
+
+    #[test]
+    fn channels_are_separated() {
+        let stereo = SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2, 3, -3]);
+        let (mut left, mut right) = stereo.split_stereo();
+        assert_eq!(left.channels(), 1);
+        assert_eq!(right.sample_rate(), 48000);
+
+        assert_eq!(left.next(), Some(1));
+        assert_eq!(left.next(), Some(2));
+        assert_eq!(right.pending(), 2);
+        assert_eq!(right.next(), Some(-1));
+        assert_eq!(right.next(), Some(-2));
+        assert_eq!(right.next(), Some(-3));
+        assert_eq!(left.next(), Some(3));
+        assert_eq!(left.next(), None);
+        assert_eq!(right.next(), None);
+    }
+
+    #[test]
+    fn dropped_channel_is_not_buffered() {
+        let stereo = SamplesBuffer::new(2, 48000, vec![1i16, -1, 2, -2, 3, -3]);
+        let (left, mut right) = stereo.split_stereo();
+        assert_eq!(right.next(), Some(-1));
+        drop(left);
+
+        assert_eq!(right.next(), Some(-2));
+        assert_eq!(right.next(), Some(-3));
+        assert!(right.shared.lock().unwrap().pending[0].is_empty());
+    }
+}
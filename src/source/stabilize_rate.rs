@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Resolution of the interpolation between two input frames.
+const LERP_STEPS: u32 = 4096;
+
+/// Internal function that builds a `StabilizeRate` object.
+pub fn stabilize_rate<I>(input: I, target: u32) -> StabilizeRate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    assert!(target >= 1, "the target sample rate must be at least 1");
+    let channels = input.channels().max(1);
+    let mut stabilize = StabilizeRate {
+        input,
+        target,
+        channels,
+        current: vec![I::Item::zero_value(); channels as usize],
+        next: vec![I::Item::zero_value(); channels as usize],
+        position: 0.0,
+        sample_in_frame: 0,
+        ended: false,
+    };
+    stabilize.prime();
+    stabilize
+}
+
+/// Resamples a source whose sample rate may change at any time to a fixed rate.
+#[derive(Clone)]
+pub struct StabilizeRate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    input: I,
+    target: u32,
+    channels: u16,
+    // The two input frames the current output frame is interpolated between.
+    current: Vec<I::Item>,
+    next: Vec<I::Item>,
+    // Position of the output frame between `current` (0.0) and `next` (1.0).
+    position: f64,
+    sample_in_frame: u16,
+    ended: bool,
+}
+
+impl<I> StabilizeRate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Loads the first two input frames.
+    fn prime(&mut self) {
+        self.position = 0.0;
+        self.sample_in_frame = 0;
+        self.ended = !self.read_frame();
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.ended = self.ended || !self.read_frame();
+    }
+
+    /// Reads the next input frame into `next`. Returns false if the input ended.
+    fn read_frame(&mut self) -> bool {
+        for sample in self.next.iter_mut() {
+            match self.input.next() {
+                Some(value) => *sample = value,
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+impl<I> Iterator for StabilizeRate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.sample_in_frame == 0 {
+            while self.position >= 1.0 && !self.ended {
+                std::mem::swap(&mut self.current, &mut self.next);
+                self.ended = !self.read_frame();
+                self.position -= 1.0;
+            }
+            if self.ended {
+                return None;
+            }
+        }
+
+        let channel = self.sample_in_frame as usize;
+        let numerator = (self.position * LERP_STEPS as f64) as u32;
+        let value = Sample::lerp(
+            self.current[channel],
+            self.next[channel],
+            numerator,
+            LERP_STEPS,
+        );
+
+        self.sample_in_frame += 1;
+        if self.sample_in_frame == self.channels {
+            self.sample_in_frame = 0;
+            // The input rate is read again for every frame so changes apply right away.
+            self.position += self.input.sample_rate() as f64 / self.target as f64;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<I> Source for StabilizeRate<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.target
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.prime();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::Source;
+
+    /// Mono source at 1 kHz that switches to 2 kHz after `switch_at` samples, mid-frame.
+    struct RateSwitch {
+        played: usize,
+        switch_at: usize,
+        len: usize,
+    }
+
+    impl Iterator for RateSwitch {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            if self.played == self.len {
+                return None;
+            }
+            self.played += 1;
+            Some(1.0)
+        }
+    }
+
+    impl Source for RateSwitch {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            if self.played < self.switch_at {
+                1000
+            } else {
+                2000
+            }
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn output_rate_stays_constant() {
+        // 100 ms at 1 kHz followed by 100 ms at 2 kHz.
+        let input = RateSwitch {
+            played: 0,
+            switch_at: 100,
+            len: 300,
+        };
+        let mut source = input.stabilize_rate(1000);
+
+        let mut count = 0;
+        while let Some(value) = source.next() {
+            assert_eq!(source.sample_rate(), 1000);
+            assert_eq!(value, 1.0);
+            count += 1;
+        }
+        // 200 ms at 1 kHz, give or take the last frame.
+        assert!((198..=200).contains(&count), "{count}");
+    }
+}
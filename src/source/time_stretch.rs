@@ -0,0 +1,406 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::Sample as CpalSample;
+
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+/// Length of the grains that are overlapped.
+const WINDOW: Duration = Duration::from_millis(30);
+/// How far a grain may be moved from its nominal position to line up with the previous one.
+const SEEK: Duration = Duration::from_millis(8);
+/// Only every few frames are compared when lining grains up, which is plenty for the match.
+const CORRELATION_STRIDE: usize = 4;
+/// Ratios beyond these only leave fragments of the input.
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 10.0;
+
+/// Internal function that builds a `TimeStretch` object.
+pub fn stretch<I>(input: I, ratio: f32) -> TimeStretch<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let channels = input.channels().max(1);
+    let sample_rate = input.sample_rate();
+    let frames = |duration: Duration| (duration.as_secs_f32() * sample_rate as f32) as usize;
+    let window = (frames(WINDOW) / 2 * 2).max(16);
+    let handle = TimeStretchHandle(Arc::new(AtomicU32::new(0)));
+    handle.set_ratio(ratio);
+    TimeStretch {
+        input,
+        ratio: handle,
+        channels,
+        sample_rate,
+        window,
+        seek: frames(SEEK),
+        buffer: Vec::new(),
+        buffer_start: 0,
+        input_done: false,
+        nominal: 0.0,
+        previous: None,
+        tail: Vec::with_capacity(window / 2 * channels as usize),
+        output: Vec::with_capacity(window / 2 * channels as usize),
+        output_pos: 0,
+    }
+}
+
+/// Changes the ratio of a [`TimeStretch`] while it plays, see [`TimeStretch::handle`].
+#[derive(Clone, Debug)]
+pub struct TimeStretchHandle(Arc<AtomicU32>);
+
+impl TimeStretchHandle {
+    /// Sets how much faster than the original the source plays, below 1.0 slows it down. The
+    /// ratio is clamped to 0.1 up to 10 and picked up at the start of the next grain.
+    pub fn set_ratio(&self, ratio: f32) {
+        let ratio = if ratio.is_nan() {
+            1.0
+        } else {
+            ratio.clamp(MIN_RATIO, MAX_RATIO)
+        };
+        self.0.store(ratio.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns how much faster than the original the source plays.
+    pub fn ratio(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Filter that changes the tempo of a source without changing its pitch.
+///
+/// The input is cut into overlapping grains that are taken further apart or closer together
+/// than they are played back. Each grain is moved by a few milliseconds so its waveform lines
+/// up with the end of the previous one (WSOLA), which keeps tones free of phase jumps. The
+/// input is expected to keep its number of channels and sample rate, wrap it in a
+/// [`UniformSourceIterator`](crate::source::UniformSourceIterator) otherwise.
+#[derive(Clone, Debug)]
+pub struct TimeStretch<I> {
+    input: I,
+    ratio: TimeStretchHandle,
+    channels: u16,
+    sample_rate: u32,
+    // Length of a grain and the distance a grain may be moved, in frames.
+    window: usize,
+    seek: usize,
+    // Interleaved input, starting at input frame `buffer_start`.
+    buffer: Vec<f32>,
+    buffer_start: u64,
+    input_done: bool,
+    // Input frame the next grain is taken around.
+    nominal: f64,
+    // Start of the last grain in input frames.
+    previous: Option<u64>,
+    // Second half of the last grain, faded out, to be overlapped with the next grain.
+    tail: Vec<f32>,
+    // Interleaved output of the last grain.
+    output: Vec<f32>,
+    output_pos: usize,
+}
+
+impl<I> TimeStretch<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a handle to change the ratio from another thread, for example from a playback
+    /// speed slider.
+    pub fn handle(&self) -> TimeStretchHandle {
+        self.ratio.clone()
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Input frame after the last buffered one.
+    #[inline]
+    fn buffer_end(&self) -> u64 {
+        self.buffer_start + (self.buffer.len() / self.channels as usize) as u64
+    }
+
+    /// Reads the input until frame `end` is buffered or the input ends. A partial last frame
+    /// is completed with silence.
+    fn fill(&mut self, end: u64) {
+        while !self.input_done && self.buffer_end() < end {
+            for _ in 0..self.channels {
+                match self.input.next() {
+                    Some(sample) => self.buffer.push(sample.to_f32()),
+                    None => {
+                        self.input_done = true;
+                        let channels = self.channels as usize;
+                        let frames = self.buffer.len().div_ceil(channels);
+                        self.buffer.resize(frames * channels, 0.0);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns a sample of the buffered input, silence past its end.
+    #[inline]
+    fn sample(&self, frame: u64, channel: usize) -> f32 {
+        let index = (frame - self.buffer_start) as usize * self.channels as usize + channel;
+        self.buffer.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Sum of the channels of a frame, used to line up grains.
+    #[inline]
+    fn mono(&self, frame: u64) -> f32 {
+        (0..self.channels as usize)
+            .map(|channel| self.sample(frame, channel))
+            .sum()
+    }
+
+    /// Returns the start near `nominal` whose first half resembles the input at `natural` the
+    /// most, `natural` being where the last grain would have continued.
+    fn best_match(&self, nominal: u64, natural: u64) -> u64 {
+        let half = self.window / 2;
+        let score = |candidate: u64| {
+            let (mut correlation, mut energy) = (0.0, 0.0);
+            for offset in (0..half as u64).step_by(CORRELATION_STRIDE) {
+                let value = self.mono(candidate + offset);
+                correlation += value * self.mono(natural + offset);
+                energy += value * value;
+            }
+            correlation / f32::max(energy, f32::EPSILON).sqrt()
+        };
+
+        let first = nominal
+            .saturating_sub(self.seek as u64)
+            .max(self.buffer_start);
+        let mut best = (nominal, score(nominal));
+        for candidate in first..=nominal + self.seek as u64 {
+            let score = score(candidate);
+            if score > best.1 {
+                best = (candidate, score);
+            }
+        }
+        best.0
+    }
+
+    /// Overlaps the next grain with the tail of the last one. Returns false once the input
+    /// is used up.
+    fn next_grain(&mut self) -> bool {
+        self.output.clear();
+        self.output_pos = 0;
+
+        let half = self.window / 2;
+        let channels = self.channels as usize;
+        let nominal = self.nominal.round() as u64;
+        self.fill(nominal + (self.seek + self.window) as u64);
+        if self.input_done && nominal >= self.buffer_end() {
+            // Let the last grain fade out.
+            self.output.extend_from_slice(&self.tail);
+            self.tail.clear();
+            return !self.output.is_empty();
+        }
+
+        let start = match self.previous {
+            Some(previous) => self.best_match(nominal, previous + half as u64),
+            None => nominal,
+        };
+        let gain = |frame: usize| (PI * frame as f32 / self.window as f32).sin().powi(2);
+        let mut tail = std::mem::take(&mut self.tail);
+        let first_grain = tail.is_empty();
+        tail.resize(half * channels, 0.0);
+        for frame in 0..half {
+            let (fade_in, fade_out) = (gain(frame), gain(frame + half));
+            for channel in 0..channels {
+                let rising = self.sample(start + frame as u64, channel);
+                let falling = self.sample(start + (frame + half) as u64, channel);
+                let overlap = &mut tail[frame * channels + channel];
+                // The first grain starts at full volume instead of fading in.
+                self.output.push(if first_grain {
+                    rising
+                } else {
+                    *overlap + rising * fade_in
+                });
+                *overlap = falling * fade_out;
+            }
+        }
+        self.tail = tail;
+        self.previous = Some(start);
+        self.nominal += half as f64 * self.ratio.ratio() as f64;
+
+        // Drop the input that neither the next grain nor its search can reach.
+        let keep = (self.nominal.round() as u64)
+            .saturating_sub(self.seek as u64)
+            .min(start + half as u64)
+            .max(self.buffer_start)
+            .min(self.buffer_end());
+        self.buffer
+            .drain(..(keep - self.buffer_start) as usize * channels);
+        self.buffer_start = keep;
+        true
+    }
+}
+
+impl<I> Iterator for TimeStretch<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.output_pos == self.output.len() && !self.next_grain() {
+            return None;
+        }
+        let sample: <I::Item as CpalSample>::Float =
+            CpalSample::from_sample(self.output[self.output_pos]);
+        self.output_pos += 1;
+        Some(sample.to_sample())
+    }
+}
+
+impl<I> Source for TimeStretch<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input
+            .total_duration()
+            .map(|duration| duration.div_f32(self.ratio.ratio()))
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos.mul_f32(self.ratio.ratio()))?;
+        self.buffer.clear();
+        self.buffer_start = 0;
+        self.input_done = false;
+        self.nominal = 0.0;
+        self.previous = None;
+        self.tail.clear();
+        self.output.clear();
+        self.output_pos = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::buffer::SamplesBuffer;
+    use crate::source::SineWave;
+    use crate::Source;
+
+    /// Estimates the frequency of a tone from its zero crossings.
+    fn frequency(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (samples.len() as f32 / sample_rate as f32)
+    }
+
+    fn tone(seconds: usize) -> SamplesBuffer<f32> {
+        SamplesBuffer::new(
+            1,
+            48000,
+            SineWave::new(440.0)
+                .take(48000 * seconds)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn slowing_down_keeps_the_pitch() {
+        let source = tone(1).stretch(0.8);
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(1250)));
+        let output: Vec<f32> = source.collect();
+        assert!(
+            (output.len() as f32 - 60000.0).abs() < 1500.0,
+            "{}",
+            output.len()
+        );
+        let measured = frequency(&output[..57600], 48000);
+        assert!((measured - 440.0).abs() < 440.0 * 0.02, "{measured}");
+    }
+
+    #[test]
+    fn speeding_up_keeps_the_pitch() {
+        let source = tone(1).stretch(1.5);
+        let output: Vec<f32> = source.collect();
+        assert!(
+            (output.len() as f32 - 32000.0).abs() < 1500.0,
+            "{}",
+            output.len()
+        );
+        let measured = frequency(&output[..30000], 48000);
+        assert!((measured - 440.0).abs() < 440.0 * 0.02, "{measured}");
+    }
+
+    #[test]
+    fn unit_ratio_plays_the_input() {
+        let input: Vec<f32> = tone(1).collect();
+        let output: Vec<f32> = tone(1).stretch(1.0).collect();
+        assert!(output.len() >= input.len());
+        assert!(input
+            .iter()
+            .zip(&output)
+            .all(|(input, output)| (input - output).abs() < 1e-4));
+    }
+
+    #[test]
+    fn ratio_changes_while_playing() {
+        let mut source = tone(2).stretch(1.0);
+        let handle = source.handle();
+        assert_eq!(source.by_ref().take(48000).count(), 48000);
+
+        handle.set_ratio(2.0);
+        assert_eq!(handle.ratio(), 2.0);
+        let rest: Vec<f32> = source.collect();
+        assert!(
+            (rest.len() as f32 - 24000.0).abs() < 1500.0,
+            "{}",
+            rest.len()
+        );
+        let measured = frequency(&rest[..20000], 48000);
+        assert!((measured - 440.0).abs() < 440.0 * 0.02, "{measured}");
+    }
+}
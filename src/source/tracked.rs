@@ -0,0 +1,250 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver};
+
+use crate::sink::SinkEvent;
+use crate::{Sample, Source};
+
+use super::SeekError;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Progress {
+    started: bool,
+    ended: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    id: u64,
+    events: Mutex<Receiver<SinkEvent>>,
+    progress: Mutex<Progress>,
+}
+
+impl Shared {
+    fn apply(&self, event: SinkEvent) {
+        let mut progress = self.progress.lock().unwrap();
+        match event {
+            SinkEvent::TrackStarted { id } if id == self.id => progress.started = true,
+            SinkEvent::TrackEnded { id } if id == self.id => progress.ended = true,
+            _ => {}
+        }
+    }
+}
+
+/// Handle to wait for a [`Tracked`] source to start or end playing, from another thread.
+///
+/// The handle follows the [`SinkEvent`]s the source sends with its [`id`](PlaybackHandle::id),
+/// for a source appended with [`Sink::append_tracked`](crate::Sink::append_tracked) these are
+/// the events [`Sink::events`](crate::Sink::events) receives.
+#[derive(Clone, Debug)]
+pub struct PlaybackHandle(Arc<Shared>);
+
+impl PlaybackHandle {
+    /// Follows the events of the source with the given id.
+    pub(crate) fn new(id: u64, events: Receiver<SinkEvent>) -> PlaybackHandle {
+        PlaybackHandle(Arc::new(Shared {
+            id,
+            events: Mutex::new(events),
+            progress: Mutex::new(Progress::default()),
+        }))
+    }
+
+    /// Returns the id the source reports its events with.
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    /// Returns true once the first sample of the source was played.
+    pub fn has_started(&self) -> bool {
+        self.poll().started
+    }
+
+    /// Returns true once the source ended, or was dropped before it ended.
+    pub fn has_ended(&self) -> bool {
+        self.poll().ended
+    }
+
+    /// Blocks until the first sample of the source is played.
+    ///
+    /// Returns false if the source was dropped without ever playing, for example because it
+    /// was cleared from a queue before its turn came.
+    pub fn wait_for_start(&self) -> bool {
+        self.wait(|progress| progress.started || progress.ended)
+    }
+
+    /// Blocks until the source ended, or was dropped before it ended.
+    ///
+    /// Returns whether the source started playing at all.
+    pub fn wait_for_end(&self) -> bool {
+        self.wait(|progress| progress.ended)
+    }
+
+    /// Applies the events received so far and returns the progress.
+    fn poll(&self) -> Progress {
+        // A thread waiting on a clone of the handle applies the events already.
+        if let Ok(events) = self.0.events.try_lock() {
+            events.try_iter().for_each(|event| self.0.apply(event));
+        }
+        *self.0.progress.lock().unwrap()
+    }
+
+    fn wait(&self, done: impl Fn(&Progress) -> bool) -> bool {
+        let events = self.0.events.lock().unwrap();
+        loop {
+            let progress = *self.0.progress.lock().unwrap();
+            if done(&progress) {
+                return progress.started;
+            }
+            match events.recv() {
+                Ok(event) => self.0.apply(event),
+                // Without anyone left to send events the source is gone.
+                Err(_) => self.0.progress.lock().unwrap().ended = true,
+            }
+        }
+    }
+}
+
+/// Sends a [`SinkEvent`] when the inner source starts and stops playing, which a
+/// [`PlaybackHandle`] follows.
+pub struct Tracked<I> {
+    input: I,
+    id: u64,
+    send: Box<dyn Fn(SinkEvent) + Send>,
+    started: bool,
+    ended: bool,
+}
+
+impl<I> Tracked<I> {
+    /// Wraps a source, the returned handle follows its playback.
+    #[inline]
+    pub fn new(input: I) -> (Tracked<I>, PlaybackHandle) {
+        let (sender, receiver) = channel();
+        let tracked = Tracked::with_events(input, 0, move |event| {
+            // Nobody following the source is not a reason to stop playing.
+            let _ = sender.send(event);
+        });
+        (tracked, PlaybackHandle::new(0, receiver))
+    }
+
+    /// Wraps a source that reports its events with `id` to `send`.
+    #[inline]
+    pub(crate) fn with_events<F>(input: I, id: u64, send: F) -> Tracked<I>
+    where
+        F: Fn(SinkEvent) + Send + 'static,
+    {
+        Tracked {
+            input,
+            id,
+            send: Box::new(send),
+            started: false,
+            ended: false,
+        }
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    fn end(&mut self) {
+        if !self.ended {
+            self.ended = true;
+            (self.send)(SinkEvent::TrackEnded { id: self.id });
+        }
+    }
+}
+
+impl<I> fmt::Debug for Tracked<I>
+where
+    I: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tracked")
+            .field("input", &self.input)
+            .field("id", &self.id)
+            .field("started", &self.started)
+            .field("ended", &self.ended)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I> Drop for Tracked<I> {
+    fn drop(&mut self) {
+        self.end();
+    }
+}
+
+impl<I> Iterator for Tracked<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.input.next();
+        match next {
+            Some(_) if !self.started => {
+                self.started = true;
+                (self.send)(SinkEvent::TrackStarted { id: self.id });
+            }
+            None => self.end(),
+            Some(_) => {}
+        }
+        next
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Tracked<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
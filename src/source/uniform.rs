@@ -24,6 +24,7 @@ where
     target_channels: u16,
     target_sample_rate: u32,
     total_duration: Option<Duration>,
+    is_infinite: bool,
 }
 
 impl<I, D> UniformSourceIterator<I, D>
@@ -41,6 +42,7 @@ where
         target_sample_rate: u32,
     ) -> UniformSourceIterator<I, D> {
         let total_duration = input.total_duration();
+        let is_infinite = input.is_infinite();
         let input = UniformSourceIterator::bootstrap(input, target_channels, target_sample_rate);
 
         UniformSourceIterator {
@@ -48,6 +50,7 @@ where
             target_channels,
             target_sample_rate,
             total_duration,
+            is_infinite,
         }
     }
 
@@ -142,6 +145,11 @@ where
         self.total_duration
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.is_infinite
+    }
+
     #[inline]
     fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
         if let Some(input) = self.inner.as_mut() {
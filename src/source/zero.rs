@@ -84,6 +84,11 @@ where
         None
     }
 
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.num_samples.is_none()
+    }
+
     #[inline]
     fn try_seek(&mut self, _: Duration) -> Result<(), SeekError> {
         Ok(())
@@ -0,0 +1,230 @@
+//! Controls for a single source played directly on the output mixer.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::source::SeekError;
+use crate::{Sample, Source};
+
+/// Handle to a source played with
+/// [`OutputStreamHandle::play_raw_controlled`](crate::OutputStreamHandle::play_raw_controlled).
+///
+/// The handle can be cloned and used from any thread; changes are picked up by the audio thread
+/// at the start of the next frame, so the channels of a frame are always played together.
+/// Dropping the handle does not stop the source, call [`stop`](SourceControl::stop) for that.
+#[derive(Clone, Debug)]
+pub struct SourceControl {
+    shared: Arc<ControlState>,
+}
+
+#[derive(Debug)]
+struct ControlState {
+    paused: AtomicBool,
+    stopped: AtomicBool,
+    // `f32` volume stored as its bits.
+    volume: AtomicU32,
+}
+
+impl SourceControl {
+    /// Pauses the source. It outputs silence and does not advance until resumed.
+    pub fn pause(&self) {
+        self.shared.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes a paused source. No effect if it is not paused.
+    pub fn resume(&self) {
+        self.shared.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns true if the source is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::Relaxed)
+    }
+
+    /// Changes the volume of the source. `1.0` is the original volume.
+    pub fn set_volume(&self, volume: f32) {
+        self.shared
+            .volume
+            .store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the volume of the source.
+    pub fn volume(&self) -> f32 {
+        f32::from_bits(self.shared.volume.load(Ordering::Relaxed))
+    }
+
+    /// Stops the source and removes it from the mixer.
+    pub fn stop(&self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Wraps `input` so it can be controlled by the returned handle.
+pub(crate) fn controlled<I>(input: I) -> (Controlled<I>, SourceControl)
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let control = SourceControl {
+        shared: Arc::new(ControlState {
+            paused: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            volume: AtomicU32::new(1.0f32.to_bits()),
+        }),
+    };
+    let source = Controlled {
+        input,
+        shared: control.shared.clone(),
+        channel: 0,
+        frame_len: 0,
+        paused: false,
+        volume: 1.0,
+    };
+    (source, control)
+}
+
+/// Source played with a [`SourceControl`].
+pub(crate) struct Controlled<I> {
+    input: I,
+    shared: Arc<ControlState>,
+    // Position in the current frame and its number of channels.
+    channel: u16,
+    frame_len: u16,
+    // Controls read at the start of the current frame.
+    paused: bool,
+    volume: f32,
+}
+
+impl<I> Iterator for Controlled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            if self.shared.stopped.load(Ordering::Relaxed) {
+                return None;
+            }
+            self.paused = self.shared.paused.load(Ordering::Relaxed);
+            self.volume = f32::from_bits(self.shared.volume.load(Ordering::Relaxed));
+            self.frame_len = self.input.channels().max(1);
+        }
+        let value = if self.paused {
+            I::Item::zero_value()
+        } else {
+            self.input.next()?.amplify(self.volume)
+        };
+        self.channel += 1;
+        if self.channel >= self.frame_len {
+            self.channel = 0;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<I> Source for Controlled<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        // Depends on how long the source is paused.
+        None
+    }
+
+    #[inline]
+    fn is_infinite(&self) -> bool {
+        self.input.is_infinite()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Weak};
+
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::stream::OutputStreamHandle;
+
+    #[test]
+    fn pause_one_source_while_another_plays() {
+        let (mixer, mut rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&mixer),
+            controls: Weak::new(),
+        };
+
+        handle
+            .play_raw(SamplesBuffer::new(1, 48000, vec![1.0f32; 10]))
+            .unwrap();
+        let control = handle
+            .play_raw_controlled(SamplesBuffer::new(
+                1,
+                48000,
+                vec![10.0f32, 20.0, 30.0, 40.0],
+            ))
+            .unwrap();
+
+        assert_eq!(rx.next(), Some(11.0));
+        control.pause();
+        assert_eq!(rx.next(), Some(1.0));
+        assert_eq!(rx.next(), Some(1.0));
+        control.resume();
+        control.set_volume(0.5);
+        assert_eq!(rx.next(), Some(11.0));
+        control.stop();
+        assert_eq!(rx.next(), Some(1.0));
+    }
+
+    #[test]
+    fn pause_waits_for_the_end_of_the_frame() {
+        let (mixer, mut rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&mixer),
+            controls: Weak::new(),
+        };
+        let control = handle
+            .play_raw_controlled(SamplesBuffer::new(2, 48000, [1.0f32, 2.0].repeat(4)))
+            .unwrap();
+
+        assert_eq!(rx.next(), Some(1.0));
+        control.pause();
+        assert_eq!(rx.next(), Some(2.0));
+        assert_eq!(rx.by_ref().take(4).collect::<Vec<_>>(), [0.0; 4]);
+        control.resume();
+        assert_eq!(
+            rx.by_ref().take(4).collect::<Vec<_>>(),
+            [1.0, 2.0, 1.0, 2.0]
+        );
+    }
+}
@@ -1,26 +1,45 @@
 use std::io::{Read, Seek};
-use std::sync::{Arc, Weak};
-use std::{error, fmt};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::{error, fmt, mem};
+
+#[cfg(feature = "crossbeam-channel")]
+use crossbeam_channel::{unbounded as channel, Receiver, Sender};
+#[cfg(not(feature = "crossbeam-channel"))]
+use std::sync::mpsc::{channel, Receiver, Sender};
 
 use crate::decoder;
-use crate::dynamic_mixer::{self, DynamicMixerController};
+use crate::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use crate::meter::{MeterHandle, OutputMeter};
+use crate::output_manager::AutoReconnectStream;
+#[cfg(feature = "wav")]
+use crate::recording::{RecordingFormat, RecordingHandle};
 use crate::sink::Sink;
 use crate::source::Source;
+use crate::source_control::{self, SourceControl};
+use crate::tap::{OutputTap, OutputTaps};
+use crate::Sample;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{DefaultStreamConfigError, Sample, SupportedStreamConfig};
-
+use cpal::SupportedStreamConfig;
 
+/// Common interface of the output backends (cpal, gstreamer).
 pub trait OutputStreamTrait {
+    /// The stream type returned by the constructors.
     type SelfHandle;
+    /// The device type the stream can be opened on.
     type OutputDevice;
-    fn try_default() -> Result<(Self::SelfHandle, OutputStreamHandle), StreamError>; 
-    fn try_from_device(device: &Self::OutputDevice) -> Result<(Self::SelfHandle, OutputStreamHandle), StreamError>; 
+    /// Returns a new stream & handle using the default output device.
+    fn try_default() -> Result<(Self::SelfHandle, OutputStreamHandle), StreamError>;
+    /// Returns a new stream & handle using the given output device.
+    fn try_from_device(device: &Self::OutputDevice) -> Result<(Self::SelfHandle, OutputStreamHandle), StreamError>;
 }
 /// `cpal::Stream` container. Also see the more useful `OutputStreamHandle`.
 ///
 /// If this is dropped playback will end & attached `OutputStreamHandle`s will no longer work.
 pub struct OutputStream {
     mixer: Arc<DynamicMixerController<f32>>,
+    controls: Arc<OutputControls>,
+    config: SupportedStreamConfig,
     _stream: cpal::Stream,
 }
 
@@ -65,6 +84,9 @@ impl OutputStreamTrait for OutputStream {
 #[derive(Clone)]
 pub struct OutputStreamHandle {
     pub(crate) mixer: Weak<DynamicMixerController<f32>>,
+    /// Controls of the device stream the mixer plays on, dangling for mixers that are not
+    /// played by one device directly.
+    pub(crate) controls: Weak<OutputControls>,
 }
 
 impl OutputStream {
@@ -79,6 +101,26 @@ impl OutputStream {
         OutputStream::try_from_device_config(device, default_config)
     }
 
+    /// Returns a stream & handle on the default output device that moves to the new default
+    /// device when the current one is gone, see [`AutoReconnectStream`].
+    pub fn try_default_auto_reconnect(
+    ) -> Result<(AutoReconnectStream, OutputStreamHandle), StreamError> {
+        AutoReconnectStream::try_default()
+    }
+
+    /// Returns a new stream & handle using the output device with the given name and its
+    /// default output configuration, see [`DeviceInfo::name`].
+    ///
+    /// Meant to reopen a device the user picked earlier. Returns [`StreamError::NoDevice`] if
+    /// no device has that name anymore, for example because it was unplugged.
+    pub fn try_from_device_name(name: &str) -> Result<(Self, OutputStreamHandle), StreamError> {
+        let info = output_devices()?
+            .into_iter()
+            .find(|info| info.name() == name)
+            .ok_or(StreamError::NoDevice)?;
+        OutputStream::try_from_device(info.device())
+    }
+
     /// Returns a new stream & handle using the given device and stream config.
     ///
     /// If the supplied `SupportedStreamConfig` is invalid for the device this function will
@@ -87,15 +129,151 @@ impl OutputStream {
         device: &cpal::Device,
         config: SupportedStreamConfig,
     ) -> Result<(Self, OutputStreamHandle), StreamError> {
-        let (mixer, _stream) = device.try_new_output_stream_config(config)?;
+        OutputStream::try_from_device_config_with_buffer(device, config, cpal::BufferSize::Default)
+    }
+
+    /// Returns a new stream & handle using the given device, stream config and buffer size.
+    ///
+    /// The buffer size is the number of frames the device asks for at a time, and with that
+    /// the latency of the output: a sound played now starts once the buffers queued before
+    /// it are played. A small buffer gives a low latency, as a rhythm game needs, but leaves
+    /// the mixer little time to fill the next one; when it is late the output underruns, which
+    /// is heard as clicks or dropouts. [`cpal::BufferSize::Default`] leaves the choice to the
+    /// device.
+    ///
+    /// Returns [`StreamError::BuildStreamError`] if a fixed size is out of the range the
+    /// config supports.
+    pub fn try_from_device_config_with_buffer(
+        device: &cpal::Device,
+        config: SupportedStreamConfig,
+        buffer_size: cpal::BufferSize,
+    ) -> Result<(Self, OutputStreamHandle), StreamError> {
+        if let (cpal::BufferSize::Fixed(frames), cpal::SupportedBufferSize::Range { min, max }) =
+            (&buffer_size, config.buffer_size())
+        {
+            if !(min..=max).contains(&frames) {
+                return Err(StreamError::BuildStreamError(
+                    cpal::BuildStreamError::StreamConfigNotSupported,
+                ));
+            }
+        }
+        let (mixer, controls, config, _stream) =
+            device.try_new_output_stream_config(config, buffer_size)?;
         _stream.play().map_err(StreamError::PlayStreamError)?;
-        let out = Self { mixer, _stream };
+        let out = Self {
+            mixer,
+            controls,
+            config,
+            _stream,
+        };
         let handle = OutputStreamHandle {
             mixer: Arc::downgrade(&out.mixer),
+            controls: Arc::downgrade(&out.controls),
         };
         Ok((out, handle))
     }
 
+    /// Returns a tap that receives a copy of everything this stream plays.
+    ///
+    /// See [`OutputTap`] for how buffers are delivered.
+    pub fn output_tap(&self) -> OutputTap {
+        self.controls.taps.tap()
+    }
+
+    /// Pauses the device, everything played on this stream stops where it is.
+    ///
+    /// Unlike pausing every [`Sink`] this stops the audio callback, so the stream uses no CPU
+    /// until it is resumed, for example while the app is in the background. Not every backend
+    /// supports pausing, on those this returns an error and the stream keeps playing.
+    pub fn pause(&self) -> Result<(), StreamError> {
+        self._stream.pause().map_err(StreamError::PauseStreamError)
+    }
+
+    /// Resumes the device after [`pause`](OutputStream::pause).
+    pub fn resume(&self) -> Result<(), StreamError> {
+        self._stream.play().map_err(StreamError::PlayStreamError)
+    }
+
+    /// Returns a receiver for the errors the device reports while the stream plays.
+    ///
+    /// For example [`cpal::StreamError::DeviceNotAvailable`] when a USB or Bluetooth device
+    /// was disconnected; the stream is silent from then on, so this is the moment to ask the
+    /// user for another device or to open a new stream. Only the receiver returned last gets
+    /// the errors. They are also logged as before.
+    pub fn error_events(&self) -> Receiver<StreamError> {
+        let (tx, rx) = channel();
+        *self.controls.errors.lock().unwrap() = Some(tx);
+        rx
+    }
+
+    /// Returns the sample format the device is actually fed with.
+    ///
+    /// Mixing always happens in `f32`. When the requested config could not be opened, another
+    /// supported config is used instead, which on some devices only accepts integer samples.
+    /// The mixed output is then converted to this format right before it is sent to the device.
+    #[inline]
+    pub fn sample_format(&self) -> cpal::SampleFormat {
+        self.config.sample_format()
+    }
+
+    /// Returns the configuration the device was actually opened with.
+    ///
+    /// When the requested config could not be opened another supported one is used, so the
+    /// sample rate, channel count and sample format may differ from what was asked for. Sounds
+    /// of another format are converted to this one.
+    #[inline]
+    pub fn config(&self) -> &SupportedStreamConfig {
+        &self.config
+    }
+
+    /// Installs a callback that processes the mixed output right before it is sent to the device.
+    ///
+    /// The callback receives the interleaved `f32` samples of one device buffer, the channel
+    /// count and the sample rate of the stream, and may change the samples in place. Use it for
+    /// effects on the master bus that do not fit a [`Source`]. Output taps and recordings
+    /// receive the processed samples. Installing a new callback replaces the previous one.
+    ///
+    /// The callback runs on the realtime audio thread. It must not allocate, lock or block in
+    /// any other way, and must return quickly, otherwise the output glitches. The audio thread
+    /// hands the replaced callback back instead of dropping it, it is dropped by the next call
+    /// to this method or along with the stream.
+    pub fn set_post_process<F>(&self, post_process: F)
+    where
+        F: FnMut(&mut [f32], u16, u32) + Send + 'static,
+    {
+        self.controls.set_post_process(Box::new(post_process));
+    }
+
+    /// Starts recording everything this stream plays to a 32 bit float WAV file at `path`.
+    ///
+    /// See [`RecordingHandle`] for the format of the file.
+    #[cfg(feature = "wav")]
+    pub fn start_recording<P>(&self, path: P) -> std::io::Result<RecordingHandle>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        self.start_recording_with_format(path, RecordingFormat::F32)
+    }
+
+    /// Starts recording everything this stream plays to a WAV file at `path`, with samples
+    /// encoded as `format`.
+    #[cfg(feature = "wav")]
+    pub fn start_recording_with_format<P>(
+        &self,
+        path: P,
+        format: RecordingFormat,
+    ) -> std::io::Result<RecordingHandle>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        RecordingHandle::start(
+            self.output_tap(),
+            self.mixer.channels(),
+            self.mixer.sample_rate(),
+            path.as_ref(),
+            format,
+        )
+    }
 }
 
 impl OutputStreamHandle {
@@ -109,6 +287,125 @@ impl OutputStreamHandle {
         Ok(())
     }
 
+    /// Plays a source with a device and returns a handle to pause, resume, change the volume of
+    /// or stop just this source.
+    ///
+    /// This is lighter than a [`Sink`] for single persistent sounds such as a looping engine
+    /// hum. Dropping the returned [`SourceControl`] does not stop the source.
+    pub fn play_raw_controlled<S>(&self, source: S) -> Result<SourceControl, PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let (source, control) = source_control::controlled(source);
+        self.play_raw(source)?;
+        Ok(control)
+    }
+
+    /// Plays a source with a device until it ends, bypassing the mixer's sample rate and
+    /// channel conversion.
+    ///
+    /// This is the lowest latency way to play a sound but the source must already match the
+    /// output format exactly: its `channels()` and `sample_rate()` must equal the stream's and
+    /// must not change while playing. Otherwise [`PlayError::FormatMismatch`] is returned and
+    /// nothing is played.
+    pub fn play_raw_direct<S>(&self, source: S) -> Result<(), PlayError>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer
+            .add_direct(source)
+            .map_err(|_| PlayError::FormatMismatch {
+                channels: mixer.channels(),
+                sample_rate: mixer.sample_rate(),
+            })
+    }
+
+    /// Changes the master volume, a gain applied to everything played through this handle
+    /// right before it goes to the device, independent of the volume of each sink.
+    ///
+    /// Negative values are treated as zero. Does nothing once the stream was dropped.
+    pub fn set_master_volume(&self, volume: f32) {
+        if let Some(mixer) = self.mixer.upgrade() {
+            mixer.set_volume(volume);
+        }
+    }
+
+    /// Returns the master volume, 1.0 by default or once the stream was dropped.
+    pub fn master_volume(&self) -> f32 {
+        self.mixer.upgrade().map_or(1.0, |mixer| mixer.volume())
+    }
+
+    /// Mixes all channels to one and plays it on every channel, for listeners who hear with
+    /// one ear only. See [`DynamicMixerController::set_mono`].
+    ///
+    /// Does nothing once the stream was dropped.
+    pub fn set_mono(&self, mono: bool) {
+        if let Some(mixer) = self.mixer.upgrade() {
+            mixer.set_mono(mono);
+        }
+    }
+
+    /// Returns true while the output is mixed to mono.
+    pub fn is_mono(&self) -> bool {
+        self.mixer.upgrade().is_some_and(|mixer| mixer.is_mono())
+    }
+
+    /// Returns a meter of the peak and RMS level of each channel of the output.
+    ///
+    /// The levels are measured in the audio callback over each buffer sent to the device, so
+    /// they update at the rate the device asks for buffers. See [`MeterHandle::read`].
+    pub fn peak_meter(&self) -> MeterHandle {
+        MeterHandle {
+            controls: self.controls.clone(),
+        }
+    }
+
+    /// Returns the number of sources the mixer of the stream summed in its last pass, see
+    /// [`DynamicMixerController::active_count`]. Zero once the stream was dropped.
+    pub fn active_count(&self) -> usize {
+        self.mixer.upgrade().map_or(0, |mixer| mixer.active_count())
+    }
+
+    /// Starts recording everything the stream plays to a 32 bit float WAV file at `path`.
+    ///
+    /// Fails with [`std::io::ErrorKind::NotConnected`] once the stream was dropped, or if
+    /// the handle does not belong to a stream that plays on a device. See
+    /// [`OutputStream::start_recording`].
+    #[cfg(feature = "wav")]
+    pub fn start_recording<P>(&self, path: P) -> std::io::Result<RecordingHandle>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        self.start_recording_with_format(path, RecordingFormat::F32)
+    }
+
+    /// Starts recording everything the stream plays to a WAV file at `path`, with samples
+    /// encoded as `format`. See [`OutputStreamHandle::start_recording`].
+    #[cfg(feature = "wav")]
+    pub fn start_recording_with_format<P>(
+        &self,
+        path: P,
+        format: RecordingFormat,
+    ) -> std::io::Result<RecordingHandle>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let (Some(mixer), Some(controls)) = (self.mixer.upgrade(), self.controls.upgrade()) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "the output stream was dropped",
+            ));
+        };
+        RecordingHandle::start(
+            controls.taps.tap(),
+            mixer.channels(),
+            mixer.sample_rate(),
+            path.as_ref(),
+            format,
+        )
+    }
+
     /// Plays a sound once. Returns a `Sink` that can be used to control the sound.
     pub fn play_once<R>(&self, input: R) -> Result<Sink, PlayError>
     where
@@ -121,6 +418,98 @@ impl OutputStreamHandle {
     }
 }
 
+/// Output stream that mixes in the sample format of the device, without converting to `f32`.
+///
+/// [`OutputStream`] always mixes `f32` samples and converts them for the device. This stream
+/// instead mixes `S` samples, for example `i16`, and hands them to the device unchanged. It only
+/// opens configurations of the device that take `S` samples, and plays whatever is added to its
+/// [`mixer`](NativeOutputStream::mixer); a [`Sink`], output taps and post-processing are only
+/// available on [`OutputStream`].
+///
+/// Integer mixing avoids the conversions of an integer-only pipeline, but trades precision
+/// for it: the mixer sums with saturation, so several loud sources clip instead of exceeding
+/// full scale, and every volume change or fade rounds to the nearest integer sample. Adapters
+/// that compute in floating point still convert internally. Unless the rest of the pipeline
+/// is integer anyway, mixing in `f32` is as fast and more accurate.
+///
+/// If this is dropped playback ends.
+pub struct NativeOutputStream<S> {
+    mixer: Arc<DynamicMixerController<S>>,
+    _stream: cpal::Stream,
+}
+
+impl<S> NativeOutputStream<S>
+where
+    S: Sample + cpal::SizedSample + Send + 'static,
+{
+    /// Opens the device with a configuration that takes `S` samples.
+    ///
+    /// The sample rate of the default configuration is kept if possible. Fails with
+    /// [`cpal::BuildStreamError::StreamConfigNotSupported`] if the device takes no `S` samples.
+    pub fn try_from_device(device: &cpal::Device) -> Result<Self, StreamError> {
+        let default_rate = device
+            .default_output_config()
+            .map_err(StreamError::DefaultStreamConfigError)?
+            .sample_rate();
+        let mut supported: Vec<_> = device
+            .supported_output_configs()
+            .map_err(StreamError::SupportedStreamConfigsError)?
+            .filter(|config| config.sample_format() == S::FORMAT)
+            .collect();
+        supported.sort_by(|a, b| b.cmp_default_heuristics(a));
+        let config = supported
+            .into_iter()
+            .map(|config| {
+                if (config.min_sample_rate()..=config.max_sample_rate()).contains(&default_rate) {
+                    config.with_sample_rate(default_rate)
+                } else {
+                    config.with_max_sample_rate()
+                }
+            })
+            .next()
+            .ok_or(StreamError::BuildStreamError(
+                cpal::BuildStreamError::StreamConfigNotSupported,
+            ))?;
+
+        let (mixer, mut mixer_rx) =
+            dynamic_mixer::mixer::<S>(config.channels(), config.sample_rate().0);
+        let stream = device
+            .build_output_stream::<S, _, _>(
+                &config.config(),
+                move |data, _| fill_native(&mut mixer_rx, data),
+                |err| {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("an error occurred on output stream: {err}");
+                    #[cfg(not(feature = "tracing"))]
+                    eprintln!("an error occurred on output stream: {err}");
+                },
+                None,
+            )
+            .map_err(StreamError::BuildStreamError)?;
+        stream.play().map_err(StreamError::PlayStreamError)?;
+        Ok(NativeOutputStream {
+            mixer,
+            _stream: stream,
+        })
+    }
+
+    /// Returns the mixer feeding the device, add sources of `S` samples to it to play them.
+    #[inline]
+    pub fn mixer(&self) -> &Arc<DynamicMixerController<S>> {
+        &self.mixer
+    }
+}
+
+/// Fills a device buffer with the mixed samples, silence once the mixer has none.
+fn fill_native<S>(mixer: &mut DynamicMixer<S>, data: &mut [S])
+where
+    S: Sample + Send + 'static,
+{
+    for d in data.iter_mut() {
+        *d = mixer.next().unwrap_or_else(S::zero_value);
+    }
+}
+
 /// An error occurred while attempting to play a sound.
 #[derive(Debug)]
 pub enum PlayError {
@@ -128,6 +517,14 @@ pub enum PlayError {
     DecoderError(decoder::DecoderError),
     /// The output device was lost.
     NoDevice,
+    /// The source does not match the output format required by
+    /// [`OutputStreamHandle::play_raw_direct`].
+    FormatMismatch {
+        /// Number of channels of the output.
+        channels: u16,
+        /// Sample rate of the output.
+        sample_rate: u32,
+    },
 }
 
 impl From<decoder::DecoderError> for PlayError {
@@ -141,6 +538,13 @@ impl fmt::Display for PlayError {
         match self {
             Self::DecoderError(e) => e.fmt(f),
             Self::NoDevice => write!(f, "NoDevice"),
+            Self::FormatMismatch {
+                channels,
+                sample_rate,
+            } => write!(
+                f,
+                "source format does not match the output ({channels} channels, {sample_rate} Hz)"
+            ),
         }
     }
 }
@@ -150,6 +554,7 @@ impl error::Error for PlayError {
         match self {
             Self::DecoderError(e) => Some(e),
             Self::NoDevice => None,
+            Self::FormatMismatch { .. } => None,
         }
     }
 }
@@ -160,6 +565,8 @@ pub enum StreamError {
     /// Could not start playing the stream, see [cpal::PlayStreamError] for
     /// details.
     PlayStreamError(cpal::PlayStreamError),
+    /// Could not pause the stream, see [cpal::PauseStreamError] for details.
+    PauseStreamError(cpal::PauseStreamError),
     /// Failed to get the stream config for device the given device. See
     /// [cpal::DefaultStreamConfigError] for details
     DefaultStreamConfigError(cpal::DefaultStreamConfigError),
@@ -168,6 +575,13 @@ pub enum StreamError {
     /// Could not list supported stream configs for device. Maybe it
     /// disconnected, for details see: [cpal::SupportedStreamConfigsError].
     SupportedStreamConfigsError(cpal::SupportedStreamConfigsError),
+    /// Could not list the output devices, see [cpal::DevicesError] for details.
+    DevicesError(cpal::DevicesError),
+    /// The device reported an error while the stream was playing, see [cpal::StreamError]
+    /// for details.
+    PlaybackError(cpal::StreamError),
+    /// Could not hand a source to the opened stream, see [PlayError] for details.
+    PlayError(PlayError),
     /// Could not find any output device
     NoDevice,
 }
@@ -176,9 +590,13 @@ impl fmt::Display for StreamError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::PlayStreamError(e) => e.fmt(f),
+            Self::PauseStreamError(e) => e.fmt(f),
             Self::BuildStreamError(e) => e.fmt(f),
             Self::DefaultStreamConfigError(e) => e.fmt(f),
             Self::SupportedStreamConfigsError(e) => e.fmt(f),
+            Self::DevicesError(e) => e.fmt(f),
+            Self::PlaybackError(e) => e.fmt(f),
+            Self::PlayError(e) => e.fmt(f),
             Self::NoDevice => write!(f, "NoDevice"),
         }
     }
@@ -188,181 +606,340 @@ impl error::Error for StreamError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Self::PlayStreamError(e) => Some(e),
+            Self::PauseStreamError(e) => Some(e),
             Self::BuildStreamError(e) => Some(e),
             Self::DefaultStreamConfigError(e) => Some(e),
             Self::SupportedStreamConfigsError(e) => Some(e),
+            Self::DevicesError(e) => Some(e),
+            Self::PlaybackError(e) => Some(e),
+            Self::PlayError(e) => Some(e),
             Self::NoDevice => None,
         }
     }
 }
 
+/// An output device listed by [`output_devices`].
+#[derive(Clone)]
+pub struct DeviceInfo {
+    name: String,
+    device: cpal::Device,
+    is_default: bool,
+}
+
+impl DeviceInfo {
+    /// Returns the name of the device, as the operating system shows it.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the device, to open a stream on it with [`OutputStream::try_from_device`].
+    pub fn device(&self) -> &cpal::Device {
+        &self.device
+    }
+
+    /// Returns true for the device [`OutputStream::try_default`] tries first.
+    pub fn is_default(&self) -> bool {
+        self.is_default
+    }
+}
+
+/// Lists the output devices of the default host, for example to let the user pick one.
+///
+/// Devices whose name can not be read, for example because they were just disconnected, are
+/// left out. The default device is recognized by its name, so with several devices of the same
+/// name all of them are marked as the default.
+pub fn output_devices() -> Result<Vec<DeviceInfo>, StreamError> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_output_device()
+        .and_then(|device| device.name().ok());
+    let devices = host.output_devices().map_err(StreamError::DevicesError)?;
+    Ok(devices
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some(DeviceInfo {
+                is_default: Some(&name) == default_name.as_ref(),
+                name,
+                device,
+            })
+        })
+        .collect())
+}
+
+/// Callback run on the mixed output, see [`OutputStream::set_post_process`].
+pub(crate) type PostProcess = Box<dyn FnMut(&mut [f32], u16, u32) + Send>;
+
+/// Exchanges post-processors between the user and the audio thread, so that a replaced one
+/// is never dropped by the audio callback.
+#[derive(Default)]
+pub(crate) enum PostProcessSlot {
+    #[default]
+    Empty,
+    /// Waiting to be picked up by the audio callback.
+    Pending(PostProcess),
+    /// Replaced by the audio callback, waiting to be dropped by the user thread.
+    Retired(#[allow(dead_code)] PostProcess),
+}
+
+/// State shared between an output stream and its audio callback.
+#[derive(Default)]
+pub(crate) struct OutputControls {
+    pub(crate) taps: OutputTaps,
+    /// Post-processor waiting to be picked up by the audio callback, or the one it replaced.
+    pub(crate) post_process: Mutex<PostProcessSlot>,
+    /// Number of buffers the mixer could not fill because a source ran dry while playing.
+    pub(crate) underruns: AtomicU64,
+    /// Number of times the backend reported it had more data queued than it could take.
+    #[cfg(feature = "gstreamer")]
+    pub(crate) overruns: AtomicU64,
+    /// Receives the errors of the device, see [`OutputStream::error_events`].
+    pub(crate) errors: Mutex<Option<Sender<StreamError>>>,
+    /// Levels of the last buffer, see [`OutputStreamHandle::peak_meter`].
+    pub(crate) meter: OutputMeter,
+}
+
+impl OutputControls {
+    /// Hands a new post-processor to the audio callback, and drops the one it replaced last.
+    pub(crate) fn set_post_process(&self, post_process: PostProcess) {
+        *self.post_process.lock().unwrap() = PostProcessSlot::Pending(post_process);
+    }
+}
+
+/// Runs in the audio callback: pulls the mixed samples and writes them to the device buffer.
+pub(crate) struct OutputProcessor {
+    mixer: DynamicMixer<f32>,
+    controls: Arc<OutputControls>,
+    buffer: Vec<f32>,
+    post_process: Option<PostProcess>,
+}
+
+impl OutputProcessor {
+    pub(crate) fn new(mixer: DynamicMixer<f32>, controls: Arc<OutputControls>) -> Self {
+        controls.meter.set_channels(mixer.channels());
+        OutputProcessor {
+            mixer,
+            controls,
+            buffer: Vec::new(),
+            post_process: None,
+        }
+    }
+
+    /// Fills `data` with the next samples of the mixer, converted to the device format.
+    pub(crate) fn fill<T>(&mut self, data: &mut [T])
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        self.fill_f32(data.len());
+        for (d, s) in data.iter_mut().zip(&self.buffer) {
+            *d = T::from_sample(*s);
+        }
+    }
+
+    /// Pulls `len` mixed samples into the internal buffer, runs the post-processor, measures
+    /// the levels and hands the samples to the taps.
+    pub(crate) fn fill_f32(&mut self, len: usize) -> &[f32] {
+        self.buffer.clear();
+        let mixer = &mut self.mixer;
+        let mut starved = false;
+        self.buffer.extend((0..len).map(|_| {
+            mixer.next().unwrap_or_else(|| {
+                starved = true;
+                0f32
+            })
+        }));
+        // The mixer also runs out when nothing plays or the last sound ended, only a source
+        // that should still be playing running dry is an underrun.
+        if self.mixer.take_ran_dry() && starved {
+            self.controls.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Never wait for the user thread, a new post-processor is picked up on a later call.
+        if let Ok(mut slot) = self.controls.post_process.try_lock() {
+            // Only moves the boxes, the replaced post-processor is dropped by the user thread.
+            match mem::take(&mut *slot) {
+                PostProcessSlot::Pending(post_process) => {
+                    if let Some(replaced) = self.post_process.replace(post_process) {
+                        *slot = PostProcessSlot::Retired(replaced);
+                    }
+                }
+                other => *slot = other,
+            }
+        }
+        if let Some(post_process) = &mut self.post_process {
+            post_process(
+                &mut self.buffer,
+                self.mixer.channels(),
+                self.mixer.sample_rate(),
+            );
+        }
+
+        self.controls.meter.update(&self.buffer);
+        self.controls.taps.send(&self.buffer);
+        &self.buffer
+    }
+}
+
+impl Drop for OutputProcessor {
+    fn drop(&mut self) {
+        self.controls.taps.close();
+    }
+}
+
 /// Extensions to `cpal::Device`
 pub(crate) trait CpalDeviceExt {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
-    ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError>;
+        buffer_size: cpal::BufferSize,
+    ) -> Result<StreamParts, cpal::BuildStreamError>;
 
     fn try_new_output_stream_config(
         &self,
         config: cpal::SupportedStreamConfig,
-    ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError>;
+        buffer_size: cpal::BufferSize,
+    ) -> Result<StreamParts, StreamError>;
 }
 
+type StreamParts = (
+    Arc<DynamicMixerController<f32>>,
+    Arc<OutputControls>,
+    SupportedStreamConfig,
+    cpal::Stream,
+);
+
 impl CpalDeviceExt for cpal::Device {
     fn new_output_stream_with_format(
         &self,
         format: cpal::SupportedStreamConfig,
-    ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), cpal::BuildStreamError> {
-        let (mixer_tx, mut mixer_rx) =
+        buffer_size: cpal::BufferSize,
+    ) -> Result<StreamParts, cpal::BuildStreamError> {
+        let (mixer_tx, mixer_rx) =
             dynamic_mixer::mixer::<f32>(format.channels(), format.sample_rate().0);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
 
-        let error_callback = |err| {
+        let errors = controls.clone();
+        let error_callback = move |err| {
             #[cfg(feature = "tracing")]
             tracing::error!("an error occurred on output stream: {err}");
             #[cfg(not(feature = "tracing"))]
             eprintln!("an error occurred on output stream: {err}");
+            if let Some(sender) = errors.errors.lock().unwrap().as_ref() {
+                let _ = sender.send(StreamError::PlaybackError(err));
+            }
         };
 
+        let config = cpal::StreamConfig {
+            buffer_size,
+            ..format.config()
+        };
         match format.sample_format() {
             cpal::SampleFormat::F32 => self.build_output_stream::<f32, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().unwrap_or(0f32))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::F64 => self.build_output_stream::<f64, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0f64))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I8 => self.build_output_stream::<i8, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i8))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I16 => self.build_output_stream::<i16, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i16))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I32 => self.build_output_stream::<i32, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i32))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::I64 => self.build_output_stream::<i64, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut()
-                        .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0i64))
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U8 => self.build_output_stream::<u8, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut().for_each(|d| {
-                        *d = mixer_rx
-                            .next()
-                            .map(Sample::from_sample)
-                            .unwrap_or(u8::MAX / 2)
-                    })
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U16 => self.build_output_stream::<u16, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut().for_each(|d| {
-                        *d = mixer_rx
-                            .next()
-                            .map(Sample::from_sample)
-                            .unwrap_or(u16::MAX / 2)
-                    })
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U32 => self.build_output_stream::<u32, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut().for_each(|d| {
-                        *d = mixer_rx
-                            .next()
-                            .map(Sample::from_sample)
-                            .unwrap_or(u32::MAX / 2)
-                    })
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             cpal::SampleFormat::U64 => self.build_output_stream::<u64, _, _>(
-                &format.config(),
-                move |data, _| {
-                    data.iter_mut().for_each(|d| {
-                        *d = mixer_rx
-                            .next()
-                            .map(Sample::from_sample)
-                            .unwrap_or(u64::MAX / 2)
-                    })
-                },
+                &config,
+                move |data, _| output.fill(data),
                 error_callback,
                 None,
             ),
             _ => return Err(cpal::BuildStreamError::StreamConfigNotSupported),
         }
-        .map(|stream| (mixer_tx, stream))
+        .map(|stream| (mixer_tx, controls, format, stream))
     }
 
     fn try_new_output_stream_config(
         &self,
         config: SupportedStreamConfig,
-    ) -> Result<(Arc<DynamicMixerController<f32>>, cpal::Stream), StreamError> {
-        self.new_output_stream_with_format(config).or_else(|err| {
-            // look through all supported formats to see if another works
-            supported_output_formats(self)?
-                .find_map(|format| self.new_output_stream_with_format(format).ok())
-                // return original error if nothing works
-                .ok_or(StreamError::BuildStreamError(err))
-        })
+        buffer_size: cpal::BufferSize,
+    ) -> Result<StreamParts, StreamError> {
+        open_with_fallback(
+            config,
+            || {
+                self.supported_output_configs()
+                    .map(Iterator::collect)
+                    .map_err(StreamError::SupportedStreamConfigsError)
+            },
+            |format| self.new_output_stream_with_format(format, buffer_size),
+        )
     }
 }
 
+/// Opens `config`, or if that fails the first of the `supported` configs that opens.
+fn open_with_fallback<T>(
+    config: SupportedStreamConfig,
+    supported: impl FnOnce() -> Result<Vec<cpal::SupportedStreamConfigRange>, StreamError>,
+    mut open: impl FnMut(SupportedStreamConfig) -> Result<T, cpal::BuildStreamError>,
+) -> Result<T, StreamError> {
+    open(config).or_else(|err| {
+        // look through all supported formats to see if another works
+        supported_output_formats(supported()?)
+            .find_map(|format| open(format).ok())
+            // return original error if nothing works
+            .ok_or(StreamError::BuildStreamError(err))
+    })
+}
+
 /// All the supported output formats with sample rates
 fn supported_output_formats(
-    device: &cpal::Device,
-) -> Result<impl Iterator<Item = cpal::SupportedStreamConfig>, StreamError> {
+    mut supported: Vec<cpal::SupportedStreamConfigRange>,
+) -> impl Iterator<Item = cpal::SupportedStreamConfig> {
     const HZ_44100: cpal::SampleRate = cpal::SampleRate(44_100);
 
-    let mut supported: Vec<_> = device
-        .supported_output_configs()
-        .map_err(StreamError::SupportedStreamConfigsError)?
-        .collect();
     supported.sort_by(|a, b| b.cmp_default_heuristics(a));
 
-    Ok(supported.into_iter().flat_map(|sf| {
+    supported.into_iter().flat_map(|sf| {
         let max_rate = sf.max_sample_rate();
         let min_rate = sf.min_sample_rate();
         let mut formats = vec![sf.with_max_sample_rate()];
@@ -371,5 +948,320 @@ fn supported_output_formats(
         }
         formats.push(sf.with_sample_rate(min_rate));
         formats
-    }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{fill_native, OutputControls, OutputProcessor, PostProcessSlot};
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::meter::{ChannelLevel, MeterHandle};
+    use crate::source::{SineWave, Source};
+
+    #[test]
+    fn tap_receives_played_samples() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let tap = controls.taps.tap();
+
+        mixer.add(SineWave::new(440.0).amplify(0.5));
+        let mut device = [0i16; 64];
+        output.fill(&mut device);
+
+        let tapped = tap.try_recv().unwrap();
+        let expected: Vec<f32> = SineWave::new(440.0).amplify(0.5).take(64).collect();
+        assert_eq!(tapped, expected);
+        let converted: Vec<i16> = tapped
+            .iter()
+            .map(|s| cpal::Sample::from_sample(*s))
+            .collect();
+        assert_eq!(&device[..], &converted[..]);
+
+        drop(output);
+        assert_eq!(tap.recv(), None);
+    }
+
+    #[test]
+    fn lagging_tap_counts_dropped_buffers() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let tap = controls.taps.tap();
+
+        mixer.add(SamplesBuffer::new(
+            1,
+            48000,
+            (0..40).map(|i| i as f32).collect::<Vec<_>>(),
+        ));
+        let mut device = [0f32; 1];
+        for _ in 0..40 {
+            output.fill(&mut device);
+        }
+        assert_eq!(tap.dropped(), 8);
+
+        // The newest buffers are kept, the oldest were dropped to make room for them.
+        assert_eq!(tap.try_recv(), Some(vec![8.0]));
+        // Every received buffer makes room for another without dropping.
+        output.fill(&mut device);
+        assert_eq!(tap.dropped(), 8);
+        let received: Vec<Vec<f32>> = std::iter::from_fn(|| tap.try_recv()).collect();
+        assert_eq!(received.len(), 32);
+        assert_eq!(received[0], vec![9.0]);
+        assert_eq!(received[30], vec![39.0]);
+        assert_eq!(received[31], vec![0.0]);
+    }
+
+    #[test]
+    fn starved_mixer_counts_underruns() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+
+        // Nothing is playing yet.
+        output.fill_f32(32);
+        assert_eq!(controls.underruns.load(Ordering::Relaxed), 0);
+
+        // A sound that ends halfway through a buffer is not an underrun.
+        mixer.add(SineWave::new(440.0).take_duration(Duration::from_millis(1)));
+        output.fill_f32(32);
+        output.fill_f32(32);
+        assert_eq!(controls.underruns.load(Ordering::Relaxed), 0);
+
+        // A stream that should play on runs dry halfway through this buffer.
+        mixer.add(RunsDry(48));
+        output.fill_f32(32);
+        assert_eq!(controls.underruns.load(Ordering::Relaxed), 0);
+        output.fill_f32(32);
+        assert_eq!(controls.underruns.load(Ordering::Relaxed), 1);
+        // Idle again.
+        output.fill_f32(32);
+        assert_eq!(controls.underruns.load(Ordering::Relaxed), 1);
+    }
+
+    /// Claims to be infinite but only has this many samples.
+    struct RunsDry(usize);
+
+    impl Iterator for RunsDry {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.0 = self.0.checked_sub(1)?;
+            Some(0.5)
+        }
+    }
+
+    impl Source for RunsDry {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            48000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+
+        fn is_infinite(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn post_process_changes_output() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        controls.set_post_process(Box::new(|samples, channels, rate| {
+            assert_eq!((channels, rate), (2, 48000));
+            samples.iter_mut().for_each(|s| *s *= 0.5);
+        }));
+
+        mixer.add(SineWave::new(440.0));
+        let mut device = [0.0f32; 64];
+        output.fill(&mut device);
+
+        let expected: Vec<f32> = SineWave::new(440.0)
+            .amplify(0.5)
+            .flat_map(|s| [s, s])
+            .take(64)
+            .collect();
+        assert_eq!(&device[..], &expected[..]);
+    }
+
+    #[test]
+    fn sample_format_follows_the_opened_config() {
+        use cpal::{SampleFormat, SampleRate, SupportedBufferSize, SupportedStreamConfig};
+        use cpal::{BuildStreamError, SupportedStreamConfigRange};
+
+        use super::{open_with_fallback, StreamError};
+
+        let config = |format| {
+            SupportedStreamConfig::new(
+                2,
+                SampleRate(48000),
+                SupportedBufferSize::Unknown,
+                format,
+            )
+        };
+        let supported = || {
+            Ok(vec![SupportedStreamConfigRange::new(
+                2,
+                SampleRate(44100),
+                SampleRate(48000),
+                SupportedBufferSize::Unknown,
+                SampleFormat::I16,
+            )])
+        };
+        // Stands in for a device that only takes integer samples.
+        let open = |config: SupportedStreamConfig| match config.sample_format() {
+            SampleFormat::I16 => Ok(config),
+            _ => Err(BuildStreamError::StreamConfigNotSupported),
+        };
+
+        let opened = open_with_fallback(config(SampleFormat::I16), supported, open).unwrap();
+        assert_eq!(opened.sample_format(), SampleFormat::I16);
+        // The f32 config is rejected, the stream falls back to a supported i16 one.
+        let opened = open_with_fallback(config(SampleFormat::F32), supported, open).unwrap();
+        assert_eq!(opened.sample_format(), SampleFormat::I16);
+
+        let opened = open_with_fallback(config(SampleFormat::F32), supported, Ok).unwrap();
+        assert_eq!(opened.sample_format(), SampleFormat::F32);
+        // Nothing opens, the error of the requested config is returned.
+        let result = open_with_fallback(config(SampleFormat::F32), || Ok(Vec::new()), open);
+        assert!(matches!(
+            result,
+            Err(StreamError::BuildStreamError(
+                BuildStreamError::StreamConfigNotSupported
+            ))
+        ));
+    }
+
+    #[test]
+    fn output_is_converted_to_the_sample_format() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let mut output = OutputProcessor::new(mixer_rx, Arc::new(OutputControls::default()));
+        mixer.add(SamplesBuffer::new(1, 48000, vec![0.5f32, -1.0, 0.5, -1.0]));
+
+        let mut device_f32 = [0.0f32; 2];
+        output.fill(&mut device_f32);
+        assert_eq!(device_f32, [0.5, -1.0]);
+        let mut device_i16 = [0i16; 2];
+        output.fill(&mut device_i16);
+        assert_eq!(device_i16, [16384, i16::MIN]);
+    }
+
+    #[test]
+    fn replaced_post_process_is_dropped_by_the_user_thread() {
+        let (_mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let first = Arc::new(());
+        let captured = first.clone();
+        controls.set_post_process(Box::new(move |_, _, _| {
+            let _ = &captured;
+        }));
+        output.fill_f32(32);
+        controls.set_post_process(Box::new(|_, _, _| {}));
+        output.fill_f32(32);
+
+        // The audio callback handed the first post-processor back.
+        assert!(matches!(
+            *controls.post_process.lock().unwrap(),
+            PostProcessSlot::Retired(_)
+        ));
+        assert_eq!(Arc::strong_count(&first), 2);
+        controls.set_post_process(Box::new(|_, _, _| {}));
+        assert_eq!(Arc::strong_count(&first), 1);
+    }
+
+    #[cfg(feature = "wav")]
+    #[test]
+    fn handle_records_at_the_stream_format() {
+        use super::OutputStreamHandle;
+        use crate::RecordingFormat;
+
+        let path = std::env::temp_dir().join("rodio-handle-recording-test.wav");
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(2, 44100);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&mixer),
+            controls: Arc::downgrade(&controls),
+        };
+        let recording = handle
+            .start_recording_with_format(&path, RecordingFormat::I16)
+            .unwrap();
+
+        handle.play_raw(SineWave::new(440.0)).unwrap();
+        let mut device = [0.0f32; 882];
+        output.fill(&mut device);
+        recording.stop().unwrap();
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 44100);
+        assert_eq!(reader.spec().bits_per_sample, 16);
+        assert_eq!(reader.duration(), 441);
+        std::fs::remove_file(path).unwrap();
+
+        drop((output, mixer, controls));
+        let error = handle.start_recording(std::env::temp_dir().join("unused.wav"));
+        assert_eq!(
+            error.err().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotConnected)
+        );
+    }
+
+    #[test]
+    fn native_output_mixes_integers() {
+        let (mixer, mut mixer_rx) = dynamic_mixer::mixer::<i16>(1, 48000);
+        mixer.add(SamplesBuffer::new(1, 48000, vec![1000i16; 4]));
+        mixer.add(SamplesBuffer::new(1, 48000, vec![-300i16, 32000, 32000, 5]));
+        let mut device = [1i16; 6];
+        fill_native(&mut mixer_rx, &mut device);
+        // Sums saturate at full scale, silence follows once the sources ended.
+        assert_eq!(device, [700, i16::MAX, i16::MAX, 1005, 0, 0]);
+    }
+
+    #[test]
+    fn meter_reads_levels_per_channel() {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(2, 48000);
+        let controls = Arc::new(OutputControls::default());
+        let mut output = OutputProcessor::new(mixer_rx, controls.clone());
+        let meter = MeterHandle {
+            controls: Arc::downgrade(&controls),
+        };
+
+        mixer.add(SamplesBuffer::new(
+            2,
+            48000,
+            vec![0.5f32, 0.0, -0.5, 0.25, 0.5, 0.0, -0.5, -0.25],
+        ));
+        output.fill_f32(8);
+        let levels = meter.read();
+        assert_eq!(levels.len(), 2);
+        assert_eq!((levels[0].peak, levels[0].rms), (0.5, 0.5));
+        assert_eq!(levels[1].peak, 0.25);
+        assert!((levels[1].rms - 0.25 / 2f32.sqrt()).abs() < 1e-6);
+
+        // Only the last buffer counts.
+        output.fill_f32(8);
+        assert_eq!(meter.read()[0], ChannelLevel::default());
+
+        drop(output);
+        drop(controls);
+        assert!(meter.read().is_empty());
+    }
 }
@@ -0,0 +1,102 @@
+//! Starts several sources or sinks on the same sample.
+
+use cpal::FromSample;
+
+use crate::stream::{OutputStreamHandle, PlayError};
+use crate::{Sample, Sink, Source};
+
+/// Collects sources and sinks that must start playing together.
+///
+/// Nothing is played until [`play`](SyncGroup::play) is called. All members of the group are
+/// then handed to the output mixer at once and begin on the same mixer sample, which keeps
+/// them sample-aligned for as long as they play (for example a music track and a metronome).
+///
+/// The group start itself is only as precise as the output buffer: the members begin within
+/// one device buffer of the call to `play`, but always on the same sample relative to each
+/// other. Sources are still decoded on the audio thread as they play, so a source that decodes
+/// slowly delays the whole mixer, but it never shifts the members against each other.
+pub struct SyncGroup {
+    handle: OutputStreamHandle,
+    sources: Vec<Box<dyn Source<Item = f32> + Send>>,
+}
+
+impl SyncGroup {
+    /// Builds a new, empty group playing on the given stream.
+    pub fn new(handle: &OutputStreamHandle) -> SyncGroup {
+        SyncGroup {
+            handle: handle.clone(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Adds a source to the group.
+    pub fn add<S>(&mut self, source: S)
+    where
+        S: Source + Send + 'static,
+        f32: FromSample<S::Item>,
+        S::Item: Sample + Send,
+    {
+        self.sources.push(Box::new(source.convert_samples()));
+    }
+
+    /// Returns a new `Sink` whose playback starts together with the rest of the group.
+    ///
+    /// Sounds appended to the sink before [`play`](SyncGroup::play) is called start on the
+    /// group's first sample.
+    pub fn add_sink(&mut self) -> Sink {
+        let (sink, queue_rx) = Sink::new_idle();
+        self.sources.push(Box::new(queue_rx));
+        sink
+    }
+
+    /// Returns the number of sources and sinks in the group.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns true if nothing was added to the group.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Starts every member of the group on the same sample.
+    pub fn play(self) -> Result<(), PlayError> {
+        let mixer = self.handle.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer.add_synchronized(self.sources);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Weak};
+
+    use crate::buffer::SamplesBuffer;
+    use crate::dynamic_mixer;
+    use crate::stream::OutputStreamHandle;
+    use crate::SyncGroup;
+
+    #[test]
+    fn grouped_sources_start_on_same_sample() {
+        let (mixer, mut rx) = dynamic_mixer::mixer::<f32>(1, 48000);
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&mixer),
+            controls: Weak::new(),
+        };
+
+        let mut group = SyncGroup::new(&handle);
+        group.add(SamplesBuffer::new(1, 48000, vec![1.0f32, 0.0, 0.0]));
+        let sink = group.add_sink();
+        sink.append(SamplesBuffer::new(1, 48000, vec![2.0f32, 0.0, 0.0]));
+        assert_eq!(group.len(), 2);
+
+        // Nothing plays before the group is started.
+        assert_eq!(rx.next(), None);
+
+        group.play().unwrap();
+        assert_eq!(rx.next(), Some(3.0));
+        assert_eq!(rx.next(), Some(0.0));
+    }
+}
@@ -0,0 +1,206 @@
+//! Copies of the mixed output as it is sent to the device.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Number of buffers an [`OutputTap`] holds before it starts dropping new ones.
+const TAP_CAPACITY: usize = 32;
+
+/// Number of samples a buffer of an [`OutputTap`] holds at most.
+const TAP_BUFFER_LEN: usize = 8192;
+
+/// Receives a copy of every buffer the output stream sends to the device.
+///
+/// The buffers contain the mixed `f32` samples of all playing sources, interleaved with the
+/// channel count of the stream, right before they are converted to the device's sample format.
+/// Device buffers longer than 8192 samples arrive split over several buffers.
+///
+/// The audio thread never waits for the tap and never allocates for it: the samples are copied
+/// into buffers allocated up front, and every received buffer is replaced by a new one on the
+/// receiving thread. The tap holds up to 32 buffers; if the consumer falls behind, the oldest
+/// buffer is dropped to make room for each new one, so the tap always holds the latest audio.
+/// [`dropped`](OutputTap::dropped) counts them, so a gap in the received audio can be detected.
+pub struct OutputTap {
+    empty: SyncSender<Vec<f32>>,
+    shared: Arc<TapShared>,
+}
+
+struct TapShared {
+    // Shared with the audio thread so it can drop the oldest buffer. The receiving thread
+    // holds the lock while it waits, which only happens while the tap is empty.
+    filled: Mutex<Receiver<Vec<f32>>>,
+    dropped: AtomicUsize,
+    closed: AtomicBool,
+}
+
+/// Reads the number of buffers an [`OutputTap`] dropped.
+pub(crate) struct TapDrops(Arc<TapShared>);
+
+impl TapDrops {
+    pub(crate) fn count(&self) -> usize {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// The audio thread's end of an [`OutputTap`].
+struct TapSender {
+    filled: SyncSender<Vec<f32>>,
+    empty: Receiver<Vec<f32>>,
+    shared: Arc<TapShared>,
+}
+
+impl OutputTap {
+    /// Blocks until the next buffer is available.
+    ///
+    /// Returns `None` once the output stream has been dropped and all buffers were received.
+    pub fn recv(&self) -> Option<Vec<f32>> {
+        let buffer = self.shared.filled.lock().unwrap().recv().ok()?;
+        self.replace_buffer();
+        Some(buffer)
+    }
+
+    /// Like [`recv`](OutputTap::recv) but waits at most `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Vec<f32>> {
+        let buffer = self.shared.filled.lock().unwrap().recv_timeout(timeout).ok()?;
+        self.replace_buffer();
+        Some(buffer)
+    }
+
+    /// Returns the next buffer if one is available, without blocking.
+    pub fn try_recv(&self) -> Option<Vec<f32>> {
+        let buffer = self.shared.filled.lock().unwrap().try_recv().ok()?;
+        self.replace_buffer();
+        Some(buffer)
+    }
+
+    /// Returns true once the output stream has been dropped. Buffers sent before that can still
+    /// be received.
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of old buffers dropped so far to make room for new ones.
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns a handle to the count of [`dropped`](OutputTap::dropped) buffers that outlives
+    /// the tap.
+    pub(crate) fn drops(&self) -> TapDrops {
+        TapDrops(self.shared.clone())
+    }
+
+    // Hands the audio thread a new buffer for the one that was received.
+    fn replace_buffer(&self) {
+        let _ = self.empty.try_send(Vec::with_capacity(TAP_BUFFER_LEN));
+    }
+}
+
+impl TapSender {
+    /// Copies `samples` into the next free buffer, or the oldest filled one if there is none.
+    /// Returns false once the tap was dropped.
+    fn send(&self, samples: &[f32]) -> bool {
+        let mut buffer = match self.empty.try_recv() {
+            Ok(buffer) => buffer,
+            Err(TryRecvError::Disconnected) => return false,
+            // All buffers are filled, the consumer lags.
+            Err(TryRecvError::Empty) => {
+                self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                let oldest = self
+                    .shared
+                    .filled
+                    .try_lock()
+                    .ok()
+                    .and_then(|filled| filled.try_recv().ok());
+                // Only while the consumer is taking a buffer, then the new one is dropped.
+                let Some(oldest) = oldest else {
+                    return true;
+                };
+                oldest
+            }
+        };
+        buffer.clear();
+        buffer.extend_from_slice(samples);
+        // Can not fail, there are no more buffers than the tap holds and the receiver is shared.
+        let _ = self.filled.try_send(buffer);
+        true
+    }
+}
+
+/// The taps registered on an output stream.
+pub(crate) struct OutputTaps {
+    register: Mutex<Sender<TapSender>>,
+    // Only locked by the audio thread and when the stream closes.
+    audio: Mutex<AudioTaps>,
+}
+
+struct AudioTaps {
+    registered: Receiver<TapSender>,
+    taps: Vec<TapSender>,
+}
+
+impl Default for OutputTaps {
+    fn default() -> Self {
+        let (register, registered) = channel();
+        OutputTaps {
+            register: Mutex::new(register),
+            audio: Mutex::new(AudioTaps {
+                registered,
+                taps: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl OutputTaps {
+    /// Registers a new tap, it receives buffers from the next one the stream plays on.
+    pub(crate) fn tap(&self) -> OutputTap {
+        let (filled_tx, filled_rx) = sync_channel(TAP_CAPACITY);
+        let (empty_tx, empty_rx) = sync_channel(TAP_CAPACITY);
+        for _ in 0..TAP_CAPACITY {
+            let _ = empty_tx.try_send(Vec::with_capacity(TAP_BUFFER_LEN));
+        }
+        let shared = Arc::new(TapShared {
+            filled: Mutex::new(filled_rx),
+            dropped: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        });
+        let sender = TapSender {
+            filled: filled_tx,
+            empty: empty_rx,
+            shared: shared.clone(),
+        };
+        if self.register.lock().unwrap().send(sender).is_err() {
+            shared.closed.store(true, Ordering::Release);
+        }
+        OutputTap {
+            empty: empty_tx,
+            shared,
+        }
+    }
+
+    /// Sends a copy of `samples` to every tap. Never blocks, and only allocates to make room
+    /// for newly registered taps.
+    pub(crate) fn send(&self, samples: &[f32]) {
+        // Only contended while the stream is closing.
+        let Ok(mut audio) = self.audio.try_lock() else {
+            return;
+        };
+        let AudioTaps { registered, taps } = &mut *audio;
+        taps.extend(registered.try_iter());
+        // Forget about taps whose receiver was dropped.
+        taps.retain(|tap| samples.chunks(TAP_BUFFER_LEN).all(|chunk| tap.send(chunk)));
+    }
+
+    /// Signals all taps that no more buffers will be sent.
+    pub(crate) fn close(&self) {
+        let mut audio = self.audio.lock().unwrap();
+        let AudioTaps { registered, taps } = &mut *audio;
+        taps.extend(registered.try_iter());
+        for tap in taps.drain(..) {
+            tap.shared.closed.store(true, Ordering::Release);
+        }
+    }
+}
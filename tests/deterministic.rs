@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use rodio::buffer::SamplesBuffer;
+use rodio::deterministic::set_deterministic;
+use rodio::dynamic_mixer;
+use rodio::source::{SineWave, Source};
+
+/// Renders a chain that resamples, filters and mixes, with noise if it is available.
+fn render() -> Vec<u8> {
+    let (mixer, output) = dynamic_mixer::mixer::<f32>(2, 44100);
+    mixer.add(
+        SineWave::new(440.0)
+            .take_duration(Duration::from_millis(100))
+            .low_pass(2000.0)
+            .fade_in(Duration::from_millis(20)),
+    );
+    mixer.add(SamplesBuffer::new(1, 22050, vec![0.25f32; 1000]).speed(1.3));
+    #[cfg(feature = "noise")]
+    {
+        mixer.add(
+            rodio::source::white(cpal::SampleRate(48000))
+                .amplify(0.1)
+                .take_duration(Duration::from_millis(100)),
+        );
+        mixer.add(
+            rodio::source::pink(cpal::SampleRate(44100))
+                .amplify(0.1)
+                .take_duration(Duration::from_millis(100)),
+        );
+    }
+    output.take(44100 / 5).flat_map(f32::to_le_bytes).collect()
+}
+
+#[test]
+fn deterministic_renders_are_identical() {
+    set_deterministic(true);
+    let first = render();
+    set_deterministic(true);
+    let second = render();
+    assert_eq!(first, second);
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::buffer::SamplesBuffer;
+use rodio::source::{EqFilterType, ShutdownToken, Side, SineWave};
+use rodio::{ChannelLayout, Source};
+use rstest::rstest;
+
+type Adapter = fn(SamplesBuffer<f32>) -> Box<dyn Source<Item = f32>>;
+
+const MS: Duration = Duration::from_millis(10);
+
+/// Helps inferring the types of the closures in the test cases.
+fn adapter(adapter: Adapter) -> Adapter {
+    adapter
+}
+
+fn empty() -> SamplesBuffer<f32> {
+    SamplesBuffer::new(1, 48000, Vec::<f32>::new())
+}
+
+#[rstest]
+#[case::buffered(adapter(|s| Box::new(s.buffered())))]
+#[case::remap_channels(adapter(|s| Box::new(s.remap_channels(ChannelLayout::Mono, ChannelLayout::Stereo))))]
+#[case::mix(adapter(|s| Box::new(s.mix(empty()))))]
+#[case::repeat_infinite(adapter(|s| Box::new(s.repeat_infinite())))]
+#[case::repeat_infinite_crossfaded(adapter(|s| Box::new(s.repeat_infinite_crossfaded(MS))))]
+#[case::loop_region(adapter(|s| Box::new(s.loop_region(0..u64::MAX, MS))))]
+#[case::take_duration(adapter(|s| Box::new(s.take_duration(MS))))]
+#[case::haas(adapter(|s| Box::new(s.haas(MS, Side::Left))))]
+#[case::delay(adapter(|s| Box::new(s.delay(MS))))]
+#[case::skip_duration(adapter(|s| Box::new(s.skip_duration(MS))))]
+#[case::split_stereo(adapter(|s| Box::new(s.remap_channels(ChannelLayout::Mono, ChannelLayout::Stereo)
+        .split_stereo()
+        .0)))]
+#[case::stabilize_rate(adapter(|s| Box::new(s.stabilize_rate(44100))))]
+#[case::amplify(adapter(|s| Box::new(s.amplify(0.5))))]
+#[case::automatic_gain_control(adapter(|s| Box::new(s.automatic_gain_control(1.0, 4.0, 0.005, 5.0))))]
+#[case::take_crossfade_with(adapter(|s| Box::new(s.take_crossfade_with(empty(), MS))))]
+#[case::crossfade_into(adapter(|s| Box::new(s.crossfade_into(empty(), MS).unwrap())))]
+#[case::fade_in(adapter(|s| Box::new(s.fade_in(MS))))]
+#[case::fade_out(adapter(|s| Box::new(s.fade_out(MS))))]
+#[case::auto_fade_out(adapter(|s| Box::new(s.auto_fade_out(MS))))]
+#[case::fadeable(adapter(|s| Box::new(s.fadeable(MS).0)))]
+#[case::adsr(adapter(|s| Box::new(s.adsr(MS, MS, 0.5, MS).0)))]
+#[case::linear_gain_ramp(adapter(|s| Box::new(s.linear_gain_ramp(MS, 0.0, 1.0, true))))]
+#[case::periodic_access(adapter(|s| Box::new(s.periodic_access(MS, |_| ()))))]
+#[case::speed(adapter(|s| Box::new(s.speed(1.5))))]
+#[case::reverb(adapter(|s| Box::new(s.buffered().reverb(MS, 0.5))))]
+#[case::sanitize(adapter(|s| Box::new(s.sanitize())))]
+#[case::slew_limit(adapter(|s| Box::new(s.slew_limit(0.1))))]
+#[case::with_cues(adapter(|s| Box::new(s.with_cues(vec![(Duration::ZERO, ())]).0)))]
+#[case::modulate_by(adapter(|s| Box::new(s.modulate_by(SineWave::new(5.0)))))]
+#[case::clip_detect(adapter(|s| Box::new(s.clip_detect().0)))]
+#[case::pan(adapter(|s| Box::new(s.pan(0.5))))]
+#[case::pausable(adapter(|s| Box::new(s.pausable(false))))]
+#[case::gated_by(adapter(|s| Box::new(s.gated_by(Arc::new(AtomicBool::new(true)), false))))]
+#[case::stoppable(adapter(|s| Box::new(s.stoppable())))]
+#[case::skippable(adapter(|s| Box::new(s.skippable())))]
+#[case::with_shutdown(adapter(|s| Box::new(s.with_shutdown(ShutdownToken::new()))))]
+#[case::track_position(adapter(|s| Box::new(s.track_position())))]
+#[case::low_pass(adapter(|s| Box::new(s.low_pass(1000.0))))]
+#[case::high_pass(adapter(|s| Box::new(s.high_pass(1000.0))))]
+#[case::biquad(adapter(|s| Box::new(s.biquad(EqFilterType::Peaking, 1000.0, 1.0, 6.0))))]
+#[case::pitch_shift(adapter(|s| Box::new(s.pitch_shift(3.0))))]
+#[case::stretch(adapter(|s| Box::new(s.stretch(1.5))))]
+#[case::normalize(adapter(|s| Box::new(s.normalize(-16.0))))]
+#[case::normalize_peak(adapter(|s| Box::new(s.normalize_peak(1.0))))]
+#[case::limit(adapter(|s| Box::new(s.limit(1.0, MS))))]
+#[case::echo(adapter(|s| Box::new(s.echo(MS, 0.5, 0.5))))]
+#[case::waveshape(adapter(|s| Box::new(s.waveshape(f32::tanh))))]
+#[case::with_buffer_health(adapter(|s| Box::new(s.with_buffer_health(MS).0)))]
+fn empty_source_stays_empty(#[case] adapter: Adapter) {
+    let mut source = adapter(empty());
+    assert_eq!(source.by_ref().take(1000).count(), 0);
+    assert!(!source.is_infinite());
+}
@@ -24,3 +24,14 @@ fn test_flac_encodings() {
         assert_eq!(decoder.total_duration(), Some(Duration::from_secs(3)));
     }
 }
+
+#[test]
+fn test_flac_codec_info() {
+    let file = std::fs::File::open("assets/audacity24bit_level5.flac").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let info = decoder.codec_info();
+    assert_eq!(info.codec, "flac");
+    assert_eq!(info.bits_per_sample, Some(24));
+    assert_eq!(info.channels, 1);
+    assert_eq!(info.sample_rate, 44100);
+}
@@ -0,0 +1,139 @@
+use std::io::BufReader;
+
+use rodio::decoder::AudioMetadata;
+use rodio::Decoder;
+
+fn music_metadata(format: &str) -> AudioMetadata {
+    let file = std::fs::File::open(format!("assets/music.{format}")).unwrap();
+    let decoder = Decoder::new(BufReader::new(file)).unwrap();
+    decoder.metadata().clone()
+}
+
+#[test]
+#[cfg(feature = "symphonia-mp3")]
+fn mp3_id3_tags() {
+    let metadata = music_metadata("mp3");
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+    assert_eq!(metadata.album, None);
+    assert_eq!(metadata.picture, None);
+}
+
+#[test]
+#[cfg(feature = "flac")]
+fn flac_vorbis_comments() {
+    let metadata = music_metadata("flac");
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+    assert_eq!(metadata.picture, None);
+}
+
+#[test]
+#[cfg(feature = "opus")]
+fn opus_comments() {
+    let metadata = music_metadata("opus");
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+    assert_eq!(metadata.picture, None);
+}
+
+#[test]
+#[cfg(feature = "vorbis")]
+fn ogg_vorbis_comments() {
+    let metadata = music_metadata("ogg");
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    assert_eq!(metadata.artist.as_deref(), Some("RP and E Goldstein"));
+    // The file has an empty album comment.
+    assert_eq!(metadata.album, None);
+}
+
+#[test]
+#[cfg(feature = "wav")]
+fn wav_without_tags() {
+    assert_eq!(music_metadata("wav"), AudioMetadata::default());
+}
+
+#[test]
+#[cfg(all(feature = "wav", not(feature = "symphonia-wav")))]
+fn wav_info_list() {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut data = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut data, spec).unwrap();
+    writer.write_sample(0i16).unwrap();
+    writer.finalize().unwrap();
+    let mut data = data.into_inner();
+
+    let mut info = b"INFO".to_vec();
+    // Values are null terminated and padded to an even length.
+    for (id, value) in [
+        (b"INAM", &b"Song\0"[..]),
+        (b"IART", b"Band\0"),
+        (b"ITRK", b"7\0"),
+    ] {
+        info.extend_from_slice(id);
+        info.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        info.extend_from_slice(value);
+        if value.len() % 2 == 1 {
+            info.push(0);
+        }
+    }
+    data.extend_from_slice(b"LIST");
+    data.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    data.extend_from_slice(&info);
+    let riff_len = data.len() as u32 - 8;
+    data[4..8].copy_from_slice(&riff_len.to_le_bytes());
+
+    let decoder = Decoder::new(std::io::Cursor::new(data)).unwrap();
+    let metadata = decoder.metadata();
+    assert_eq!(metadata.title.as_deref(), Some("Song"));
+    assert_eq!(metadata.artist.as_deref(), Some("Band"));
+    assert_eq!(metadata.track_number, Some(7));
+    assert_eq!(metadata.album, None);
+}
+
+#[test]
+#[cfg(all(feature = "flac", not(feature = "symphonia-flac")))]
+fn flac_picture_block() {
+    let mut data = std::fs::read("assets/music.flac").unwrap();
+    let mut picture = Vec::new();
+    picture.extend(3u32.to_be_bytes()); // front cover
+    picture.extend(9u32.to_be_bytes());
+    picture.extend(b"image/png");
+    picture.extend(0u32.to_be_bytes()); // no description
+    picture.extend([0; 16]);
+    picture.extend(3u32.to_be_bytes());
+    picture.extend([1, 2, 3]);
+    let mut block = vec![6];
+    block.extend(&(picture.len() as u32).to_be_bytes()[1..]);
+    block.extend(picture);
+    // After the marker and the stream info block, which is never the last one here.
+    let at = 4 + 4 + 34;
+    data.splice(at..at, block);
+
+    let decoder = Decoder::new(std::io::Cursor::new(data)).unwrap();
+    let metadata = decoder.metadata();
+    assert_eq!(
+        metadata.title.as_deref(),
+        Some("Corelli Trio Sonata 11, m1")
+    );
+    let picture = metadata.picture.as_ref().unwrap();
+    assert_eq!(picture.media_type, "image/png");
+    assert_eq!(picture.data, vec![1, 2, 3]);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "opus")]
+
+use std::io::BufReader;
+use std::time::Duration;
+
+use rodio::Source;
+
+#[test]
+fn test_opus_decodes() {
+    // Stereo Opus file encoded from 48 kHz audio.
+    let file = std::fs::File::open("assets/music.opus").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    assert_eq!(decoder.channels(), 2);
+    assert_eq!(decoder.sample_rate(), 48000);
+    // File is not just silence
+    assert!(decoder.any(|x| x != 0));
+}
+
+#[test]
+fn test_opus_codec_info() {
+    let file = std::fs::File::open("assets/music.opus").unwrap();
+    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let info = decoder.codec_info();
+    assert_eq!(info.codec, "opus");
+    assert_eq!(info.container, Some("ogg"));
+    assert_eq!(info.bits_per_sample, None);
+}
+
+#[test]
+fn test_opus_seek_backwards_restarts() {
+    let open = || {
+        let file = std::fs::File::open("assets/music.opus").unwrap();
+        rodio::Decoder::new(BufReader::new(file)).unwrap()
+    };
+    let mut decoder = open();
+    decoder.try_seek(Duration::from_secs(5)).unwrap();
+    decoder.try_seek(Duration::ZERO).unwrap();
+    assert!(decoder.take(48000).eq(open().take(48000)));
+}
+
+#[test]
+fn test_opus_without_tags_is_an_error() {
+    // Keep only the first page, which holds the header but not the tags.
+    let data = std::fs::read("assets/music.opus").unwrap();
+    let segments = data[26] as usize;
+    let page_len = 27
+        + segments
+        + data[27..27 + segments]
+            .iter()
+            .map(|&s| s as usize)
+            .sum::<usize>();
+    let truncated = std::io::Cursor::new(data[..page_len].to_vec());
+    assert!(matches!(
+        rodio::Decoder::new(truncated),
+        Err(rodio::decoder::DecoderError::UnrecognizedFormat)
+    ));
+}
@@ -20,8 +20,13 @@ use rstest_reuse::{self, *};
 )]
 #[cfg_attr(
     all(feature = "flac", not(feature = "symphonia-flac")),
-    case("flac", false, "claxon")
+    case("flac", true, "claxon")
 )]
+#[cfg_attr(
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    case("ogg", true, "lewton")
+)]
+#[cfg_attr(feature = "opus", case("opus", true, "opus"))]
 #[cfg_attr(feature = "symphonia-mp3", case("mp3", true, "symphonia"))]
 // note: disabled, broken decoder see issue: #577
 #[cfg_attr(feature = "symphonia-isomp4", case("m4a", true, "symphonia"))]
@@ -42,6 +47,14 @@ fn all_decoders(
     all(feature = "wav", not(feature = "symphonia-wav")),
     case("wav", "hound")
 )]
+#[cfg_attr(
+    all(feature = "flac", not(feature = "symphonia-flac")),
+    case("flac", "claxon")
+)]
+#[cfg_attr(
+    all(feature = "vorbis", not(feature = "symphonia-vorbis")),
+    case("ogg", "lewton")
+)]
 #[cfg_attr(feature = "symphonia-mp3", case("mp3", "symphonia"))]
 // note: disabled, broken decoder see issue: #577
 // #[cfg_attr(feature = "symphonia-isomp4", case("m4a", "symphonia"))]
@@ -143,7 +156,7 @@ fn seek_does_not_break_channel_order(
 
         source.try_seek(beep_start + offset).unwrap();
         let samples: Vec<_> = source.by_ref().take(100).collect();
-        let channel0 = 0 + channel_offset;
+        let channel0 = channel_offset;
         assert!(
             is_silent(&samples, source.channels(), channel0),
             "channel0 should be silent, 
@@ -162,9 +175,9 @@ fn seek_does_not_break_channel_order(
     }
 }
 
-fn second_channel_beep_range<R: rodio::Source>(source: &mut R) -> std::ops::Range<usize>
+fn second_channel_beep_range<R>(source: &mut R) -> std::ops::Range<usize>
 where
-    R: Iterator<Item = f32>,
+    R: rodio::Source + Iterator<Item = f32>,
 {
     let channels = source.channels() as usize;
     let samples: Vec<f32> = source.by_ref().collect();
@@ -212,8 +225,7 @@ where
 fn is_silent(samples: &[f32], channels: u16, channel: usize) -> bool {
     assert_eq!(samples.len(), 100);
     let channel = samples.iter().skip(channel).step_by(channels as usize);
-    let volume =
-        channel.map(|s| s.abs()).sum::<f32>() as f32 / samples.len() as f32 * channels as f32;
+    let volume = channel.map(|s| s.abs()).sum::<f32>() / samples.len() as f32 * channels as f32;
 
     const BASICALLY_ZERO: f32 = 0.0001;
     volume < BASICALLY_ZERO
@@ -229,14 +241,12 @@ fn time_remaining(decoder: Decoder<impl Read + Seek>) -> Duration {
 fn get_music(format: &str) -> Decoder<impl Read + Seek> {
     let asset = Path::new("assets/music").with_extension(format);
     let file = std::fs::File::open(asset).unwrap();
-    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
-    decoder
+    rodio::Decoder::new(BufReader::new(file)).unwrap()
 }
 
 fn get_rl(format: &str) -> Decoder<impl Read + Seek> {
     let asset = Path::new("assets/RL").with_extension(format);
     println!("opening: {}", asset.display());
     let file = std::fs::File::open(asset).unwrap();
-    let decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
-    decoder
+    rodio::Decoder::new(BufReader::new(file)).unwrap()
 }
@@ -0,0 +1,58 @@
+use std::io::{BufReader, Read};
+
+use rodio::decoder::DecoderError;
+use rodio::{Decoder, Source};
+use rstest::rstest;
+
+/// A reader without `Seek`, like a network socket.
+struct Socket(Vec<u8>, usize);
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // Small reads, as they arrive from the network.
+        let len = buf.len().min(1000).min(self.0.len() - self.1);
+        buf[..len].copy_from_slice(&self.0[self.1..self.1 + len]);
+        self.1 += len;
+        Ok(len)
+    }
+}
+
+fn socket(asset: &str) -> Socket {
+    Socket(std::fs::read(format!("assets/{asset}")).unwrap(), 0)
+}
+
+#[rstest]
+#[cfg_attr(feature = "wav", case("music.wav"))]
+#[cfg_attr(feature = "flac", case("music.flac"))]
+#[cfg_attr(feature = "vorbis", case("music.ogg"))]
+#[cfg_attr(feature = "opus", case("music.opus"))]
+#[cfg_attr(feature = "symphonia-mp3", case("music.mp3"))]
+fn streams_decode_like_files(#[case] asset: &str) {
+    let file = std::fs::File::open(format!("assets/{asset}")).unwrap();
+    let from_file = Decoder::new(BufReader::new(file)).unwrap();
+    let from_stream = Decoder::new_streaming(socket(asset)).unwrap();
+    assert_eq!(from_stream.channels(), from_file.channels());
+    assert_eq!(from_stream.sample_rate(), from_file.sample_rate());
+    assert!(from_stream.eq(from_file), "{asset} decodes differently");
+}
+
+#[test]
+#[cfg(feature = "symphonia-mp3")]
+fn streams_seek_forward_only() {
+    let mut decoder = Decoder::new_streaming(socket("music.mp3")).unwrap();
+    decoder.try_seek(std::time::Duration::from_secs(1)).unwrap();
+    assert!(decoder.next().is_some());
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    let result = Decoder::new_streaming(Socket(vec![0; 5000], 0));
+    assert!(matches!(result, Err(DecoderError::UnrecognizedFormat)));
+}
+
+#[test]
+#[cfg(feature = "symphonia-isomp4")]
+fn mp4_with_trailing_index_is_not_streamable() {
+    let result = Decoder::new_streaming(socket("music.m4a"));
+    assert!(matches!(result, Err(DecoderError::NotStreamable)));
+}
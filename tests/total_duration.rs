@@ -0,0 +1,57 @@
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::Path;
+use std::time::Duration;
+
+use rodio::{Decoder, Source};
+use rstest::rstest;
+
+fn decoded_duration(decoder: Decoder<impl Read + Seek>) -> Duration {
+    let rate = decoder.sample_rate() as f64;
+    let channels = decoder.channels() as f64;
+    let samples = decoder.count() as f64;
+    Duration::from_secs_f64(samples / rate / channels)
+}
+
+fn open(asset: &str) -> Decoder<impl Read + Seek> {
+    let file = std::fs::File::open(Path::new("assets").join(asset)).unwrap();
+    Decoder::new(BufReader::new(file)).unwrap()
+}
+
+#[rstest]
+#[cfg_attr(feature = "wav", case("music.wav"))]
+#[cfg_attr(feature = "wav", case("RL.wav"))]
+#[cfg_attr(feature = "flac", case("music.flac"))]
+#[cfg_attr(feature = "flac", case("RL.flac"))]
+#[cfg_attr(feature = "vorbis", case("music.ogg"))]
+#[cfg_attr(feature = "vorbis", case("RL.ogg"))]
+#[cfg_attr(feature = "opus", case("music.opus"))]
+#[cfg_attr(feature = "symphonia-mp3", case("music.mp3"))]
+#[cfg_attr(feature = "symphonia-mp3", case("RL.mp3"))]
+fn total_duration_matches_decoded_length(#[case] asset: &str) {
+    let total = open(asset).total_duration().expect("duration is known");
+    let decoded = decoded_duration(open(asset));
+    assert!(
+        total.abs_diff(decoded) < Duration::from_millis(5),
+        "{asset}: reported {total:?}, decoded {decoded:?}"
+    );
+}
+
+#[test]
+#[cfg(feature = "symphonia-mp3")]
+fn constant_bitrate_mp3_without_header_is_estimated() {
+    let mut data = std::fs::read("assets/RL.mp3").unwrap();
+    // Disable the Info header, which states the number of frames.
+    let at = data.windows(4).position(|tag| tag == b"Info").unwrap();
+    data[at..at + 4].copy_from_slice(b"None");
+
+    let total = Decoder::new(Cursor::new(data.clone()))
+        .unwrap()
+        .total_duration()
+        .expect("duration is estimated");
+    let decoded = decoded_duration(Decoder::new(Cursor::new(data)).unwrap());
+    // Estimated from the bitrate of the first frames, so only roughly right.
+    assert!(
+        total.abs_diff(decoded) < decoded / 20,
+        "reported {total:?}, decoded {decoded:?}"
+    );
+}
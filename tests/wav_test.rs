@@ -1,3 +1,5 @@
+#![cfg(feature = "wav")]
+
 use std::io::BufReader;
 
 #[test]
@@ -32,3 +34,157 @@ fn test_wav_encodings() {
     let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
     assert!(decoder.any(|x| x != 0));
 }
+
+#[test]
+fn test_wav_bytes_consumed() {
+    let data = std::fs::read("assets/music.wav").unwrap();
+    let size = data.len() as u64;
+    let mut decoder = rodio::Decoder::new(std::io::Cursor::new(data)).unwrap();
+    assert_eq!(decoder.total_bytes(), Some(size));
+
+    let after_header = decoder.bytes_consumed();
+    assert!(after_header > 0);
+    decoder.by_ref().take(100_000).for_each(drop);
+    let after_some = decoder.bytes_consumed();
+    assert!(after_some > after_header);
+
+    decoder.by_ref().for_each(drop);
+    assert!(decoder.bytes_consumed() > after_some);
+    assert!(decoder.bytes_consumed() <= size);
+}
+
+#[test]
+fn test_wav_preview_quality() {
+    use rodio::decoder::DecodeQuality;
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/music.wav").unwrap();
+    let full = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let (channels, sample_rate) = (full.channels(), full.sample_rate());
+    let full_len = full.count();
+
+    let file = std::fs::File::open("assets/music.wav").unwrap();
+    let mut preview = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    preview.set_quality(DecodeQuality::Preview);
+    assert_eq!(preview.channels(), 1);
+    assert!(preview.sample_rate() <= 11025);
+    let decimation = (sample_rate / preview.sample_rate()) as usize;
+    assert!(decimation > 1);
+
+    // The preview hands out one sample per few frames of the half second after the start,
+    // then stops decoding.
+    let window = sample_rate as usize / 2;
+    assert!(full_len / channels as usize > window);
+    let preview_len = preview.by_ref().count();
+    assert_eq!(preview_len, window.div_ceil(decimation));
+    assert_eq!(preview.frames_decoded(), window as u64);
+
+    preview.try_seek(std::time::Duration::from_secs(1)).unwrap();
+    assert_eq!(preview.by_ref().count(), preview_len);
+
+    preview.try_seek(std::time::Duration::ZERO).unwrap();
+    preview.set_quality(DecodeQuality::Full);
+    assert_eq!(preview.channels(), channels);
+    assert_eq!(preview.sample_rate(), sample_rate);
+    assert_eq!(preview.count(), full_len);
+}
+
+#[test]
+fn test_wav_quality_changes_after_the_reported_frame() {
+    use rodio::decoder::DecodeQuality;
+    use rodio::Source;
+
+    let file = std::fs::File::open("assets/music.wav").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let channels = decoder.channels();
+    decoder.by_ref().take(3).for_each(drop);
+
+    let frame_len = decoder.current_frame_len().unwrap();
+    decoder.set_quality(DecodeQuality::Preview);
+    for _ in 0..frame_len {
+        assert_eq!(decoder.channels(), channels);
+        decoder.next().unwrap();
+    }
+    assert_eq!(decoder.channels(), 1);
+    assert!(decoder.current_frame_len().unwrap() > 0);
+}
+
+#[test]
+fn test_wav_frames_decoded() {
+    use rodio::decoder::DecodeQuality;
+    use rodio::Source;
+    use std::time::Duration;
+
+    let file = std::fs::File::open("assets/music.wav").unwrap();
+    let mut decoder = rodio::Decoder::new(BufReader::new(file)).unwrap();
+    let channels = decoder.channels() as usize;
+    assert_eq!(decoder.frames_decoded(), 0);
+
+    // A frame only counts once all of its samples were read.
+    decoder.by_ref().take(1000 * channels + 1).for_each(drop);
+    assert_eq!(decoder.frames_decoded(), 1000);
+
+    decoder.try_seek(Duration::from_millis(100)).unwrap();
+    assert_eq!(decoder.frames_decoded(), 0);
+    let remaining = decoder.by_ref().count();
+    assert_eq!(decoder.frames_decoded(), (remaining / channels) as u64);
+
+    // Previewing still counts every decoded frame.
+    decoder.try_seek(Duration::ZERO).unwrap();
+    decoder.set_quality(DecodeQuality::Preview);
+    let frames = decoder.by_ref().count() as u64;
+    assert!(decoder.frames_decoded() > frames);
+}
+
+/// Builds a mono 16 bit WAV file whose `smpl` chunk loops the given frames (end inclusive).
+fn wav_with_loop(samples: &[i16], loop_start: u32, loop_end: u32) -> Vec<u8> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut data = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut data, spec).unwrap();
+    for &sample in samples {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+    let mut data = data.into_inner();
+
+    let mut smpl = [0u32; 9 + 6];
+    smpl[7] = 1; // number of loops
+    smpl[9 + 2] = loop_start;
+    smpl[9 + 3] = loop_end;
+    data.extend_from_slice(b"smpl");
+    data.extend_from_slice(&(smpl.len() as u32 * 4).to_le_bytes());
+    data.extend(smpl.iter().flat_map(|value| value.to_le_bytes()));
+    let riff_len = data.len() as u32 - 8;
+    data[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_wav_loop_points() {
+    let samples: Vec<i16> = (0..10).map(|i| i * 1000).collect();
+    let data = wav_with_loop(&samples, 4, 7);
+    let decoder = rodio::Decoder::new(std::io::Cursor::new(data)).unwrap();
+    assert_eq!(decoder.loop_points(), Some(4..8));
+
+    let (sink, queue_rx) = rodio::Sink::new_idle();
+    sink.append_looped(decoder, std::time::Duration::ZERO);
+    let played: Vec<i16> = queue_rx
+        .take(16)
+        .map(|value| (value * 32768.0).round() as i16)
+        .collect();
+    let expected: Vec<i16> = [0, 1, 2, 3, 4, 5, 6, 7, 4, 5, 6, 7, 4, 5, 6, 7]
+        .iter()
+        .map(|i| i * 1000)
+        .collect();
+    assert_eq!(played, expected);
+
+    let plain = rodio::Decoder::new(std::io::Cursor::new(
+        std::fs::read("assets/music.wav").unwrap(),
+    ));
+    assert_eq!(plain.unwrap().loop_points(), None);
+}